@@ -1,24 +1,150 @@
 use std::collections::HashMap;
-use syn::{Lit, Meta, MetaNameValue, NestedMeta};
+use syn::{FnArg, Lit, Meta, MetaNameValue, NestedMeta, PatType, Signature};
 
 use crate::constants::{
-    DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, DEFAULT_MEMORY_SIZE, DEFAULT_STACK_SIZE,
+    DEFAULT_MAX_ADVICE_SIZE, DEFAULT_MAX_CYCLES, DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE,
+    DEFAULT_MEMORY_SIZE, DEFAULT_STACK_SIZE, RAM_START_ADDRESS,
 };
+use crate::rv_trace::{MemoryConfig, MemoryLayout};
+
+/// Polynomial commitment schemes selectable via the `pcs` attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitmentSchemeAttribute {
+    Hyrax,
+    HyperKZG,
+    Zeromorph,
+}
+
+/// Guest heap allocators selectable via the `allocator` attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AllocatorAttribute {
+    Bump,
+    FreeList,
+}
 
 pub struct Attributes {
     pub wasm: bool,
+    /// Whether only a hash of the function's inputs and outputs is public; see
+    /// `MemoryLayout::hash_io`.
+    pub hash_io: bool,
+    pub pcs: CommitmentSchemeAttribute,
+    /// Path to a custom `Jolt` VM to prove with, instead of `RV32IJoltVM`. It must implement
+    /// `Jolt<F, PCS, C, M>` for the `c` and `m` attributes, which default to those of
+    /// `RV32IJoltVM`.
+    pub vm: Option<String>,
+    pub c: Option<u64>,
+    pub m: Option<u64>,
+    pub allocator: AllocatorAttribute,
+    /// Address at which the guest's RAM starts; see `MemoryConfig::ram_start`.
+    pub ram_start: u64,
     pub memory_size: u64,
     pub stack_size: u64,
     pub max_input_size: u64,
     pub max_output_size: u64,
+    /// Size of the advice region, if set explicitly. Defaults to `DEFAULT_MAX_ADVICE_SIZE` for
+    /// functions with `#[private]` arguments, and to no advice region otherwise.
+    pub max_advice_size: Option<u64>,
+    pub max_cycles: u64,
+}
+
+impl Attributes {
+    /// Whether an argument is passed to the guest through the advice region rather than as a
+    /// public input: `#[private]` arguments are, as are all arguments of a function with hashed
+    /// I/O.
+    pub fn is_advice_arg(&self, is_private: bool) -> bool {
+        is_private || self.hash_io
+    }
+
+    /// Whether any of the arguments of the function with signature `sig` is passed as advice.
+    /// `sig` must still carry its `#[private]` attributes.
+    pub fn has_advice_args(&self, sig: &Signature) -> bool {
+        sig.inputs
+            .iter()
+            .any(|arg| self.is_advice_arg(is_private_arg(arg)))
+    }
+
+    /// Size of the advice region. The region is only needed if the function has arguments
+    /// passed as advice or uses hints, the latter of which must set `max_advice_size`
+    /// explicitly.
+    pub fn advice_size(&self, has_advice_args: bool) -> u64 {
+        match self.max_advice_size {
+            Some(size) => size,
+            None if has_advice_args => DEFAULT_MAX_ADVICE_SIZE,
+            None => 0,
+        }
+    }
+
+    /// The memory configuration the function's guest must be traced with.
+    pub fn memory_config(&self, has_advice_args: bool) -> MemoryConfig {
+        MemoryConfig {
+            ram_start: self.ram_start,
+            memory_size: self.memory_size,
+            stack_size: self.stack_size,
+            max_input_size: self.max_input_size,
+            max_output_size: self.max_output_size,
+            max_advice_size: self.advice_size(has_advice_args),
+        }
+    }
+
+    /// The memory layout of the function's guest. Its prover and verifier keys must be
+    /// generated with exactly this layout, as it is bound into the program digest.
+    pub fn memory_layout(&self, has_advice_args: bool) -> MemoryLayout {
+        let mut memory_layout = self.memory_config(has_advice_args).memory_layout();
+        memory_layout.hash_io = self.hash_io;
+        memory_layout
+    }
+}
+
+/// Whether a function argument is marked `#[private]`.
+pub fn is_private_arg(arg: &FnArg) -> bool {
+    match arg {
+        FnArg::Typed(PatType { attrs, .. }) => {
+            attrs.iter().any(|attr| attr.path.is_ident("private"))
+        }
+        FnArg::Receiver(_) => false,
+    }
 }
 
 pub fn parse_attributes(attr: &Vec<NestedMeta>) -> Attributes {
     let mut attributes = HashMap::<_, u64>::new();
     let mut wasm = false;
+    let mut hash_io = false;
+    let mut pcs = CommitmentSchemeAttribute::Hyrax;
+    let mut vm = None;
+    let mut allocator = AllocatorAttribute::Bump;
 
     for attr in attr {
         match attr {
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(lit),
+                ..
+            })) if path.is_ident("pcs") => {
+                pcs = match lit.value().to_lowercase().as_str() {
+                    "hyrax" => CommitmentSchemeAttribute::Hyrax,
+                    "hyperkzg" => CommitmentSchemeAttribute::HyperKZG,
+                    "zeromorph" => CommitmentSchemeAttribute::Zeromorph,
+                    _ => panic!("invalid pcs, expected one of: hyrax, hyperkzg, zeromorph"),
+                };
+            }
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(lit),
+                ..
+            })) if path.is_ident("vm") => {
+                vm = Some(lit.value());
+            }
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(lit),
+                ..
+            })) if path.is_ident("allocator") => {
+                allocator = match lit.value().to_lowercase().as_str() {
+                    "bump" => AllocatorAttribute::Bump,
+                    "free_list" => AllocatorAttribute::FreeList,
+                    _ => panic!("invalid allocator, expected one of: bump, free_list"),
+                };
+            }
             NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. })) => {
                 let value: u64 = match lit {
                     Lit::Int(lit) => lit.base10_parse().unwrap(),
@@ -26,20 +152,29 @@ pub fn parse_attributes(attr: &Vec<NestedMeta>) -> Attributes {
                 };
                 let ident = &path.get_ident().expect("Expected identifier");
                 match ident.to_string().as_str() {
+                    "ram_start" => attributes.insert("ram_start", value),
                     "memory_size" => attributes.insert("memory_size", value),
                     "stack_size" => attributes.insert("stack_size", value),
                     "max_input_size" => attributes.insert("max_input_size", value),
                     "max_output_size" => attributes.insert("max_output_size", value),
+                    "max_advice_size" => attributes.insert("max_advice_size", value),
+                    "max_cycles" => attributes.insert("max_cycles", value),
+                    "c" => attributes.insert("c", value),
+                    "m" => attributes.insert("m", value),
                     _ => panic!("invalid attribute"),
                 };
             }
             NestedMeta::Meta(Meta::Path(path)) if path.is_ident("wasm") => {
                 wasm = true;
             }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("hash_io") => {
+                hash_io = true;
+            }
             _ => panic!("expected integer literal"),
         }
     }
 
+    let ram_start = *attributes.get("ram_start").unwrap_or(&RAM_START_ADDRESS);
     let memory_size = *attributes
         .get("memory_size")
         .unwrap_or(&DEFAULT_MEMORY_SIZE);
@@ -50,12 +185,28 @@ pub fn parse_attributes(attr: &Vec<NestedMeta>) -> Attributes {
     let max_output_size = *attributes
         .get("max_output_size")
         .unwrap_or(&DEFAULT_MAX_OUTPUT_SIZE);
+    let max_advice_size = attributes.get("max_advice_size").copied();
+    let max_cycles = *attributes.get("max_cycles").unwrap_or(&DEFAULT_MAX_CYCLES);
+    let c = attributes.get("c").copied();
+    let m = attributes.get("m").copied();
+    if vm.is_none() && (c.is_some() || m.is_some()) {
+        panic!("the c and m attributes are only supported along with vm");
+    }
 
     Attributes {
         wasm,
+        hash_io,
+        pcs,
+        vm,
+        c,
+        m,
+        allocator,
+        ram_start,
         memory_size,
         stack_size,
         max_input_size,
         max_output_size,
+        max_advice_size,
+        max_cycles,
     }
 }
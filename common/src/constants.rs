@@ -12,20 +12,23 @@ pub const DEFAULT_MEMORY_SIZE: u64 = 10 * 1024 * 1024;
 pub const DEFAULT_STACK_SIZE: u64 = 4096;
 pub const DEFAULT_MAX_INPUT_SIZE: u64 = 4096;
 pub const DEFAULT_MAX_OUTPUT_SIZE: u64 = 4096;
+pub const DEFAULT_MAX_ADVICE_SIZE: u64 = 4096;
+pub const DEFAULT_MAX_CYCLES: u64 = 1 << 28;
+
+/// Maximum number of bytes of a guest's panic message kept in `JoltDevice::diagnostics`.
+pub const MAX_DIAGNOSTICS_SIZE: u64 = 1024;
+
+/// Size of the Keccak-256 digest that replaces the public inputs and outputs of a guest
+/// with hashed I/O; see `MemoryLayout::hash_io`.
+pub const IO_DIGEST_SIZE: u64 = 32;
 
-pub const fn memory_address_to_witness_index(address: u64, ram_witness_offset: u64) -> usize {
-    (address + ram_witness_offset - RAM_START_ADDRESS) as usize
-}
-pub const fn witness_index_to_memory_address(index: usize, ram_witness_offset: u64) -> u64 {
-    index as u64 + RAM_START_ADDRESS - ram_witness_offset
-}
 pub const fn virtual_register_index(index: u64) -> u64 {
     index + VIRTUAL_REGISTER_COUNT
 }
 
 // Layout of the witness (where || denotes concatenation):
-//     registers || inputs || outputs || panic || padding || RAM
+//     registers || inputs || outputs || panic || padding || advice || padding || RAM
 // Layout of VM memory:
-//     peripheral devices || inputs || outputs || panic || padding || RAM
+//     peripheral devices || inputs || outputs || panic || padding || advice || padding || RAM
 // Notably, we want to be able to map the VM memory address space to witness indices
-// using a constant shift, namely (RAM_WITNESS_OFFSET + RAM_START_ADDRESS)
+// using a constant shift, namely (ram_witness_offset - ram_start)
@@ -1,6 +1,10 @@
 use std::str::FromStr;
 
-use crate::constants::{MEMORY_OPS_PER_INSTRUCTION, RAM_START_ADDRESS, REGISTER_COUNT};
+use crate::constants::{
+    DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, DEFAULT_MEMORY_SIZE, DEFAULT_STACK_SIZE,
+    IO_DIGEST_SIZE, MAX_DIAGNOSTICS_SIZE, MEMORY_OPS_PER_INSTRUCTION, RAM_START_ADDRESS,
+    REGISTER_COUNT,
+};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use serde::{Deserialize, Serialize};
 use strum_macros::FromRepr;
@@ -222,11 +226,14 @@ pub struct ELFInstruction {
     pub rd: Option<u64>,
     pub imm: Option<u32>,
     /// If this instruction is part of a "virtual sequence" (see Section 6.2 of the
-    /// Jolt paper), then this contains the instruction's index within the sequence.
-    pub virtual_sequence_index: Option<usize>,
+    /// Jolt paper), then this contains the number of virtual instructions after this
+    /// one in the sequence. I.e. if this is the last instruction in the sequence,
+    /// `virtual_sequence_remaining` will be Some(0); if this is the penultimate instruction
+    /// in the sequence, `virtual_sequence_remaining` will be Some(1); etc.
+    pub virtual_sequence_remaining: Option<usize>,
 }
 
-pub const NUM_CIRCUIT_FLAGS: usize = 11;
+pub const NUM_CIRCUIT_FLAGS: usize = 12;
 
 impl ELFInstruction {
     #[rustfmt::skip]
@@ -241,8 +248,9 @@ impl ELFInstruction {
         // 6: Instruction writes lookup output to rd
         // 7: Sign-bit of imm
         // 8: Is concat
-        // 9: Increment virtual PC
+        // 9: Virtual instruction
         // 10: Assert instruction
+        // 11: Don't update PC
 
         let mut flags = [false; NUM_CIRCUIT_FLAGS];
 
@@ -330,23 +338,21 @@ impl ELFInstruction {
             | RV32IM::BLT
             | RV32IM::BGE
             | RV32IM::BLTU
-            | RV32IM::BGEU,
+            | RV32IM::BGEU
+            | RV32IM::VIRTUAL_ASSERT_EQ
+            | RV32IM::VIRTUAL_ASSERT_LTE
+            | RV32IM::VIRTUAL_ASSERT_VALID_SIGNED_REMAINDER
+            | RV32IM::VIRTUAL_ASSERT_VALID_UNSIGNED_REMAINDER
+            | RV32IM::VIRTUAL_ASSERT_VALID_DIV0,
         );
 
-        // TODO(moodlezoup): Use these flags in R1CS constraints
-        flags[9] = match self.virtual_sequence_index {
-            // For virtual sequences, we set
-            //     virtual PC := ProgARW     (the bytecode `a` value)
-            // if it's the first instruction in the sequence.
-            // Otherwise, we increment the virtual PC:
-            //     virtual PC += 1
-            // This prevents a malicious prover from reordering or omitting
-            // instructions from the virtual sequence.
-            Some(i) => i > 0,
-            // For "real" instructions, we always set
-            //     virtual PC := ProgARW     (the bytecode `a` value)
-            None => false
-        };
+        // Virtual instructions must be executed in order, so the R1CS
+        // constrains the virtual PC of the next instruction to be
+        //     virtual PC + 1
+        // This prevents a malicious prover from reordering or omitting
+        // instructions from the virtual sequence.
+        flags[9] = self.virtual_sequence_remaining.is_some();
+
         flags[10] = matches!(self.opcode,
             RV32IM::VIRTUAL_ASSERT_EQ                        |
             RV32IM::VIRTUAL_ASSERT_LTE                       |
@@ -355,6 +361,11 @@ impl ELFInstruction {
             RV32IM::VIRTUAL_ASSERT_VALID_DIV0,
         );
 
+        // All instructions in a virtual sequence share the ELF address of the
+        // instruction they replace, so the (real) PC should only be updated
+        // by the last instruction in the sequence.
+        flags[11] = matches!(self.virtual_sequence_remaining, Some(remaining) if remaining != 0);
+
         flags
     }
 }
@@ -598,27 +609,105 @@ impl RV32IM {
 /// all reads from the reserved memory address space for program inputs and all writes
 /// to the reserved memory address space for program outputs.
 /// The inputs and outputs are part of the public inputs to the proof.
-#[derive(
-    Debug, Clone, PartialEq, Serialize, Deserialize, CanonicalSerialize, CanonicalDeserialize,
-)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct JoltDevice {
     pub inputs: Vec<u8>,
     pub outputs: Vec<u8>,
     pub panic: bool,
+    /// Set when the guest writes to the termination address, signaling that it has halted.
+    pub termination: bool,
     pub memory_layout: MemoryLayout,
+    /// Debug output the guest wrote to the console address. This is not part of the
+    /// program I/O: it is not serialized with proofs and does not affect verification.
+    pub console: Vec<u8>,
+    /// Private inputs the guest can read from the advice region, followed by the responses to
+    /// its hint requests. Advice is untrusted: it is committed to by the prover but, unlike
+    /// `inputs`, never revealed to the verifier.
+    pub advice: Vec<u8>,
+    /// Bytes of the hint request the guest is currently writing to the hint address.
+    pub hint_request: Vec<u8>,
+    /// The panic message and location the guest wrote to the diagnostics address, up to
+    /// `MAX_DIAGNOSTICS_SIZE` bytes. Like `console`, this is not part of the program I/O.
+    pub diagnostics: Vec<u8>,
+}
+
+// Implemented by hand so that `console`, `advice`, `hint_request` and `diagnostics` are left
+// out of serialized proofs.
+impl CanonicalSerialize for JoltDevice {
+    fn serialize_with_mode<W: ark_serialize::Write>(
+        &self,
+        mut writer: W,
+        compress: ark_serialize::Compress,
+    ) -> Result<(), ark_serialize::SerializationError> {
+        self.inputs.serialize_with_mode(&mut writer, compress)?;
+        self.outputs.serialize_with_mode(&mut writer, compress)?;
+        self.panic.serialize_with_mode(&mut writer, compress)?;
+        self.termination
+            .serialize_with_mode(&mut writer, compress)?;
+        self.memory_layout
+            .serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: ark_serialize::Compress) -> usize {
+        self.inputs.serialized_size(compress)
+            + self.outputs.serialized_size(compress)
+            + self.panic.serialized_size(compress)
+            + self.termination.serialized_size(compress)
+            + self.memory_layout.serialized_size(compress)
+    }
+}
+
+impl CanonicalDeserialize for JoltDevice {
+    fn deserialize_with_mode<R: ark_serialize::Read>(
+        mut reader: R,
+        compress: ark_serialize::Compress,
+        validate: ark_serialize::Validate,
+    ) -> Result<Self, ark_serialize::SerializationError> {
+        Ok(Self {
+            inputs: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            outputs: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            panic: bool::deserialize_with_mode(&mut reader, compress, validate)?,
+            termination: bool::deserialize_with_mode(&mut reader, compress, validate)?,
+            memory_layout: MemoryLayout::deserialize_with_mode(&mut reader, compress, validate)?,
+            console: Vec::new(),
+            advice: Vec::new(),
+            hint_request: Vec::new(),
+            diagnostics: Vec::new(),
+        })
+    }
+}
+
+impl ark_serialize::Valid for JoltDevice {
+    fn check(&self) -> Result<(), ark_serialize::SerializationError> {
+        Ok(())
+    }
 }
 
 impl JoltDevice {
-    pub fn new(max_input_size: u64, max_output_size: u64) -> Self {
+    pub fn new(memory_layout: MemoryLayout) -> Self {
         Self {
             inputs: Vec::new(),
             outputs: Vec::new(),
             panic: false,
-            memory_layout: MemoryLayout::new(max_input_size, max_output_size),
+            termination: false,
+            memory_layout,
+            console: Vec::new(),
+            advice: Vec::new(),
+            hint_request: Vec::new(),
+            diagnostics: Vec::new(),
         }
     }
 
     pub fn load(&self, address: u64) -> u8 {
+        if self.is_hint_service(address) {
+            return 0;
+        }
+
+        if self.is_advice(address) {
+            let internal_address = (address - self.memory_layout.advice_start) as usize;
+            return self.advice.get(internal_address).copied().unwrap_or(0);
+        }
+
         let internal_address = self.convert_read_address(address);
         if self.inputs.len() <= internal_address {
             0
@@ -634,6 +723,28 @@ impl JoltDevice {
             return;
         }
 
+        if address == self.memory_layout.termination {
+            self.termination = true;
+            return;
+        }
+
+        if address == self.memory_layout.console {
+            self.console.push(value);
+            return;
+        }
+
+        if address == self.memory_layout.hint {
+            self.hint_request.push(value);
+            return;
+        }
+
+        if address == self.memory_layout.diagnostics {
+            if (self.diagnostics.len() as u64) < MAX_DIAGNOSTICS_SIZE {
+                self.diagnostics.push(value);
+            }
+            return;
+        }
+
         let internal_address = self.convert_write_address(address);
         if self.outputs.len() <= internal_address {
             self.outputs.resize(internal_address + 1, 0);
@@ -650,6 +761,10 @@ impl JoltDevice {
         address >= self.memory_layout.input_start && address < self.memory_layout.input_end
     }
 
+    pub fn is_advice(&self, address: u64) -> bool {
+        address >= self.memory_layout.advice_start && address < self.memory_layout.advice_end
+    }
+
     pub fn is_output(&self, address: u64) -> bool {
         address >= self.memory_layout.output_start && address < self.memory_layout.panic
     }
@@ -658,6 +773,81 @@ impl JoltDevice {
         address == self.memory_layout.panic
     }
 
+    pub fn is_termination(&self, address: u64) -> bool {
+        address == self.memory_layout.termination
+    }
+
+    pub fn is_console(&self, address: u64) -> bool {
+        address == self.memory_layout.console
+    }
+
+    pub fn is_hint(&self, address: u64) -> bool {
+        address == self.memory_layout.hint
+    }
+
+    pub fn is_hint_service(&self, address: u64) -> bool {
+        address == self.memory_layout.hint_service
+    }
+
+    pub fn is_diagnostics(&self, address: u64) -> bool {
+        address == self.memory_layout.diagnostics
+    }
+
+    /// Appends the response to a hint request to the advice, where the guest reads it as a
+    /// little-endian `u32` length followed by the response bytes.
+    pub fn push_hint_response(&mut self, response: &[u8]) {
+        assert!(
+            (self.advice.len() + 4 + response.len()) as u64 <= self.memory_layout.max_advice_size,
+            "hint responses exceed the maximum advice size"
+        );
+        self.advice
+            .extend_from_slice(&(response.len() as u32).to_le_bytes());
+        self.advice.extend_from_slice(response);
+    }
+
+    /// With hashed I/O, the digest of the guest's inputs and outputs, which it writes to the
+    /// start of the output region. `None` if the guest doesn't hash its I/O or didn't write
+    /// the digest.
+    pub fn io_digest(&self) -> Option<[u8; 32]> {
+        if !self.memory_layout.hash_io {
+            return None;
+        }
+        self.outputs.get(..IO_DIGEST_SIZE as usize)?.try_into().ok()
+    }
+
+    /// The guest's serialized return value. With hashed I/O this follows the digest in the
+    /// output region, and is not part of the program I/O.
+    pub fn output_bytes(&self) -> &[u8] {
+        if self.memory_layout.hash_io {
+            self.outputs.get(IO_DIGEST_SIZE as usize..).unwrap_or(&[])
+        } else {
+            &self.outputs
+        }
+    }
+
+    /// Drops everything but the digest from the outputs of a guest with hashed I/O, leaving
+    /// only the program I/O the verifier sees.
+    pub fn truncate_hashed_outputs(&mut self) {
+        if self.memory_layout.hash_io {
+            self.outputs.truncate(IO_DIGEST_SIZE as usize);
+        }
+    }
+
+    /// The guest's panic message, as formatted by its panic handler (e.g. "panicked at
+    /// src/lib.rs:12:5:\nindex out of bounds"), with invalid UTF-8 replaced. `None` if the
+    /// guest didn't panic or didn't report a message.
+    pub fn panic_message(&self) -> Option<String> {
+        if !self.panic || self.diagnostics.is_empty() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&self.diagnostics).into_owned())
+    }
+
+    /// The guest's console output, with invalid UTF-8 replaced.
+    pub fn console_output(&self) -> String {
+        String::from_utf8_lossy(&self.console).into_owned()
+    }
+
     fn convert_read_address(&self, address: u64) -> usize {
         (address - self.memory_layout.input_start) as usize
     }
@@ -667,55 +857,215 @@ impl JoltDevice {
     }
 }
 
+/// The state of the guest machine at a point in its execution, used as the starting
+/// point of a continuation segment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MachineSnapshot {
+    /// Address of the next instruction to execute.
+    pub pc: u64,
+    /// Values of the RISC-V registers, followed by any virtual registers.
+    pub registers: Vec<u64>,
+    /// The non-zero bytes of RAM, as (address, value) pairs sorted by address.
+    pub ram: Vec<(u64, u8)>,
+    /// Program I/O written so far.
+    pub device: JoltDevice,
+}
+
+/// The placement and sizes of a guest program's memory regions, as chosen when it is built.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryConfig {
+    /// Address at which RAM starts, `RAM_START_ADDRESS` by default. The I/O regions are placed
+    /// directly below it, so it must leave room for them above the emulator's peripheral
+    /// devices, which occupy addresses up to `0x10002000`.
+    pub ram_start: u64,
+    /// Size of RAM, starting at `ram_start`, which holds the program's code and data,
+    /// followed by its stack and heap.
+    pub memory_size: u64,
+    pub stack_size: u64,
+    pub max_input_size: u64,
+    pub max_output_size: u64,
+    /// Size of the advice region for private inputs and hint responses; zero disables it.
+    pub max_advice_size: u64,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            ram_start: RAM_START_ADDRESS,
+            memory_size: DEFAULT_MEMORY_SIZE,
+            stack_size: DEFAULT_STACK_SIZE,
+            max_input_size: DEFAULT_MAX_INPUT_SIZE,
+            max_output_size: DEFAULT_MAX_OUTPUT_SIZE,
+            max_advice_size: 0,
+        }
+    }
+}
+
+impl MemoryConfig {
+    pub fn memory_layout(&self) -> MemoryLayout {
+        MemoryLayout::with_ram_start(
+            self.ram_start,
+            self.max_input_size,
+            self.max_output_size,
+            self.max_advice_size,
+        )
+    }
+}
+
 #[derive(
     Debug, Clone, PartialEq, Serialize, Deserialize, CanonicalSerialize, CanonicalDeserialize,
 )]
 pub struct MemoryLayout {
+    /// Address at which RAM, holding the program's code, data, stack and heap, starts.
+    pub ram_start: u64,
     pub ram_witness_offset: u64,
     pub max_input_size: u64,
     pub max_output_size: u64,
     pub input_start: u64,
     pub input_end: u64,
+    pub max_advice_size: u64,
+    /// The advice region holds private inputs. It is aligned to its size rounded up to a
+    /// power of two, so that it can be committed to separately from the rest of memory.
+    pub advice_start: u64,
+    pub advice_end: u64,
     pub output_start: u64,
     pub output_end: u64,
     pub panic: u64,
+    pub termination: u64,
+    /// Bytes written here are captured as console output; see `JoltDevice::console`.
+    pub console: u64,
+    /// Hint requests are written here a byte at a time; see `JoltDevice::hint_request`.
+    pub hint: u64,
+    /// Loading from here asks the host to service the pending hint request.
+    pub hint_service: u64,
+    /// The panic handler writes the panic message here; see `JoltDevice::diagnostics`.
+    pub diagnostics: u64,
+    /// If set, the guest's inputs and outputs are not public. The guest reads its inputs from
+    /// the advice region and writes a Keccak-256 digest of them and its return value to the
+    /// start of the output region, which is the only output the verifier checks.
+    pub hash_io: bool,
 }
 
 impl MemoryLayout {
-    pub fn new(max_input_size: u64, max_output_size: u64) -> Self {
+    /// The layout of a program whose RAM starts at the default `RAM_START_ADDRESS`.
+    pub fn new(max_input_size: u64, max_output_size: u64, max_advice_size: u64) -> Self {
+        Self::with_ram_start(
+            RAM_START_ADDRESS,
+            max_input_size,
+            max_output_size,
+            max_advice_size,
+        )
+    }
+
+    /// The layout of a program whose RAM starts at `ram_start`, with the I/O regions placed
+    /// directly below it.
+    pub fn with_ram_start(
+        ram_start: u64,
+        max_input_size: u64,
+        max_output_size: u64,
+        max_advice_size: u64,
+    ) -> Self {
+        let ram_witness_offset =
+            ram_witness_offset(max_input_size, max_output_size, max_advice_size);
+        let io_start = ram_start
+            .checked_sub(ram_witness_offset)
+            .expect("RAM start address leaves no room for the I/O regions");
+        let advice_start =
+            io_start + advice_witness_index(max_input_size, max_output_size, max_advice_size);
         Self {
-            ram_witness_offset: ram_witness_offset(max_input_size, max_output_size),
+            ram_start,
+            ram_witness_offset,
             max_input_size,
             max_output_size,
-            input_start: input_start(max_input_size, max_output_size),
-            input_end: input_end(max_input_size, max_output_size),
-            output_start: output_start(max_input_size, max_output_size),
-            output_end: output_end(max_input_size, max_output_size),
-            panic: panic_address(max_input_size, max_output_size),
+            input_start: input_start(io_start),
+            input_end: input_end(io_start, max_input_size),
+            max_advice_size,
+            advice_start,
+            advice_end: advice_start + max_advice_size,
+            output_start: output_start(io_start, max_input_size),
+            output_end: output_end(io_start, max_input_size, max_output_size),
+            panic: panic_address(io_start, max_input_size, max_output_size),
+            termination: termination_address(io_start, max_input_size, max_output_size),
+            console: console_address(io_start, max_input_size, max_output_size),
+            hint: hint_address(io_start, max_input_size, max_output_size),
+            hint_service: hint_service_address(io_start, max_input_size, max_output_size),
+            diagnostics: diagnostics_address(io_start, max_input_size, max_output_size),
+            hash_io: false,
         }
     }
+
+    /// Size of the advice region as committed to, i.e. `max_advice_size` rounded up to a
+    /// power of two. Zero if there is no advice region.
+    pub fn advice_commitment_size(&self) -> usize {
+        advice_commitment_size(self.max_advice_size) as usize
+    }
+
+    /// Maps a memory address at or above the start of the I/O regions to its index in the
+    /// memory witness; see the layout described in `constants`.
+    pub fn witness_index(&self, address: u64) -> usize {
+        (address + self.ram_witness_offset - self.ram_start) as usize
+    }
+}
+
+pub fn ram_witness_offset(max_input: u64, max_output: u64, max_advice: u64) -> u64 {
+    (advice_witness_index(max_input, max_output, max_advice) + advice_commitment_size(max_advice))
+        .next_power_of_two()
+}
+
+fn advice_commitment_size(max_advice: u64) -> u64 {
+    if max_advice == 0 {
+        0
+    } else {
+        max_advice.next_power_of_two()
+    }
+}
+
+fn advice_witness_index(max_input: u64, max_output: u64, max_advice: u64) -> u64 {
+    // Registers, inputs, outputs, the panic and termination bits, the console, hint and
+    // diagnostics bytes, and the padding bytes between the input, output, and panic regions
+    let io_size = REGISTER_COUNT + max_input + max_output + 8;
+    match advice_commitment_size(max_advice) {
+        0 => io_size,
+        size => io_size.next_multiple_of(size),
+    }
+}
+
+fn input_start(io_start: u64) -> u64 {
+    io_start + REGISTER_COUNT
+}
+
+fn input_end(io_start: u64, max_input: u64) -> u64 {
+    input_start(io_start) + max_input
+}
+
+fn output_start(io_start: u64, max_input: u64) -> u64 {
+    input_end(io_start, max_input) + 1
+}
+
+fn output_end(io_start: u64, max_input: u64, max_output: u64) -> u64 {
+    output_start(io_start, max_input) + max_output
 }
 
-pub fn ram_witness_offset(max_input: u64, max_output: u64) -> u64 {
-    (REGISTER_COUNT + max_input + max_output + 1).next_power_of_two()
+fn panic_address(io_start: u64, max_input: u64, max_output: u64) -> u64 {
+    output_end(io_start, max_input, max_output) + 1
 }
 
-fn input_start(max_input: u64, max_output: u64) -> u64 {
-    RAM_START_ADDRESS - ram_witness_offset(max_input, max_output) + REGISTER_COUNT
+fn termination_address(io_start: u64, max_input: u64, max_output: u64) -> u64 {
+    panic_address(io_start, max_input, max_output) + 1
 }
 
-fn input_end(max_input: u64, max_output: u64) -> u64 {
-    input_start(max_input, max_output) + max_input
+fn console_address(io_start: u64, max_input: u64, max_output: u64) -> u64 {
+    termination_address(io_start, max_input, max_output) + 1
 }
 
-fn output_start(max_input: u64, max_output: u64) -> u64 {
-    input_end(max_input, max_output) + 1
+fn hint_address(io_start: u64, max_input: u64, max_output: u64) -> u64 {
+    console_address(io_start, max_input, max_output) + 1
 }
 
-fn output_end(max_input: u64, max_output: u64) -> u64 {
-    output_start(max_input, max_output) + max_output
+fn hint_service_address(io_start: u64, max_input: u64, max_output: u64) -> u64 {
+    hint_address(io_start, max_input, max_output) + 1
 }
 
-fn panic_address(max_input: u64, max_output: u64) -> u64 {
-    output_end(max_input, max_output) + 1
+fn diagnostics_address(io_start: u64, max_input: u64, max_output: u64) -> u64 {
+    hint_service_address(io_start, max_input, max_output) + 1
 }
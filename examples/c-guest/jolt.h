@@ -0,0 +1,68 @@
+/*
+ * I/O helpers for C guests.
+ *
+ * The addresses below are those of the default memory configuration, i.e.
+ * `MemoryConfig::default()` (4096-byte inputs and outputs, no advice). For any
+ * other configuration, print `Program::memory_layout()` on the host and pass
+ * the corresponding addresses with -D when compiling the guest.
+ */
+#ifndef JOLT_H
+#define JOLT_H
+
+#ifndef JOLT_INPUT_START
+#define JOLT_INPUT_START 0x7fffc040
+#endif
+#ifndef JOLT_MAX_INPUT_SIZE
+#define JOLT_MAX_INPUT_SIZE 4096
+#endif
+#ifndef JOLT_OUTPUT_START
+#define JOLT_OUTPUT_START 0x7fffd041
+#endif
+#ifndef JOLT_MAX_OUTPUT_SIZE
+#define JOLT_MAX_OUTPUT_SIZE 4096
+#endif
+#ifndef JOLT_PANIC_ADDRESS
+#define JOLT_PANIC_ADDRESS 0x7fffe042
+#endif
+#ifndef JOLT_TERMINATION_ADDRESS
+#define JOLT_TERMINATION_ADDRESS 0x7fffe043
+#endif
+#ifndef JOLT_CONSOLE_ADDRESS
+#define JOLT_CONSOLE_ADDRESS 0x7fffe044
+#endif
+
+#ifndef __ASSEMBLER__
+
+#include <stdint.h>
+
+/* The inputs set with `Program::set_input`. Unused bytes read as zero. */
+static inline const volatile uint8_t *jolt_input(void) {
+    return (const volatile uint8_t *)JOLT_INPUT_START;
+}
+
+/*
+ * The guest's outputs, which become `JoltDevice::outputs`. The output region
+ * is write-only: reading it back makes the tracer fail.
+ */
+static inline volatile uint8_t *jolt_output(void) {
+    return (volatile uint8_t *)JOLT_OUTPUT_START;
+}
+
+/* Writes a string to the console (see `JoltDevice::console`). */
+static inline void jolt_print(const char *s) {
+    while (*s) {
+        *(volatile uint8_t *)JOLT_CONSOLE_ADDRESS = (uint8_t)*s++;
+    }
+}
+
+/* Halts the guest, which makes the host report `TraceError::GuestPanic`. */
+static inline void __attribute__((noreturn)) jolt_panic(void) {
+    *(volatile uint8_t *)JOLT_PANIC_ADDRESS = 1;
+    *(volatile uint8_t *)JOLT_TERMINATION_ADDRESS = 1;
+    for (;;) {
+    }
+}
+
+#endif /* __ASSEMBLER__ */
+
+#endif /* JOLT_H */
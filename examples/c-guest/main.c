@@ -0,0 +1,29 @@
+#include <stdint.h>
+
+#include "jolt.h"
+
+/*
+ * Computes the n-th Fibonacci number modulo 2^32. The host passes n as four
+ * little-endian bytes and reads the result back the same way.
+ */
+int main(void) {
+    const volatile uint8_t *input = jolt_input();
+    uint32_t n = input[0] | input[1] << 8 | input[2] << 16 | (uint32_t)input[3] << 24;
+    if (n > 1000000) {
+        jolt_print("n is too large\n");
+        jolt_panic();
+    }
+
+    uint32_t a = 0, b = 1;
+    for (uint32_t i = 0; i < n; i++) {
+        uint32_t next = a + b;
+        a = b;
+        b = next;
+    }
+
+    volatile uint8_t *output = jolt_output();
+    for (int i = 0; i < 4; i++) {
+        output[i] = (uint8_t)(a >> (8 * i));
+    }
+    return 0;
+}
@@ -107,10 +107,17 @@ where
 
     let task = move || {
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
-
-        let preprocessing: crate::jolt::vm::JoltPreprocessing<F, PCS> =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 22);
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
+
+        let preprocessing: crate::jolt::vm::JoltPreprocessing<F, PCS> = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 22,
+        )
+        .unwrap();
 
         let (jolt_proof, jolt_commitments) = <RV32IJoltVM as Jolt<_, PCS, C, M>>::prove(
             io_device,
@@ -133,7 +140,8 @@ where
             &jolt_proof.instruction_lookups,
         );
 
-        let verification_result = RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments);
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing.verifier_key(), jolt_proof, jolt_commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
@@ -161,10 +169,17 @@ where
 
     let task = move || {
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
-
-        let preprocessing: crate::jolt::vm::JoltPreprocessing<F, PCS> =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 22);
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
+
+        let preprocessing: crate::jolt::vm::JoltPreprocessing<F, PCS> = RV32IJoltVM::preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 22,
+        )
+        .unwrap();
 
         let (jolt_proof, jolt_commitments) = <RV32IJoltVM as Jolt<_, PCS, C, M>>::prove(
             io_device,
@@ -172,7 +187,8 @@ where
             circuit_flags,
             preprocessing.clone(),
         );
-        let verification_result = RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments);
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing.verifier_key(), jolt_proof, jolt_commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
@@ -133,8 +133,7 @@ impl<F: BiniusSpecific> JoltField for BiniusField<F> {
     fn from_bytes(bytes: &[u8]) -> Self {
         assert_eq!(bytes.len(), Self::NUM_BYTES);
 
-        let field_element = bytemuck::try_from_bytes::<F>(bytes).unwrap();
-        Self(field_element.to_owned())
+        Self(bytemuck::pod_read_unaligned::<F>(bytes))
     }
 }
 
@@ -258,18 +257,20 @@ impl<F: BiniusSpecific> CanonicalSerialize for BiniusField<F> {
 }
 
 impl<F: BiniusSpecific> CanonicalDeserialize for BiniusField<F> {
-    // Required method
     fn deserialize_with_mode<R: std::io::prelude::Read>(
-        _reader: R,
+        mut reader: R,
         _compress: ark_serialize::Compress,
         _validate: ark_serialize::Validate,
     ) -> Result<Self, ark_serialize::SerializationError> {
-        todo!()
+        let mut bytes = [0u8; 16];
+        reader.read_exact(&mut bytes)?;
+        Ok(<Self as JoltField>::from_bytes(&bytes))
     }
 }
 
 impl<F: BiniusSpecific> ark_serialize::Valid for BiniusField<F> {
     fn check(&self) -> Result<(), ark_serialize::SerializationError> {
-        todo!()
+        // Every 128-bit pattern is a valid field element.
+        Ok(())
     }
 }
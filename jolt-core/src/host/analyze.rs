@@ -5,22 +5,22 @@ use tracer::{ELFInstruction, JoltDevice, RVTraceRow, RV32IM};
 
 use crate::{
     field::JoltField,
-    jolt::vm::{rv32i_vm::RV32I, JoltTraceStep},
+    jolt::{instruction::JoltInstructionSet, vm::JoltTraceStep},
 };
 
 #[derive(Clone, Serialize, Deserialize)]
-pub struct ProgramSummary {
+pub struct ProgramSummary<InstructionSet: JoltInstructionSet> {
     pub raw_trace: Vec<RVTraceRow>,
 
     pub bytecode: Vec<ELFInstruction>,
     pub memory_init: Vec<(u64, u8)>,
 
     pub io_device: JoltDevice,
-    pub processed_trace: Vec<JoltTraceStep<RV32I>>,
+    pub processed_trace: Vec<JoltTraceStep<InstructionSet>>,
     pub circuit_flags: Vec<bool>,
 }
 
-impl ProgramSummary {
+impl<InstructionSet: JoltInstructionSet> ProgramSummary<InstructionSet> {
     pub fn trace_len(&self) -> usize {
         self.processed_trace.len()
     }
@@ -0,0 +1,288 @@
+//! Content-addressed cache of guest builds.
+//!
+//! Each build configuration (workspace, guest, function, memory and stack sizes, and toolchain)
+//! gets its own directory holding the linker script and cargo target directory, so that
+//! cargo can build incrementally and guests from different workspaces never share state.
+//! Built ELFs are stored under a fingerprint of that configuration and of the guest's
+//! sources, and are reused without invoking cargo as long as the sources are unchanged.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use eyre::{bail, eyre, Result};
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+
+/// Environment variable overriding the default cache root.
+pub const CACHE_DIR_ENV: &str = "JOLT_GUEST_CACHE_DIR";
+
+/// The cache root used unless one is set with `Program::set_cache_dir`: `$JOLT_GUEST_CACHE_DIR`
+/// if set, and `jolt-guest-cache` in the system's temporary directory otherwise.
+pub fn default_cache_dir() -> PathBuf {
+    std::env::var_os(CACHE_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("jolt-guest-cache"))
+}
+
+/// Where a guest is built, and where its ELF is stored once built.
+pub(crate) struct GuestBuild {
+    dir: PathBuf,
+    fingerprint: String,
+}
+
+impl GuestBuild {
+    /// Locates the guest package `guest` in the current cargo workspace and hashes its
+    /// sources, along with those of every local package it depends on and the workspace's
+    /// `Cargo.lock`. `config` must capture everything else that affects the build.
+    pub fn new(cache_dir: &Path, guest: &str, config: &[&str]) -> Result<Self> {
+        let metadata = Metadata::load()?;
+        let package_dirs = metadata.local_dependencies(guest)?;
+        Self::from_packages(
+            cache_dir,
+            guest,
+            Path::new(metadata.workspace_root()),
+            &package_dirs,
+            config,
+        )
+    }
+
+    /// Like `new`, with the workspace and the directories of the guest's local packages given.
+    fn from_packages(
+        cache_dir: &Path,
+        guest: &str,
+        workspace_root: &Path,
+        package_dirs: &[PathBuf],
+        config: &[&str],
+    ) -> Result<Self> {
+        let mut hasher = Keccak256::new();
+        hash_str(&mut hasher, &workspace_root.to_string_lossy());
+        for item in config {
+            hash_str(&mut hasher, item);
+        }
+        let config_hash = hex(&hasher.finalize());
+        let dir = cache_dir.join(format!("{}-{}", guest, &config_hash[..16]));
+
+        let mut hasher = Keccak256::new();
+        hash_str(&mut hasher, &config_hash);
+        for package_dir in package_dirs {
+            hash_str(&mut hasher, &package_dir.to_string_lossy());
+            hash_dir(&mut hasher, package_dir, package_dir)?;
+        }
+        let lock_file = workspace_root.join("Cargo.lock");
+        if let Ok(lock) = fs::read(lock_file) {
+            hasher.update(lock);
+        }
+        let fingerprint = hex(&hasher.finalize());
+
+        Ok(Self { dir, fingerprint })
+    }
+
+    pub fn linker_path(&self) -> PathBuf {
+        self.dir.join("linker.ld")
+    }
+
+    pub fn target_dir(&self) -> PathBuf {
+        self.dir.join("target")
+    }
+
+    /// The cached ELF for the current sources, which exists only if they were built before.
+    pub fn elf_path(&self) -> PathBuf {
+        self.dir.join("elf").join(&self.fingerprint)
+    }
+}
+
+/// Returns the full version information of the rustc in `toolchain`.
+pub(crate) fn rustc_version(toolchain: &str) -> Result<String> {
+    let output = Command::new("rustc")
+        .env("RUSTUP_TOOLCHAIN", toolchain)
+        .arg("-vV")
+        .output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The output of `cargo metadata` for the current workspace.
+struct Metadata(Value);
+
+impl Metadata {
+    /// Runs `cargo metadata` offline, so that fingerprinting a guest never needs the network.
+    /// Only if the registry cache is missing some dependency, e.g. before the first build,
+    /// is the metadata fetched online.
+    fn load() -> Result<Self> {
+        let metadata = |offline: bool| {
+            let mut command = Command::new("cargo");
+            command.args(["metadata", "--format-version", "1"]);
+            if offline {
+                command.arg("--offline");
+            }
+            command.output()
+        };
+        let mut output = metadata(true)?;
+        if !output.status.success() {
+            output = metadata(false)?;
+        }
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr));
+        }
+        Ok(Self(serde_json::from_slice(&output.stdout)?))
+    }
+
+    fn workspace_root(&self) -> &str {
+        self.0["workspace_root"].as_str().unwrap_or_default()
+    }
+
+    /// Returns the directories of the package `name` and of every local package it
+    /// (transitively) depends on, i.e. workspace members and path dependencies, sorted.
+    fn local_dependencies(&self, name: &str) -> Result<Vec<PathBuf>> {
+        let packages = self.0["packages"].as_array().cloned().unwrap_or_default();
+        // Packages on the local filesystem have no source
+        let local = |package: &&Value| package["source"].is_null();
+        let root = packages
+            .iter()
+            .filter(local)
+            .find(|package| package["name"] == name)
+            .ok_or_else(|| eyre!("guest package {} not found in workspace", name))?;
+
+        let dependencies: HashMap<&str, Vec<&str>> = self.0["resolve"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|node| {
+                let id = node["id"].as_str().unwrap_or_default();
+                let dependencies = node["dependencies"].as_array().into_iter().flatten();
+                (id, dependencies.filter_map(Value::as_str).collect())
+            })
+            .collect();
+
+        let root_id = root["id"].as_str().unwrap_or_default();
+        let mut visited = HashSet::from([root_id]);
+        let mut queue = VecDeque::from([root_id]);
+        while let Some(id) = queue.pop_front() {
+            for &dependency in dependencies.get(id).into_iter().flatten() {
+                if visited.insert(dependency) {
+                    queue.push_back(dependency);
+                }
+            }
+        }
+
+        let mut dirs: Vec<PathBuf> = packages
+            .iter()
+            .filter(local)
+            .filter(|package| visited.contains(package["id"].as_str().unwrap_or_default()))
+            .filter_map(|package| package["manifest_path"].as_str())
+            .filter_map(|manifest| Path::new(manifest).parent())
+            .map(Path::to_path_buf)
+            .collect();
+        dirs.sort();
+        Ok(dirs)
+    }
+}
+
+/// Hashes the relative path and contents of every file under `dir`, skipping hidden entries
+/// and `target` directories.
+fn hash_dir(hasher: &mut Keccak256, root: &Path, dir: &Path) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.') || name == "target" {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            hash_dir(hasher, root, &path)?;
+        } else {
+            hash_str(hasher, &path.strip_prefix(root)?.to_string_lossy());
+            let contents = fs::read(&path)?;
+            hasher.update((contents.len() as u64).to_le_bytes());
+            hasher.update(contents);
+        }
+    }
+    Ok(())
+}
+
+/// Hashes `s` with a length prefix, so that consecutive strings can't be confused.
+fn hash_str(hasher: &mut Keccak256, s: &str) {
+    hasher.update((s.len() as u64).to_le_bytes());
+    hasher.update(s.as_bytes());
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "jolt-guest-cache-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("guest/src")).unwrap();
+        fs::write(root.join("Cargo.lock"), "version = 3\n").unwrap();
+        fs::write(
+            root.join("guest/Cargo.toml"),
+            "[package]\nname = \"guest\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("guest/src/lib.rs"), "fn f() {}\n").unwrap();
+        root
+    }
+
+    fn build(root: &Path, config: &[&str]) -> GuestBuild {
+        GuestBuild::from_packages(
+            &root.join("cache"),
+            "guest",
+            root,
+            &[root.join("guest")],
+            config,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn fingerprint_tracks_sources_config_and_lock_file() {
+        let root = workspace("fingerprint");
+        let build_path = |config: &[&str]| build(&root, config).elf_path();
+        let original = build_path(&["fib"]);
+
+        // Stable across runs, and unaffected by build outputs and hidden files
+        assert_eq!(build_path(&["fib"]), original);
+        fs::create_dir_all(root.join("guest/target")).unwrap();
+        fs::write(root.join("guest/target/guest"), "elf").unwrap();
+        fs::write(root.join("guest/.hidden"), "").unwrap();
+        assert_eq!(build_path(&["fib"]), original);
+
+        fs::write(root.join("guest/src/lib.rs"), "fn g() {}\n").unwrap();
+        assert_ne!(build_path(&["fib"]), original);
+        fs::write(root.join("guest/src/lib.rs"), "fn f() {}\n").unwrap();
+        assert_eq!(build_path(&["fib"]), original);
+
+        fs::write(root.join("guest/src/main.rs"), "").unwrap();
+        assert_ne!(build_path(&["fib"]), original);
+        fs::remove_file(root.join("guest/src/main.rs")).unwrap();
+        assert_eq!(build_path(&["fib"]), original);
+
+        let other_config = build(&root, &["fib", "1024"]);
+        assert_ne!(other_config.elf_path(), original);
+        assert_ne!(
+            other_config.target_dir(),
+            build(&root, &["fib"]).target_dir()
+        );
+
+        fs::write(root.join("Cargo.lock"), "version = 4\n").unwrap();
+        assert_ne!(build_path(&["fib"]), original);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
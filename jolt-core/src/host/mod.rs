@@ -1,42 +1,44 @@
 #![allow(clippy::type_complexity)]
 
-use core::str::FromStr;
 use std::{
     fs::{self, File},
     io::{self, Read, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::Command,
 };
 
+use eyre::{bail, Result, WrapErr};
 use postcard;
 use rayon::prelude::*;
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 
+pub use common::rv_trace::MemoryConfig;
 use common::{
-    constants::{
-        DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, DEFAULT_MEMORY_SIZE, DEFAULT_STACK_SIZE,
-    },
-    rv_trace::{JoltDevice, NUM_CIRCUIT_FLAGS},
+    constants::{DEFAULT_MAX_CYCLES, REGISTER_COUNT},
+    rv_trace::{JoltDevice, MemoryLayout, MemoryOp, RVTraceRow, NUM_CIRCUIT_FLAGS},
 };
-use strum::EnumCount;
-pub use tracer::ELFInstruction;
+pub use tracer::{ELFInstruction, HintProviders, TraceError, UnsupportedInstruction};
 
 use crate::{
     field::JoltField,
     jolt::{
         instruction::{
-            mulh::MULHInstruction, mulhsu::MULHSUInstruction, VirtualInstructionSequence,
+            div::DIVInstruction, divu::DIVUInstruction, mulh::MULHInstruction,
+            mulhsu::MULHSUInstruction, rem::REMInstruction, remu::REMUInstruction,
+            JoltInstructionSet, VirtualInstructionSequence,
         },
-        vm::{bytecode::BytecodeRow, rv32i_vm::RV32I, JoltTraceStep},
+        vm::{bytecode::BytecodeRow, JoltSegment, JoltTraceStep},
     },
     utils::thread::unsafe_allocate_zero_vec,
 };
 
 use self::analyze::ProgramSummary;
+use self::cache::{default_cache_dir, rustc_version, GuestBuild};
 #[cfg(not(target_arch = "wasm32"))]
 use self::toolchain::install_toolchain;
 
 pub mod analyze;
+pub mod cache;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod toolchain;
 
@@ -45,11 +47,13 @@ pub struct Program {
     guest: String,
     func: Option<String>,
     input: Vec<u8>,
-    memory_size: u64,
-    stack_size: u64,
-    max_input_size: u64,
-    max_output_size: u64,
+    advice: Vec<u8>,
+    hints: HintProviders,
+    memory_config: MemoryConfig,
+    max_cycles: u64,
+    hash_io: bool,
     std: bool,
+    cache_dir: Option<PathBuf>,
     pub elf: Option<PathBuf>,
 }
 
@@ -59,15 +63,36 @@ impl Program {
             guest: guest.to_string(),
             func: None,
             input: Vec::new(),
-            memory_size: DEFAULT_MEMORY_SIZE,
-            stack_size: DEFAULT_STACK_SIZE,
-            max_input_size: DEFAULT_MAX_INPUT_SIZE,
-            max_output_size: DEFAULT_MAX_OUTPUT_SIZE,
+            advice: Vec::new(),
+            hints: HintProviders::default(),
+            memory_config: MemoryConfig::default(),
+            max_cycles: DEFAULT_MAX_CYCLES,
+            hash_io: false,
             std: false,
+            cache_dir: None,
             elf: None,
         }
     }
 
+    /// Creates a program from a prebuilt guest ELF instead of a guest crate, e.g. one written
+    /// in C or assembly (see `examples/c-guest`). `memory_config` must match the layout the
+    /// ELF was linked with. Fails if the ELF is not a 32-bit RISC-V executable or places any
+    /// code or data outside of the guest's RAM.
+    pub fn from_elf(
+        path: impl AsRef<Path>,
+        memory_config: MemoryConfig,
+    ) -> Result<Self, TraceError> {
+        let path = path.as_ref();
+        let elf = fs::read(path)?;
+        tracer::check_layout(&elf, &memory_config)?;
+
+        let guest = path.file_stem().unwrap_or_default().to_string_lossy();
+        let mut program = Self::new(&guest);
+        program.memory_config = memory_config;
+        program.elf = Some(path.to_path_buf());
+        Ok(program)
+    }
+
     pub fn set_std(&mut self, std: bool) {
         self.std = std;
     }
@@ -81,189 +106,458 @@ impl Program {
         self.input.append(&mut serialized);
     }
 
+    /// Appends a private input, which the guest reads from the advice region. Unlike
+    /// inputs set with `set_input`, advice is not revealed to the verifier. The advice
+    /// region must be enabled with `set_max_advice_size`.
+    pub fn set_advice<T: Serialize>(&mut self, advice: &T) {
+        let mut serialized = postcard::to_stdvec(advice).unwrap();
+        self.advice.append(&mut serialized);
+    }
+
+    /// Registers `provider` to answer the guest's requests for the hint `name` (see
+    /// `jolt::hint`). Hint responses are untrusted, so the guest should check them; they
+    /// are stored in the advice region, which must be large enough to hold them all.
+    pub fn set_hint_provider<Req, Resp>(
+        &mut self,
+        name: &str,
+        provider: impl Fn(Req) -> Resp + Send + Sync + 'static,
+    ) where
+        Req: DeserializeOwned,
+        Resp: Serialize,
+    {
+        let name_str = name.to_string();
+        self.hints.insert(
+            name,
+            std::sync::Arc::new(move |request: &[u8]| {
+                let request = postcard::from_bytes(request).unwrap_or_else(|e| {
+                    panic!(
+                        "failed to deserialize request for hint \"{}\": {}",
+                        name_str, e
+                    )
+                });
+                postcard::to_stdvec(&provider(request)).unwrap()
+            }),
+        );
+    }
+
+    /// Sets the placement and sizes of all of the guest's memory regions at once.
+    pub fn set_memory_config(&mut self, memory_config: MemoryConfig) {
+        self.memory_config = memory_config;
+    }
+
+    /// Sets the address at which the guest's RAM starts. The guest is linked there, and its
+    /// I/O regions are placed directly below it.
+    pub fn set_ram_start(&mut self, address: u64) {
+        self.memory_config.ram_start = address;
+    }
+
     pub fn set_memory_size(&mut self, len: u64) {
-        self.memory_size = len;
+        self.memory_config.memory_size = len;
     }
 
     pub fn set_stack_size(&mut self, len: u64) {
-        self.stack_size = len;
+        self.memory_config.stack_size = len;
     }
 
     pub fn set_max_input_size(&mut self, size: u64) {
-        self.max_input_size = size;
+        self.memory_config.max_input_size = size;
     }
 
     pub fn set_max_output_size(&mut self, size: u64) {
-        self.max_output_size = size;
+        self.memory_config.max_output_size = size;
     }
 
+    pub fn set_max_advice_size(&mut self, size: u64) {
+        self.memory_config.max_advice_size = size;
+    }
+
+    pub fn set_max_cycles(&mut self, max_cycles: u64) {
+        self.max_cycles = max_cycles;
+    }
+
+    /// Sets the directory guest builds are cached in, overriding `cache::default_cache_dir`.
+    pub fn set_cache_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.cache_dir = Some(dir.into());
+    }
+
+    /// Marks the guest as hashing its I/O (see `MemoryLayout::hash_io`). This must match
+    /// how the guest was compiled, and its arguments must be passed with `set_advice`.
+    pub fn set_hash_io(&mut self, hash_io: bool) {
+        self.hash_io = hash_io;
+    }
+
+    /// The memory layout the guest will be traced with, as determined by the
+    /// configured RAM start address and maximum input, output and advice sizes.
+    pub fn memory_layout(&self) -> MemoryLayout {
+        let mut memory_layout = self.memory_config.memory_layout();
+        memory_layout.hash_io = self.hash_io;
+        memory_layout
+    }
+
+    /// Builds the guest, unless an ELF was already set. Builds are cached (see `cache`), so
+    /// cargo is only invoked if the guest's sources or build configuration changed since it
+    /// was last built. Fails if the guest toolchain can't be installed or the guest doesn't
+    /// compile.
     #[tracing::instrument(skip_all, name = "Program::build")]
-    pub fn build(&mut self) {
+    pub fn build(&mut self) -> Result<()> {
         if self.elf.is_none() {
             #[cfg(not(target_arch = "wasm32"))]
-            install_toolchain().unwrap();
-            self.save_linker();
-
-            let rust_flags = [
-                "-C",
-                &format!("link-arg=-T{}", self.linker_path()),
-                "-C",
-                "passes=loweratomic",
-                "-C",
-                "panic=abort",
-            ];
+            install_toolchain().wrap_err("failed to install the guest toolchain")?;
 
             let toolchain = "riscv32i-jolt-zkvm-elf";
-            let mut envs = vec![
-                ("CARGO_ENCODED_RUSTFLAGS", rust_flags.join("\x1f")),
-                ("RUSTUP_TOOLCHAIN", toolchain.to_string()),
-            ];
-
-            if let Some(func) = &self.func {
-                envs.push(("JOLT_FUNC_NAME", func.to_string()));
+            let cache_dir = self.cache_dir.clone().unwrap_or_else(default_cache_dir);
+            let guest_build = GuestBuild::new(
+                &cache_dir,
+                &self.guest,
+                &[
+                    self.func.as_deref().unwrap_or_default(),
+                    &self.memory_config.ram_start.to_string(),
+                    &self.memory_config.memory_size.to_string(),
+                    &self.memory_config.stack_size.to_string(),
+                    &rustc_version(toolchain).wrap_err("failed to query guest toolchain")?,
+                ],
+            )
+            .wrap_err("failed to fingerprint guest")?;
+
+            let elf = guest_build.elf_path();
+            if !elf.exists() {
+                self.compile(&guest_build, toolchain)?;
             }
+            self.elf = Some(elf);
+        }
+        Ok(())
+    }
 
-            let target = format!(
-                "/tmp/jolt-guest-target-{}-{}",
-                self.guest,
-                self.func.as_ref().unwrap_or(&"".to_string())
-            );
-
-            let output = Command::new("cargo")
-                .envs(envs)
-                .args([
-                    "build",
-                    "--release",
-                    "--features",
-                    "guest",
-                    "-p",
-                    &self.guest,
-                    "--target-dir",
-                    &target,
-                    "--target",
-                    toolchain,
-                ])
-                .output()
-                .expect("failed to build guest");
-
-            if !output.status.success() {
-                io::stderr().write_all(&output.stderr).unwrap();
-                panic!("failed to compile guest");
-            }
+    fn compile(&self, guest_build: &GuestBuild, toolchain: &str) -> Result<()> {
+        let linker_path = guest_build.linker_path();
+        self.save_linker(&linker_path);
+
+        let rust_flags = [
+            "-C",
+            &format!("link-arg=-T{}", linker_path.display()),
+            "-C",
+            "passes=loweratomic",
+            "-C",
+            "panic=abort",
+        ];
+
+        let mut envs = vec![
+            ("CARGO_ENCODED_RUSTFLAGS", rust_flags.join("\x1f")),
+            ("RUSTUP_TOOLCHAIN", toolchain.to_string()),
+        ];
+
+        if let Some(func) = &self.func {
+            envs.push(("JOLT_FUNC_NAME", func.to_string()));
+        }
 
-            let elf = format!("{}/{}/release/guest", target, toolchain);
-            self.elf = Some(PathBuf::from_str(&elf).unwrap());
+        let target_dir = guest_build.target_dir();
+        let output = Command::new("cargo")
+            .envs(envs)
+            .args([
+                "build",
+                "--release",
+                "--features",
+                "guest",
+                "-p",
+                &self.guest,
+                "--target",
+                toolchain,
+            ])
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .output()
+            .wrap_err("failed to build guest")?;
+
+        if !output.status.success() {
+            io::stderr().write_all(&output.stderr)?;
+            bail!("failed to compile guest");
         }
+
+        // Copy, then rename, so that a concurrent build never sees a partially written ELF
+        let elf = guest_build.elf_path();
+        let built = target_dir.join(toolchain).join("release").join("guest");
+        let partial = elf.with_extension("partial");
+        fs::create_dir_all(elf.parent().unwrap()).wrap_err("could not create guest cache")?;
+        fs::copy(built, &partial).wrap_err("could not cache guest ELF")?;
+        fs::rename(partial, elf).wrap_err("could not cache guest ELF")?;
+        Ok(())
     }
 
     pub fn decode(&mut self) -> (Vec<ELFInstruction>, Vec<(u64, u8)>) {
-        self.build();
+        self.build()
+            .unwrap_or_else(|e| panic!("failed to build guest: {:#}", e));
         let elf = self.elf.as_ref().unwrap();
         let mut elf_file = File::open(elf).unwrap();
         let mut elf_contents = Vec::new();
         elf_file.read_to_end(&mut elf_contents).unwrap();
-        tracer::decode(&elf_contents)
+        let (instructions, memory_init) =
+            tracer::decode(&elf_contents, self.memory_config.ram_start)
+                .unwrap_or_else(|e| panic!("failed to decode guest: {}", e));
+        let instructions = instructions
+            .into_iter()
+            .flat_map(|instruction| match instruction.opcode {
+                tracer::RV32IM::MULH => MULHInstruction::<32>::virtual_sequence(instruction),
+                tracer::RV32IM::MULHSU => MULHSUInstruction::<32>::virtual_sequence(instruction),
+                tracer::RV32IM::DIV => DIVInstruction::<32>::virtual_sequence(instruction),
+                tracer::RV32IM::DIVU => DIVUInstruction::<32>::virtual_sequence(instruction),
+                tracer::RV32IM::REM => REMInstruction::<32>::virtual_sequence(instruction),
+                tracer::RV32IM::REMU => REMUInstruction::<32>::virtual_sequence(instruction),
+                _ => vec![instruction],
+            })
+            .collect();
+        (instructions, memory_init)
+    }
+
+    /// Checks that every instruction in the guest ELF can be proven by Jolt, without running
+    /// it. Returns `TraceError::UnsupportedInstructions` listing each offending instruction,
+    /// e.g. a floating-point or compressed instruction pulled in by a dependency.
+    pub fn check(&mut self) -> Result<(), TraceError> {
+        self.build()
+            .map_err(|e| TraceError::ElfRead(io::Error::other(format!("{:#}", e))))?;
+        let elf = fs::read(self.elf.as_ref().unwrap())?;
+        let unsupported = tracer::check(&elf, self.memory_config.ram_start)?;
+        if unsupported.is_empty() {
+            Ok(())
+        } else {
+            Err(TraceError::UnsupportedInstructions(unsupported))
+        }
     }
 
-    // TODO(moodlezoup): Make this generic over InstructionSet
     #[tracing::instrument(skip_all, name = "Program::trace")]
-    pub fn trace<F: JoltField>(mut self) -> (JoltDevice, Vec<JoltTraceStep<RV32I>>, Vec<F>) {
-        self.build();
+    pub fn trace<InstructionSet: JoltInstructionSet, F: JoltField>(
+        mut self,
+    ) -> Result<(JoltDevice, Vec<JoltTraceStep<InstructionSet>>, Vec<F>), TraceError> {
+        self.check()?;
+        let memory_layout = self.memory_layout();
         let elf = self.elf.unwrap();
-        let (raw_trace, io_device) =
-            tracer::trace(&elf, &self.input, self.max_input_size, self.max_output_size);
-
-        let trace: Vec<_> = raw_trace
-            .into_par_iter()
-            .flat_map(|row| match row.instruction.opcode {
-                tracer::RV32IM::MULH => MULHInstruction::<32>::virtual_sequence(row),
-                tracer::RV32IM::MULHSU => MULHSUInstruction::<32>::virtual_sequence(row),
-                tracer::RV32IM::DIV => todo!(),
-                tracer::RV32IM::DIVU => todo!(),
-                tracer::RV32IM::REM => todo!(),
-                tracer::RV32IM::REMU => todo!(),
-                _ => vec![row],
-            })
-            .map(|row| {
-                let instruction_lookup = if let Ok(jolt_instruction) = RV32I::try_from(&row) {
-                    Some(jolt_instruction)
-                } else {
-                    // Instruction does not use lookups
-                    None
-                };
-
-                JoltTraceStep {
-                    instruction_lookup,
-                    bytecode_row: BytecodeRow::from_instruction::<RV32I>(&row.instruction),
-                    memory_ops: (&row).into(),
+        let (raw_trace, io_device) = tracer::trace(
+            &elf,
+            &self.input,
+            &self.advice,
+            &memory_layout,
+            Some(&self.hints),
+            self.max_cycles,
+        )?;
+
+        let trace = expand_trace::<InstructionSet>(raw_trace);
+        let circuit_flag_trace = circuit_flags(&trace, trace.len().next_power_of_two());
+
+        Ok((io_device, trace, circuit_flag_trace))
+    }
+
+    /// Traces the program like `trace`, but treats a guest panic as an error, reported as
+    /// `TraceError::GuestPanic` with the guest's panic message, if any. A panicking program
+    /// can still be proven from the output of `trace`.
+    pub fn trace_checked<InstructionSet: JoltInstructionSet, F: JoltField>(
+        self,
+    ) -> Result<(JoltDevice, Vec<JoltTraceStep<InstructionSet>>, Vec<F>), TraceError> {
+        let (io_device, trace, circuit_flag_trace) = self.trace::<InstructionSet, F>()?;
+        if io_device.panic {
+            return Err(TraceError::GuestPanic(io_device.panic_message()));
+        }
+        Ok((io_device, trace, circuit_flag_trace))
+    }
+
+    /// Traces the program like `trace`, but splits its execution into segments of
+    /// `segment_length` RISC-V instructions, which can be proven separately with
+    /// `Jolt::prove_segment` and checked together with `Jolt::verify_segments`. Segments run
+    /// a few instructions longer where needed to keep virtual sequences away from their
+    /// boundaries, which must lie between two instructions that are not virtual sequences.
+    ///
+    /// Segments are traced lazily. Every segment's memory witness spans the program's entire
+    /// memory (see `set_memory_size`), so that consecutive segments' memory states line up.
+    pub fn trace_segments<InstructionSet: JoltInstructionSet, F: JoltField>(
+        mut self,
+        segment_length: u64,
+    ) -> Result<impl Iterator<Item = Result<JoltSegment<InstructionSet, F>, TraceError>>, TraceError>
+    {
+        self.check()?;
+        let memory_layout = self.memory_layout();
+        let elf = self.elf.unwrap();
+        let memory_size = (memory_layout.ram_witness_offset + self.memory_config.memory_size)
+            .next_power_of_two() as usize;
+        // Every segment's memory must hold all hint responses from the start, so the program
+        // is first run to completion to collect them, without keeping its trace, and then
+        // replayed in segments.
+        let (advice, hints) = if self.hints.is_empty() {
+            (self.advice, Some(&self.hints))
+        } else {
+            let io_device = tracer::run(
+                &elf,
+                &self.input,
+                &self.advice,
+                &memory_layout,
+                Some(&self.hints),
+                self.max_cycles,
+            )?;
+            (io_device.advice, None)
+        };
+        let segments = tracer::trace_segments(
+            &elf,
+            &self.input,
+            &advice,
+            &memory_layout,
+            hints,
+            self.max_cycles,
+            segment_length,
+        )?
+        .exclude_from_boundaries(is_virtual_sequence);
+
+        // The emulator has no notion of virtual registers, so their values at each segment
+        // boundary are recovered from the preceding segments' traces.
+        let mut registers = vec![0u64; REGISTER_COUNT as usize];
+        let segments = segments.enumerate().map(move |(index, segment)| {
+            let segment = segment?;
+            let mut initial_state = segment.initial_state;
+            initial_state
+                .registers
+                .extend_from_slice(&registers[initial_state.registers.len()..]);
+
+            let trace = expand_trace::<InstructionSet>(segment.rows);
+            for step in trace.iter() {
+                // memory_ops[2] is the rd write
+                if let MemoryOp::Write(register, value) = step.memory_ops[2] {
+                    registers[register as usize] = value;
                 }
-            })
-            .collect();
-        let padded_trace_len = trace.len().next_power_of_two();
-
-        let mut circuit_flag_trace = unsafe_allocate_zero_vec(padded_trace_len * NUM_CIRCUIT_FLAGS);
-        circuit_flag_trace
-            .par_chunks_mut(padded_trace_len)
-            .enumerate()
-            .for_each(|(flag_index, chunk)| {
-                chunk.iter_mut().zip(trace.iter()).for_each(|(flag, row)| {
-                    let packed_circuit_flags = row.bytecode_row.bitflags >> RV32I::COUNT;
-                    // Check if the flag is set in the packed representation
-                    if (packed_circuit_flags >> (NUM_CIRCUIT_FLAGS - flag_index - 1)) & 1 != 0 {
-                        *flag = F::one();
-                    }
-                });
-            });
+            }
+            let circuit_flags = circuit_flags(
+                &trace,
+                JoltSegment::<InstructionSet, F>::padded_trace_length(trace.len()),
+            );
 
-        (io_device, trace, circuit_flag_trace)
+            Ok(JoltSegment {
+                index,
+                trace,
+                circuit_flags,
+                initial_state,
+                program_io: segment.device,
+                memory_size,
+            })
+        });
+        Ok(segments)
     }
 
-    pub fn trace_analyze<F: JoltField>(mut self) -> ProgramSummary {
-        self.build();
+    pub fn trace_analyze<InstructionSet: JoltInstructionSet, F: JoltField>(
+        mut self,
+    ) -> Result<ProgramSummary<InstructionSet>, TraceError> {
+        self.check()?;
         let elf = self.elf.as_ref().unwrap();
-        let (raw_trace, _) =
-            tracer::trace(elf, &self.input, self.max_input_size, self.max_output_size);
+        let (raw_trace, _) = tracer::trace(
+            elf,
+            &self.input,
+            &self.advice,
+            &self.memory_layout(),
+            Some(&self.hints),
+            self.max_cycles,
+        )?;
 
         let (bytecode, memory_init) = self.decode();
-        let (io_device, processed_trace, circuit_flags) = self.trace();
+        let (io_device, processed_trace, circuit_flags) = self.trace::<InstructionSet, F>()?;
         let circuit_flags: Vec<bool> = circuit_flags
             .into_iter()
             .map(|flag: F| flag.is_one())
             .collect();
 
-        ProgramSummary {
+        Ok(ProgramSummary {
             raw_trace,
             bytecode,
             memory_init,
             io_device,
             processed_trace,
             circuit_flags,
-        }
+        })
     }
 
-    fn save_linker(&self) {
-        let linker_path = PathBuf::from_str(&self.linker_path()).unwrap();
+    fn save_linker(&self, linker_path: &Path) {
         if let Some(parent) = linker_path.parent() {
             fs::create_dir_all(parent).expect("could not create linker file");
         }
 
         let linker_script = LINKER_SCRIPT_TEMPLATE
-            .replace("{MEMORY_SIZE}", &self.memory_size.to_string())
-            .replace("{STACK_SIZE}", &self.stack_size.to_string());
+            .replace(
+                "{RAM_START}",
+                &format!("{:#x}", self.memory_config.ram_start),
+            )
+            .replace("{MEMORY_SIZE}", &self.memory_config.memory_size.to_string())
+            .replace("{STACK_SIZE}", &self.memory_config.stack_size.to_string());
 
         let mut file = File::create(linker_path).expect("could not create linker file");
         file.write_all(linker_script.as_bytes())
             .expect("could not save linker");
     }
+}
 
-    fn linker_path(&self) -> String {
-        format!("/tmp/jolt-guest-linkers/{}.ld", self.guest)
-    }
+/// Whether Jolt implements `opcode` as a virtual sequence (see `expand_trace`).
+fn is_virtual_sequence(opcode: tracer::RV32IM) -> bool {
+    matches!(
+        opcode,
+        tracer::RV32IM::MULH
+            | tracer::RV32IM::MULHSU
+            | tracer::RV32IM::DIV
+            | tracer::RV32IM::DIVU
+            | tracer::RV32IM::REM
+            | tracer::RV32IM::REMU
+    )
+}
+
+/// Expands a RISC-V execution trace into Jolt trace steps, replacing instructions that
+/// Jolt implements as virtual sequences by those sequences.
+fn expand_trace<InstructionSet: JoltInstructionSet>(
+    raw_trace: Vec<RVTraceRow>,
+) -> Vec<JoltTraceStep<InstructionSet>> {
+    raw_trace
+        .into_par_iter()
+        .flat_map(|row| match row.instruction.opcode {
+            tracer::RV32IM::MULH => MULHInstruction::<32>::virtual_trace(row),
+            tracer::RV32IM::MULHSU => MULHSUInstruction::<32>::virtual_trace(row),
+            tracer::RV32IM::DIV => DIVInstruction::<32>::virtual_trace(row),
+            tracer::RV32IM::DIVU => DIVUInstruction::<32>::virtual_trace(row),
+            tracer::RV32IM::REM => REMInstruction::<32>::virtual_trace(row),
+            tracer::RV32IM::REMU => REMUInstruction::<32>::virtual_trace(row),
+            _ => vec![row],
+        })
+        .map(|row| {
+            let instruction_lookup = if let Ok(jolt_instruction) = InstructionSet::try_from(&row) {
+                Some(jolt_instruction)
+            } else {
+                // Instruction does not use lookups
+                None
+            };
+
+            JoltTraceStep {
+                instruction_lookup,
+                bytecode_row: BytecodeRow::from_instruction::<InstructionSet>(&row.instruction),
+                memory_ops: (&row).into(),
+            }
+        })
+        .collect()
+}
+
+/// Unpacks the circuit flags of each trace step into `NUM_CIRCUIT_FLAGS` vectors of length
+/// `padded_trace_len`, concatenated.
+fn circuit_flags<InstructionSet: JoltInstructionSet, F: JoltField>(
+    trace: &[JoltTraceStep<InstructionSet>],
+    padded_trace_len: usize,
+) -> Vec<F> {
+    let mut circuit_flag_trace = unsafe_allocate_zero_vec(padded_trace_len * NUM_CIRCUIT_FLAGS);
+    circuit_flag_trace
+        .par_chunks_mut(padded_trace_len)
+        .enumerate()
+        .for_each(|(flag_index, chunk)| {
+            chunk.iter_mut().zip(trace.iter()).for_each(|(flag, row)| {
+                let packed_circuit_flags = row.bytecode_row.bitflags >> InstructionSet::COUNT;
+                // Check if the flag is set in the packed representation
+                if (packed_circuit_flags >> (NUM_CIRCUIT_FLAGS - flag_index - 1)) & 1 != 0 {
+                    *flag = F::one();
+                }
+            });
+        });
+    circuit_flag_trace
 }
 
 const LINKER_SCRIPT_TEMPLATE: &str = r#"
 MEMORY {
-  program (rwx) : ORIGIN = 0x80000000, LENGTH = {MEMORY_SIZE}
+  program (rwx) : ORIGIN = {RAM_START}, LENGTH = {MEMORY_SIZE}
 }
 
 SECTIONS {
@@ -288,5 +582,70 @@ SECTIONS {
   _STACK_PTR = .;
   . = ALIGN(8);
   _HEAP_PTR = .;
+  _HEAP_END = ORIGIN(program) + LENGTH(program);
 }
 "#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracer::test_utils::{elf_with_text, words, write_elf};
+
+    /// Writes a 32-bit RISC-V ELF with a single `.text` section holding a `nop` at `address`.
+    fn write_nop_elf(address: u32, entry: u32) -> PathBuf {
+        write_elf(&elf_with_text(address, &words(&[0x13]), entry))
+    }
+
+    #[test]
+    fn from_elf_accepts_program_in_ram() {
+        let config = MemoryConfig::default();
+        let ram_start = config.ram_start as u32;
+        let path = write_nop_elf(ram_start, ram_start);
+
+        let program = Program::from_elf(&path, config.clone()).unwrap();
+        assert_eq!(program.elf, Some(path.clone()));
+        assert_eq!(program.memory_config, config);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn from_elf_rejects_section_outside_ram() {
+        let config = MemoryConfig::default();
+        let ram_end = (config.ram_start + config.memory_size) as u32;
+        let path = write_nop_elf(ram_end - 2, config.ram_start as u32);
+
+        let result = Program::from_elf(&path, config);
+        fs::remove_file(path).unwrap();
+        assert!(matches!(
+            result,
+            Err(TraceError::SectionOutOfRange { start, end, .. })
+                if start == ram_end as u64 - 2 && end == ram_end as u64 + 2
+        ));
+    }
+
+    #[test]
+    fn from_elf_rejects_entry_outside_ram() {
+        let config = MemoryConfig::default();
+        let path = write_nop_elf(config.ram_start as u32, 0x1000);
+
+        let result = Program::from_elf(&path, config);
+        fs::remove_file(path).unwrap();
+        assert!(matches!(result, Err(TraceError::EntryOutOfRange(0x1000))));
+    }
+
+    #[test]
+    fn from_elf_checks_against_given_memory_config() {
+        // Linked at the default RAM start, but loaded with RAM elsewhere
+        let default_start = MemoryConfig::default().ram_start as u32;
+        let path = write_nop_elf(default_start, default_start);
+        let config = MemoryConfig {
+            ram_start: 0x4000_0000,
+            memory_size: 0x1000,
+            ..Default::default()
+        };
+
+        let result = Program::from_elf(&path, config);
+        fs::remove_file(path).unwrap();
+        assert!(matches!(result, Err(TraceError::SectionOutOfRange { .. })));
+    }
+}
@@ -1,14 +1,15 @@
 use std::{
     fs::{self, read_to_string, File},
     future::Future,
-    io::Write,
-    path::PathBuf,
+    io::{self, Write},
+    path::{Path, PathBuf},
 };
 
 use dirs::home_dir;
 use eyre::{bail, eyre, Result};
 use indicatif::{ProgressBar, ProgressStyle};
 use reqwest::Client;
+use sha2::{Digest, Sha256};
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::runtime::Runtime;
 
@@ -16,9 +17,21 @@ const TOOLCHAIN_TAG: &str = include_str!("../../../.jolt.rust.toolchain-tag");
 const DOWNLOAD_RETRIES: usize = 5;
 const DELAY_BASE_MS: u64 = 500;
 
+/// Environment variable pointing to a local toolchain archive or unpacked toolchain, which
+/// is installed instead of downloading one.
+pub const TOOLCHAIN_PATH_ENV: &str = "JOLT_TOOLCHAIN_PATH";
+/// Environment variable holding the expected SHA-256 of the archive in `JOLT_TOOLCHAIN_PATH`.
+pub const TOOLCHAIN_SHA256_ENV: &str = "JOLT_TOOLCHAIN_SHA256";
+
 #[cfg(not(target_arch = "wasm32"))]
-/// Installs the toolchain if it is not already
+/// Installs the toolchain if it is not already, from `JOLT_TOOLCHAIN_PATH` if it is set
+/// (see `install_toolchain_from`), and by downloading it otherwise.
 pub fn install_toolchain() -> Result<()> {
+    if let Some(path) = std::env::var_os(TOOLCHAIN_PATH_ENV) {
+        let sha256 = std::env::var(TOOLCHAIN_SHA256_ENV).ok();
+        return install_toolchain_from(Path::new(&path), sha256.as_deref());
+    }
+
     if !has_toolchain() {
         let client = Client::builder().user_agent("Mozilla/5.0").build()?;
         let toolchain_url = toolchain_url();
@@ -27,10 +40,75 @@ pub fn install_toolchain() -> Result<()> {
         rt.block_on(retry_times(DOWNLOAD_RETRIES, DELAY_BASE_MS, || {
             download_toolchain(&client, &toolchain_url)
         }))?;
-        unpack_toolchain()?;
+        unpack_toolchain(&jolt_dir().join("rust-toolchain.tar.gz"))?;
+        write_tag_file()?;
+    }
+    link_toolchain(&toolchain_dir())
+}
+
+/// Installs the toolchain without network access, from either a release archive
+/// (`rust-toolchain-<target>.tar.gz`) or a directory holding an unpacked toolchain, which
+/// is used in place. An archive is only unpacked after checking it against `sha256`, or if
+/// that is `None`, against the checksum in `<archive>.sha256`.
+pub fn install_toolchain_from(path: &Path, sha256: Option<&str>) -> Result<()> {
+    if path.is_dir() {
+        if !path.join("bin").join("rustc").exists() {
+            bail!("{} does not contain a Rust toolchain", path.display());
+        }
+        return link_toolchain(path);
+    }
+
+    if !has_toolchain() {
+        let expected = match sha256 {
+            Some(sha256) => sha256.to_string(),
+            None => {
+                let checksum_file = PathBuf::from(format!("{}.sha256", path.display()));
+                let checksum = read_to_string(&checksum_file).map_err(|_| {
+                    eyre!(
+                        "no checksum for {}: pass one with --sha256 or {}, or save it to {}",
+                        path.display(),
+                        TOOLCHAIN_SHA256_ENV,
+                        checksum_file.display()
+                    )
+                })?;
+                parse_checksum(&checksum).to_string()
+            }
+        };
+        verify_checksum(path, &expected)?;
+
+        fs::create_dir_all(jolt_dir())?;
+        unpack_toolchain(&fs::canonicalize(path)?)?;
         write_tag_file()?;
     }
-    link_toolchain()
+    link_toolchain(&toolchain_dir())
+}
+
+/// Extracts the checksum from the contents of a checksum file, which may be in the
+/// `sha256sum` format, i.e. the checksum followed by the file name.
+fn parse_checksum(contents: &str) -> &str {
+    contents.split_whitespace().next().unwrap_or_default()
+}
+
+fn verify_checksum(path: &Path, expected: &str) -> Result<()> {
+    let mut archive =
+        File::open(path).map_err(|e| eyre!("failed to read {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut archive, &mut hasher)
+        .map_err(|e| eyre!("failed to read {}: {}", path.display(), e))?;
+    let actual: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        bail!(
+            "checksum mismatch for {}: expected {}, got {}",
+            path.display(),
+            expected.trim(),
+            actual
+        );
+    }
+    Ok(())
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -43,6 +121,15 @@ where
         println!("Attempt {}/{}", i + 1, times);
         match f().await {
             Ok(t) => return Ok(t),
+            // Retrying won't help without a network connection
+            Err(e) if is_connect_error(&e) => {
+                bail!(
+                    "could not connect to download the toolchain ({}). To install it offline, \
+                     run `jolt install-toolchain --from <archive>` or set {}",
+                    e,
+                    TOOLCHAIN_PATH_ENV
+                );
+            }
             Err(e) => {
                 let timeout = delay_timeout(i, base_ms);
                 println!("Toolchain download error {i}/{times}: {e}. Retrying in {timeout}ms");
@@ -53,6 +140,14 @@ where
     Err(eyre!("failed after {} retries", times))
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn is_connect_error(error: &eyre::Report) -> bool {
+    error
+        .chain()
+        .filter_map(|e| e.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_connect() || e.is_timeout())
+}
+
 fn delay_timeout(i: usize, base_ms: u64) -> u64 {
     let timeout = 2u64.pow(i as u32) * base_ms;
     rand::random::<u64>() % timeout
@@ -65,15 +160,10 @@ fn write_tag_file() -> Result<()> {
     Ok(())
 }
 
-fn link_toolchain() -> Result<()> {
-    let link_path = jolt_dir().join("rust/build/host/stage2");
+fn link_toolchain(link_path: &Path) -> Result<()> {
     let output = std::process::Command::new("rustup")
-        .args([
-            "toolchain",
-            "link",
-            "riscv32i-jolt-zkvm-elf",
-            link_path.to_str().unwrap(),
-        ])
+        .args(["toolchain", "link", "riscv32i-jolt-zkvm-elf"])
+        .arg(link_path)
         .output()?;
 
     if !output.status.success() {
@@ -83,9 +173,10 @@ fn link_toolchain() -> Result<()> {
     Ok(())
 }
 
-fn unpack_toolchain() -> Result<()> {
+fn unpack_toolchain(archive: &Path) -> Result<()> {
     let output = std::process::Command::new("tar")
-        .args(["-xzf", "rust-toolchain.tar.gz"])
+        .arg("-xzf")
+        .arg(archive)
         .current_dir(jolt_dir())
         .output()?;
 
@@ -154,6 +245,49 @@ fn jolt_dir() -> PathBuf {
     home_dir().unwrap().join(".jolt")
 }
 
+/// The unpacked toolchain inside `jolt_dir`.
+fn toolchain_dir() -> PathBuf {
+    jolt_dir().join("rust/build/host/stage2")
+}
+
 fn toolchain_tag_file() -> PathBuf {
     jolt_dir().join(".toolchaintag")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_checksums() {
+        let checksum = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        assert_eq!(parse_checksum(checksum), checksum);
+        assert_eq!(parse_checksum(&format!("{}\n", checksum)), checksum);
+        assert_eq!(
+            parse_checksum(&format!("{}  rust-toolchain.tar.gz\n", checksum)),
+            checksum
+        );
+        assert_eq!(parse_checksum(""), "");
+    }
+
+    #[test]
+    fn verifies_checksums() {
+        let path = std::env::temp_dir().join(format!(
+            "jolt-toolchain-checksum-test-{}",
+            std::process::id()
+        ));
+        fs::write(&path, "test").unwrap();
+
+        // SHA-256 of "test"
+        let checksum = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        assert!(verify_checksum(&path, checksum).is_ok());
+        assert!(verify_checksum(&path, &format!(" {}\n", checksum.to_uppercase())).is_ok());
+
+        let error = verify_checksum(&path, &checksum.replace('9', "0")).unwrap_err();
+        assert!(error.to_string().contains("checksum mismatch"));
+        assert!(verify_checksum(&path, "").is_err());
+
+        fs::remove_file(&path).unwrap();
+        assert!(verify_checksum(&path, checksum).is_err());
+    }
+}
@@ -3,15 +3,19 @@ use tracer::{ELFInstruction, RVTraceRow, RegisterState, RV32IM};
 
 use super::VirtualInstructionSequence;
 use crate::jolt::instruction::{
-    add::ADDInstruction, beq::BEQInstruction, mul::MULInstruction,
-    virtual_advice::ADVICEInstruction, virtual_assert_valid_div0::AssertValidDiv0Instruction,
-    virtual_assert_valid_signed_remainder::AssertValidSignedRemainderInstruction, JoltInstruction,
+    add::ADDInstruction, and::ANDInstruction, beq::BEQInstruction, mul::MULInstruction,
+    slt::SLTInstruction, sltu::SLTUInstruction, virtual_advice::ADVICEInstruction,
+    virtual_assert_valid_div0::AssertValidDiv0Instruction,
+    virtual_assert_valid_signed_remainder::AssertValidSignedRemainderInstruction,
+    xor::XORInstruction, JoltInstruction,
 };
 /// Perform signed division and return the result
 pub struct DIVInstruction<const WORD_SIZE: usize>;
 
 impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_SIZE> {
-    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+    const SEQUENCE_LENGTH: usize = 14;
+
+    fn virtual_trace(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
         assert_eq!(trace_row.instruction.opcode, RV32IM::DIV);
         // DIV operands
         let x = trace_row.register_state.rs1_val.unwrap();
@@ -21,29 +25,46 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_
         let r_y = trace_row.instruction.rs2;
         // Virtual registers used in sequence
         let v_0 = Some(virtual_register_index(0));
-        let v_r: Option<u64> = Some(virtual_register_index(1));
-        let v_qy = Some(virtual_register_index(2));
+        let v_q = Some(virtual_register_index(1));
+        let v_r = Some(virtual_register_index(2));
+        let v_qy = Some(virtual_register_index(3));
+        let v_signs = Some(virtual_register_index(4));
+        let v_signs_differ = Some(virtual_register_index(5));
+        let v_r_nonzero = Some(virtual_register_index(6));
+        let v_y_nonzero = Some(virtual_register_index(7));
+        let v_adjust = Some(virtual_register_index(8));
 
         let mut virtual_sequence = vec![];
 
+        // The advice is computed using floor division (i.e. the remainder has the
+        // same sign as the divisor), which is what the signed remainder assertion
+        // checks. Division by zero yields a quotient of -1 and a remainder of x.
         let (quotient, remainder) = match WORD_SIZE {
             32 => {
-                let mut quotient = x as i32 / y as i32;
-                let mut remainder = x as i32 % y as i32;
-                if (remainder < 0 && (y as i32) > 0) || (remainder > 0 && (y as i32) < 0) {
-                    remainder += y as i32;
-                    quotient -= 1;
+                if y == 0 {
+                    (u32::MAX as u64, x)
+                } else {
+                    let mut quotient = (x as i32).wrapping_div(y as i32);
+                    let mut remainder = (x as i32).wrapping_rem(y as i32);
+                    if (remainder < 0 && (y as i32) > 0) || (remainder > 0 && (y as i32) < 0) {
+                        remainder += y as i32;
+                        quotient -= 1;
+                    }
+                    (quotient as u32 as u64, remainder as u32 as u64)
                 }
-                (quotient as u32 as u64, remainder as u32 as u64)
             }
             64 => {
-                let mut quotient = x as i64 / y as i64;
-                let mut remainder = x as i64 % y as i64;
-                if (remainder < 0 && (y as i64) > 0) || (remainder > 0 && (y as i64) < 0) {
-                    remainder += y as i64;
-                    quotient -= 1;
+                if y == 0 {
+                    (u64::MAX, x)
+                } else {
+                    let mut quotient = (x as i64).wrapping_div(y as i64);
+                    let mut remainder = (x as i64).wrapping_rem(y as i64);
+                    if (remainder < 0 && (y as i64) > 0) || (remainder > 0 && (y as i64) < 0) {
+                        remainder += y as i64;
+                        quotient -= 1;
+                    }
+                    (quotient as u64, remainder as u64)
                 }
-                (quotient as u64, remainder as u64)
             }
             _ => panic!("Unsupported WORD_SIZE: {}", WORD_SIZE),
         };
@@ -55,9 +76,11 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_
                 opcode: RV32IM::VIRTUAL_ADVICE,
                 rs1: None,
                 rs2: None,
-                rd: trace_row.instruction.rd,
+                rd: v_q,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: None,
@@ -65,7 +88,7 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_
                 rd_post_val: Some(q),
             },
             memory_state: None,
-            advice_value: Some(quotient), // What should advice value be here?
+            advice_value: Some(quotient),
         });
 
         let r = ADVICEInstruction::<WORD_SIZE>(remainder).lookup_entry();
@@ -77,7 +100,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_
                 rs2: None,
                 rd: v_r,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: None,
@@ -85,7 +110,7 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_
                 rd_post_val: Some(r),
             },
             memory_state: None,
-            advice_value: Some(remainder), // What should advice value be here?
+            advice_value: Some(remainder),
         });
 
         let is_valid: u64 = AssertValidSignedRemainderInstruction::<WORD_SIZE>(r, y).lookup_entry();
@@ -98,7 +123,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_
                 rs2: r_y,
                 rd: None,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(r),
@@ -116,10 +143,12 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_
                 address: trace_row.instruction.address,
                 opcode: RV32IM::VIRTUAL_ASSERT_VALID_DIV0,
                 rs1: r_y,
-                rs2: trace_row.instruction.rd,
+                rs2: v_q,
                 rd: None,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(y),
@@ -135,11 +164,13 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_
             instruction: ELFInstruction {
                 address: trace_row.instruction.address,
                 opcode: RV32IM::MUL,
-                rs1: trace_row.instruction.rd,
+                rs1: v_q,
                 rs2: r_y,
                 rd: v_qy,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(q),
@@ -159,7 +190,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_
                 rs2: v_r,
                 rd: v_0,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(q_y),
@@ -179,7 +212,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_
                 rs2: r_x,
                 rd: None,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(add_0),
@@ -190,6 +225,170 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for DIVInstruction<WORD_
             advice_value: None,
         });
 
+        // RISC-V signed division rounds towards zero, so the floored quotient
+        // must be incremented if the remainder is nonzero and the operands have
+        // different signs (and the divisor is nonzero).
+        let signs = XORInstruction(x, y).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::XOR,
+                rs1: r_x,
+                rs2: r_y,
+                rd: v_signs,
+                imm: None,
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
+            },
+            register_state: RegisterState {
+                rs1_val: Some(x),
+                rs2_val: Some(y),
+                rd_post_val: Some(signs),
+            },
+            memory_state: None,
+            advice_value: None,
+        });
+
+        let signs_differ = SLTInstruction(signs, 0).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::SLT,
+                rs1: v_signs,
+                rs2: Some(0),
+                rd: v_signs_differ,
+                imm: None,
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
+            },
+            register_state: RegisterState {
+                rs1_val: Some(signs),
+                rs2_val: Some(0),
+                rd_post_val: Some(signs_differ),
+            },
+            memory_state: None,
+            advice_value: None,
+        });
+
+        let r_nonzero = SLTUInstruction(0, r).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::SLTU,
+                rs1: Some(0),
+                rs2: v_r,
+                rd: v_r_nonzero,
+                imm: None,
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
+            },
+            register_state: RegisterState {
+                rs1_val: Some(0),
+                rs2_val: Some(r),
+                rd_post_val: Some(r_nonzero),
+            },
+            memory_state: None,
+            advice_value: None,
+        });
+
+        let y_nonzero = SLTUInstruction(0, y).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::SLTU,
+                rs1: Some(0),
+                rs2: r_y,
+                rd: v_y_nonzero,
+                imm: None,
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
+            },
+            register_state: RegisterState {
+                rs1_val: Some(0),
+                rs2_val: Some(y),
+                rd_post_val: Some(y_nonzero),
+            },
+            memory_state: None,
+            advice_value: None,
+        });
+
+        let adjust = ANDInstruction(signs_differ, r_nonzero).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::AND,
+                rs1: v_signs_differ,
+                rs2: v_r_nonzero,
+                rd: v_adjust,
+                imm: None,
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
+            },
+            register_state: RegisterState {
+                rs1_val: Some(signs_differ),
+                rs2_val: Some(r_nonzero),
+                rd_post_val: Some(adjust),
+            },
+            memory_state: None,
+            advice_value: None,
+        });
+
+        let adjust_nonzero_divisor = ANDInstruction(adjust, y_nonzero).lookup_entry();
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::AND,
+                rs1: v_adjust,
+                rs2: v_y_nonzero,
+                rd: v_adjust,
+                imm: None,
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
+            },
+            register_state: RegisterState {
+                rs1_val: Some(adjust),
+                rs2_val: Some(y_nonzero),
+                rd_post_val: Some(adjust_nonzero_divisor),
+            },
+            memory_state: None,
+            advice_value: None,
+        });
+
+        let quotient = ADDInstruction::<WORD_SIZE>(q, adjust_nonzero_divisor).lookup_entry();
+        // Writes to x0 are discarded, so it still reads as zero afterwards
+        let rd_post_val = if trace_row.instruction.rd == Some(0) {
+            0
+        } else {
+            quotient
+        };
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::ADD,
+                rs1: v_q,
+                rs2: v_adjust,
+                rd: trace_row.instruction.rd,
+                imm: None,
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
+            },
+            register_state: RegisterState {
+                rs1_val: Some(q),
+                rs2_val: Some(adjust_nonzero_divisor),
+                rd_post_val: Some(rd_post_val),
+            },
+            memory_state: None,
+            advice_value: None,
+        });
+
+        assert_eq!(virtual_sequence.len(), Self::SEQUENCE_LENGTH);
         virtual_sequence
     }
 }
@@ -204,45 +403,41 @@ mod test {
 
     use super::*;
 
-    #[test]
-    // TODO(moodlezoup): Turn this into a macro, similar to the `jolt_instruction_test` macro
-    fn div_virtual_sequence_32() {
-        let mut rng = test_rng();
-
-        let r_x = rng.next_u64() % 32;
-        let r_y = rng.next_u64() % 32;
-        let rd = rng.next_u64() % 32;
-
-        let x = rng.next_u32() as u64;
-        let y = if r_y == r_x { x } else { rng.next_u32() as u64 };
-
-        let mut quotient = x as i32 / y as i32;
-        let remainder = x as i32 % y as i32;
-        if (remainder < 0 && (y as i32) > 0) || (remainder > 0 && (y as i32) < 0) {
-            quotient -= 1;
+    fn div_result_32(x: u64, y: u64) -> u64 {
+        if y == 0 {
+            u32::MAX as u64
+        } else {
+            (x as i32).wrapping_div(y as i32) as u32 as u64
         }
-        let result = quotient as u32 as u64;
+    }
+
+    fn test_div_virtual_sequence_32(r_x: u64, r_y: u64, rd: u64, x: u64, y: u64) {
+        let result = div_result_32(x, y);
 
         let div_trace_row = RVTraceRow {
             instruction: ELFInstruction {
-                address: rng.next_u64(),
+                address: 0x80000000,
                 opcode: RV32IM::DIV,
                 rs1: Some(r_x),
                 rs2: Some(r_y),
                 rd: Some(rd),
                 imm: None,
-                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
                 rs2_val: Some(y),
-                rd_post_val: Some(result as u64),
+                rd_post_val: Some(if rd == 0 { 0 } else { result }),
             },
             memory_state: None,
             advice_value: None,
         };
 
-        let virtual_sequence = DIVInstruction::<32>::virtual_sequence(div_trace_row);
+        let virtual_sequence = DIVInstruction::<32>::virtual_trace(div_trace_row);
+        assert_eq!(
+            virtual_sequence.len(),
+            DIVInstruction::<32>::SEQUENCE_LENGTH
+        );
         let mut registers = vec![0u64; REGISTER_COUNT as usize];
         registers[r_x as usize] = x;
         registers[r_y as usize] = y;
@@ -258,7 +453,10 @@ mod test {
             let lookup = RV32I::try_from(&row).unwrap();
             let output = lookup.lookup_entry();
             if let Some(rd) = row.instruction.rd {
-                registers[rd as usize] = output;
+                // Writes to x0 are discarded
+                if rd != 0 {
+                    registers[rd as usize] = output;
+                }
                 assert_eq!(
                     registers[rd as usize],
                     row.register_state.rd_post_val.unwrap()
@@ -269,19 +467,68 @@ mod test {
         }
 
         for (index, val) in registers.iter().enumerate() {
-            if index as u64 == r_x {
+            if index as u64 == rd && rd != 0 {
+                // Check that result was written to rd
+                assert_eq!(*val, result);
+            } else if index as u64 == r_x {
                 // Check that r_x hasn't been clobbered
                 assert_eq!(*val, x);
             } else if index as u64 == r_y {
                 // Check that r_y hasn't been clobbered
                 assert_eq!(*val, y);
-            } else if index as u64 == rd {
-                // Check that result was written to rd
-                assert_eq!(*val, result as u64);
             } else if index < 32 {
                 // None of the other "real" registers were touched
                 assert_eq!(*val, 0);
             }
         }
     }
+
+    #[test]
+    // TODO(moodlezoup): Turn this into a macro, similar to the `jolt_instruction_test` macro
+    fn div_virtual_sequence_32() {
+        let mut rng = test_rng();
+        for _ in 0..256 {
+            let r_x = rng.next_u64() % 32;
+            let r_y = rng.next_u64() % 32;
+            let rd = rng.next_u64() % 32;
+
+            // x0 always reads as zero
+            let x = if r_x == 0 { 0 } else { rng.next_u32() as u64 };
+            let y = if r_y == 0 {
+                0
+            } else if r_y == r_x {
+                x
+            } else {
+                rng.next_u32() as u64
+            };
+            test_div_virtual_sequence_32(r_x, r_y, rd, x, y);
+        }
+
+        // Edge-cases
+        let i32_min = i32::MIN as u32 as u64;
+        let minus_one = -1i32 as u32 as u64;
+        let minus_seven = -7i32 as u32 as u64;
+        let operands = [
+            (7, 2),
+            (minus_seven, 2),
+            (7, -2i32 as u32 as u64),
+            (minus_seven, -2i32 as u32 as u64),
+            (1234, 0),
+            (minus_seven, 0),
+            (0, 0),
+            (i32_min, minus_one),
+            (i32_min, 1),
+            (minus_one, i32_min),
+        ];
+        for (x, y) in operands {
+            test_div_virtual_sequence_32(1, 2, 3, x, y);
+            // Destination register aliases the source registers
+            test_div_virtual_sequence_32(1, 2, 1, x, y);
+            test_div_virtual_sequence_32(1, 2, 2, x, y);
+            // x0 as the destination or a source register
+            test_div_virtual_sequence_32(1, 2, 0, x, y);
+            test_div_virtual_sequence_32(0, 2, 3, 0, y);
+            test_div_virtual_sequence_32(1, 0, 3, x, 0);
+        }
+    }
 }
@@ -5,7 +5,7 @@ use serde::Serialize;
 use std::marker::Sync;
 use std::ops::Range;
 use strum::{EnumCount, IntoEnumIterator};
-use tracer::RVTraceRow;
+use tracer::{RVTraceRow, RegisterState};
 
 use crate::field::JoltField;
 use crate::jolt::subtable::LassoSubtable;
@@ -71,7 +71,13 @@ pub trait JoltInstruction: Clone + Debug + Send + Sync + Serialize {
 }
 
 pub trait JoltInstructionSet:
-    JoltInstruction + IntoEnumIterator + EnumCount + for<'a> TryFrom<&'a ELFInstruction> + Send + Sync
+    JoltInstruction
+    + IntoEnumIterator
+    + EnumCount
+    + for<'a> TryFrom<&'a ELFInstruction>
+    + for<'a> TryFrom<&'a RVTraceRow>
+    + Send
+    + Sync
 {
     fn enum_index(instruction: &Self) -> usize {
         // Discriminant: https://doc.rust-lang.org/reference/items/enumerations.html#pointer-casting
@@ -126,7 +132,29 @@ impl From<Range<usize>> for SubtableIndices {
 }
 
 pub trait VirtualInstructionSequence {
-    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow>;
+    /// The number of virtual instructions that this instruction expands into.
+    const SEQUENCE_LENGTH: usize;
+
+    /// Expands `instruction` into its virtual sequence, for bytecode preprocessing.
+    fn virtual_sequence(instruction: ELFInstruction) -> Vec<ELFInstruction> {
+        let dummy_trace_row = RVTraceRow {
+            instruction,
+            register_state: RegisterState {
+                rs1_val: Some(0),
+                rs2_val: Some(0),
+                rd_post_val: Some(0),
+            },
+            memory_state: None,
+            advice_value: None,
+        };
+        Self::virtual_trace(dummy_trace_row)
+            .into_iter()
+            .map(|trace_row| trace_row.instruction)
+            .collect()
+    }
+
+    /// Expands `trace_row` into the trace rows of its virtual sequence.
+    fn virtual_trace(trace_row: RVTraceRow) -> Vec<RVTraceRow>;
 }
 
 pub mod add;
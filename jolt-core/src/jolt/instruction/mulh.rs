@@ -10,7 +10,9 @@ use crate::jolt::instruction::{
 pub struct MULHInstruction<const WORD_SIZE: usize>;
 
 impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD_SIZE> {
-    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+    const SEQUENCE_LENGTH: usize = 7;
+
+    fn virtual_trace(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
         assert_eq!(trace_row.instruction.opcode, RV32IM::MULH);
         // MULH operands
         let x = trace_row.register_state.rs1_val.unwrap();
@@ -37,7 +39,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rs2: None,
                 rd: v_sx,
                 imm: None,
-                virtual_sequence_index: Some(0),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
@@ -57,7 +61,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rs2: None,
                 rd: v_sy,
                 imm: None,
-                virtual_sequence_index: Some(1),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(y),
@@ -77,7 +83,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rs2: r_y,
                 rd: v_0,
                 imm: None,
-                virtual_sequence_index: Some(2),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
@@ -97,7 +105,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rs2: r_y,
                 rd: v_1,
                 imm: None,
-                virtual_sequence_index: Some(3),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(s_x),
@@ -117,7 +127,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rs2: r_x,
                 rd: v_2,
                 imm: None,
-                virtual_sequence_index: Some(4),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(s_y),
@@ -137,7 +149,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rs2: v_1,
                 rd: v_3,
                 imm: None,
-                virtual_sequence_index: Some(5),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(xy_high_bits),
@@ -157,7 +171,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHInstruction<WORD
                 rs2: v_2,
                 rd: trace_row.instruction.rd,
                 imm: None,
-                virtual_sequence_index: Some(6),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(partial_sum),
@@ -202,7 +218,7 @@ mod test {
                 rs2: Some(r_y),
                 rd: Some(rd),
                 imm: None,
-                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
@@ -213,7 +229,7 @@ mod test {
             advice_value: None,
         };
 
-        let virtual_sequence = MULHInstruction::<32>::virtual_sequence(mulh_trace_row);
+        let virtual_sequence = MULHInstruction::<32>::virtual_trace(mulh_trace_row);
         let mut registers = vec![0u64; REGISTER_COUNT as usize];
         registers[r_x as usize] = x;
         registers[r_y as usize] = y;
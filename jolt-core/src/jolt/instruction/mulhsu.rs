@@ -11,7 +11,9 @@ use crate::jolt::instruction::{
 pub struct MULHSUInstruction<const WORD_SIZE: usize>;
 
 impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHSUInstruction<WORD_SIZE> {
-    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+    const SEQUENCE_LENGTH: usize = 4;
+
+    fn virtual_trace(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
         assert_eq!(trace_row.instruction.opcode, RV32IM::MULHSU);
         // MULHSU operands
         let x = trace_row.register_state.rs1_val.unwrap();
@@ -35,7 +37,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHSUInstruction<WO
                 rs2: None,
                 rd: v_sx,
                 imm: None,
-                virtual_sequence_index: Some(0),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
@@ -55,7 +59,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHSUInstruction<WO
                 rs2: r_y,
                 rd: v_1,
                 imm: None,
-                virtual_sequence_index: Some(1),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
@@ -75,7 +81,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHSUInstruction<WO
                 rs2: r_y,
                 rd: v_2,
                 imm: None,
-                virtual_sequence_index: Some(2),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(s_x),
@@ -95,7 +103,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for MULHSUInstruction<WO
                 rs2: v_2,
                 rd: trace_row.instruction.rd,
                 imm: None,
-                virtual_sequence_index: Some(3),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(xy_high_bits),
@@ -140,7 +150,7 @@ mod test {
                 rs2: Some(r_y),
                 rd: Some(rd),
                 imm: None,
-                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
@@ -151,7 +161,7 @@ mod test {
             advice_value: None,
         };
 
-        let virtual_sequence = MULHSUInstruction::<32>::virtual_sequence(mulhsu_trace_row);
+        let virtual_sequence = MULHSUInstruction::<32>::virtual_trace(mulhsu_trace_row);
         let mut registers = vec![0u64; REGISTER_COUNT as usize];
         registers[r_x as usize] = x;
         registers[r_y as usize] = y;
@@ -13,7 +13,9 @@ use crate::jolt::instruction::{
 pub struct REMUInstruction<const WORD_SIZE: usize>;
 
 impl<const WORD_SIZE: usize> VirtualInstructionSequence for REMUInstruction<WORD_SIZE> {
-    fn virtual_sequence(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
+    const SEQUENCE_LENGTH: usize = 8;
+
+    fn virtual_trace(trace_row: RVTraceRow) -> Vec<RVTraceRow> {
         assert_eq!(trace_row.instruction.opcode, RV32IM::REMU);
         // REMU operands
         let x = trace_row.register_state.rs1_val.unwrap();
@@ -24,12 +26,20 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for REMUInstruction<WORD
         // Virtual registers used in sequence
         let v_0 = Some(virtual_register_index(0));
         let v_q = Some(virtual_register_index(1));
-        let v_qy = Some(virtual_register_index(2));
+        let v_r = Some(virtual_register_index(2));
+        let v_qy = Some(virtual_register_index(3));
 
         let mut virtual_sequence = vec![];
 
-        let quotient = x / y;
-        let remainder = x - quotient * y;
+        let (quotient, remainder) = match y {
+            // Division by zero yields a quotient with all bits set and a remainder of x
+            0 => match WORD_SIZE {
+                32 => (u32::MAX as u64, x),
+                64 => (u64::MAX, x),
+                _ => panic!("Unsupported WORD_SIZE: {}", WORD_SIZE),
+            },
+            _ => (x / y, x % y),
+        };
 
         let q = ADVICEInstruction::<WORD_SIZE>(quotient).lookup_entry();
         virtual_sequence.push(RVTraceRow {
@@ -40,7 +50,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for REMUInstruction<WORD
                 rs2: None,
                 rd: v_q,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: None,
@@ -58,9 +70,11 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for REMUInstruction<WORD
                 opcode: RV32IM::VIRTUAL_ADVICE,
                 rs1: None,
                 rs2: None,
-                rd: trace_row.instruction.rd,
+                rd: v_r,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: None,
@@ -80,7 +94,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for REMUInstruction<WORD
                 rs2: r_y,
                 rd: v_qy,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(q),
@@ -97,11 +113,13 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for REMUInstruction<WORD
             instruction: ELFInstruction {
                 address: trace_row.instruction.address,
                 opcode: RV32IM::VIRTUAL_ASSERT_VALID_UNSIGNED_REMAINDER,
-                rs1: trace_row.instruction.rd,
+                rs1: v_r,
                 rs2: r_y,
                 rd: None,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(r),
@@ -121,7 +139,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for REMUInstruction<WORD
                 rs2: r_x,
                 rd: None,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(q_y),
@@ -138,10 +158,12 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for REMUInstruction<WORD
                 address: trace_row.instruction.address,
                 opcode: RV32IM::ADD,
                 rs1: v_qy,
-                rs2: trace_row.instruction.rd,
+                rs2: v_r,
                 rd: v_0,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(q_y),
@@ -161,7 +183,9 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for REMUInstruction<WORD
                 rs2: r_x,
                 rd: None,
                 imm: None,
-                virtual_sequence_index: Some(virtual_sequence.len()),
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
             },
             register_state: RegisterState {
                 rs1_val: Some(add_0),
@@ -172,6 +196,35 @@ impl<const WORD_SIZE: usize> VirtualInstructionSequence for REMUInstruction<WORD
             advice_value: None,
         });
 
+        let remainder = ADDInstruction::<WORD_SIZE>(r, 0).lookup_entry();
+        // Writes to x0 are discarded, so it still reads as zero afterwards
+        let rd_post_val = if trace_row.instruction.rd == Some(0) {
+            0
+        } else {
+            remainder
+        };
+        virtual_sequence.push(RVTraceRow {
+            instruction: ELFInstruction {
+                address: trace_row.instruction.address,
+                opcode: RV32IM::ADD,
+                rs1: v_r,
+                rs2: Some(0),
+                rd: trace_row.instruction.rd,
+                imm: None,
+                virtual_sequence_remaining: Some(
+                    Self::SEQUENCE_LENGTH - virtual_sequence.len() - 1,
+                ),
+            },
+            register_state: RegisterState {
+                rs1_val: Some(r),
+                rs2_val: Some(0),
+                rd_post_val: Some(rd_post_val),
+            },
+            memory_state: None,
+            advice_value: None,
+        });
+
+        assert_eq!(virtual_sequence.len(), Self::SEQUENCE_LENGTH);
         virtual_sequence
     }
 }
@@ -186,40 +239,37 @@ mod test {
 
     use super::*;
 
-    #[test]
-    // TODO(moodlezoup): Turn this into a macro, similar to the `jolt_instruction_test` macro
-    fn remu_virtual_sequence_32() {
-        let mut rng = test_rng();
-
-        let r_x = rng.next_u64() % 32;
-        let r_y = rng.next_u64() % 32;
-        let rd = rng.next_u64() % 32;
+    fn remu_result_32(x: u64, y: u64) -> u64 {
+        x.checked_rem(y).unwrap_or(x)
+    }
 
-        let x = rng.next_u32() as u64;
-        let y = if r_x == r_y { x } else { rng.next_u32() as u64 };
-        let quotient = x / y;
-        let result = x - quotient * y;
+    fn test_remu_virtual_sequence_32(r_x: u64, r_y: u64, rd: u64, x: u64, y: u64) {
+        let result = remu_result_32(x, y);
 
         let remu_trace_row = RVTraceRow {
             instruction: ELFInstruction {
-                address: rng.next_u64(),
+                address: 0x80000000,
                 opcode: RV32IM::REMU,
                 rs1: Some(r_x),
                 rs2: Some(r_y),
                 rd: Some(rd),
                 imm: None,
-                virtual_sequence_index: None,
+                virtual_sequence_remaining: None,
             },
             register_state: RegisterState {
                 rs1_val: Some(x),
                 rs2_val: Some(y),
-                rd_post_val: Some(result as u64),
+                rd_post_val: Some(if rd == 0 { 0 } else { result }),
             },
             memory_state: None,
             advice_value: None,
         };
 
-        let virtual_sequence = REMUInstruction::<32>::virtual_sequence(remu_trace_row);
+        let virtual_sequence = REMUInstruction::<32>::virtual_trace(remu_trace_row);
+        assert_eq!(
+            virtual_sequence.len(),
+            REMUInstruction::<32>::SEQUENCE_LENGTH
+        );
         let mut registers = vec![0u64; REGISTER_COUNT as usize];
         registers[r_x as usize] = x;
         registers[r_y as usize] = y;
@@ -235,31 +285,79 @@ mod test {
             let lookup = RV32I::try_from(&row).unwrap();
             let output = lookup.lookup_entry();
             if let Some(rd) = row.instruction.rd {
-                registers[rd as usize] = output;
+                // Writes to x0 are discarded
+                if rd != 0 {
+                    registers[rd as usize] = output;
+                }
                 assert_eq!(
                     registers[rd as usize],
                     row.register_state.rd_post_val.unwrap()
                 );
             } else {
-                // Virtual assert instruction
-                assert!(output == 1);
+                assert!(output == 1)
             }
         }
 
         for (index, val) in registers.iter().enumerate() {
-            if index as u64 == r_x {
+            if index as u64 == rd && rd != 0 {
+                // Check that result was written to rd
+                assert_eq!(*val, result);
+            } else if index as u64 == r_x {
                 // Check that r_x hasn't been clobbered
                 assert_eq!(*val, x);
             } else if index as u64 == r_y {
                 // Check that r_y hasn't been clobbered
                 assert_eq!(*val, y);
-            } else if index as u64 == rd {
-                // Check that result was written to rd
-                assert_eq!(*val, result as u64);
             } else if index < 32 {
                 // None of the other "real" registers were touched
                 assert_eq!(*val, 0);
             }
         }
     }
+
+    #[test]
+    // TODO(moodlezoup): Turn this into a macro, similar to the `jolt_instruction_test` macro
+    fn remu_virtual_sequence_32() {
+        let mut rng = test_rng();
+        for _ in 0..256 {
+            let r_x = rng.next_u64() % 32;
+            let r_y = rng.next_u64() % 32;
+            let rd = rng.next_u64() % 32;
+
+            // x0 always reads as zero
+            let x = if r_x == 0 { 0 } else { rng.next_u32() as u64 };
+            let y = if r_y == 0 {
+                0
+            } else if r_y == r_x {
+                x
+            } else {
+                rng.next_u32() as u64
+            };
+            test_remu_virtual_sequence_32(r_x, r_y, rd, x, y);
+        }
+
+        // Edge-cases
+        let u32_max = u32::MAX as u64;
+        let operands = [
+            (7, 2),
+            (2, 7),
+            (1234, 0),
+            (u32_max, 0),
+            (0, 0),
+            (u32_max, 1),
+            (u32_max, u32_max),
+            (u32_max, 1 << 31),
+            (1 << 31, u32_max),
+        ];
+        for (x, y) in operands {
+            test_remu_virtual_sequence_32(1, 2, 3, x, y);
+            // Destination register aliases the source registers
+            test_remu_virtual_sequence_32(1, 2, 1, x, y);
+            test_remu_virtual_sequence_32(1, 2, 2, x, y);
+            // x0 as the destination or a source register
+            test_remu_virtual_sequence_32(1, 2, 0, x, y);
+            test_remu_virtual_sequence_32(0, 2, 3, 0, y);
+            test_remu_virtual_sequence_32(1, 0, 3, x, 0);
+        }
+    }
 }
@@ -2,7 +2,10 @@ use ark_ff::Zero;
 use rand::rngs::StdRng;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, marker::PhantomData};
+use std::{
+    collections::{BTreeMap, HashMap},
+    marker::PhantomData,
+};
 
 use crate::field::JoltField;
 use crate::jolt::instruction::JoltInstructionSet;
@@ -10,14 +13,16 @@ use crate::poly::commitment::commitment_scheme::{BatchType, CommitShape, Commitm
 use crate::poly::eq_poly::EqPolynomial;
 use crate::utils::transcript::{AppendToTranscript, ProofTranscript};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use common::constants::{BYTES_PER_INSTRUCTION, RAM_START_ADDRESS, REGISTER_COUNT};
+use common::constants::{BYTES_PER_INSTRUCTION, REGISTER_COUNT};
 use common::rv_trace::ELFInstruction;
 use common::to_ram_address;
 
 use rayon::prelude::*;
 
 use crate::{
-    lasso::memory_checking::{MemoryCheckingProof, MemoryCheckingProver, MemoryCheckingVerifier},
+    lasso::memory_checking::{
+        MemoryCheckingProof, MemoryCheckingProver, MemoryCheckingVerifier, NoPreprocessing,
+    },
     poly::{
         dense_mlpoly::DensePolynomial,
         identity_poly::IdentityPolynomial,
@@ -50,6 +55,11 @@ pub struct BytecodeRow {
     rs2: u64,
     /// "Immediate" value for this instruction (0 if unused).
     imm: u64,
+    /// If this instruction is part of a "virtual sequence" (see Section 6.2 of the
+    /// Jolt paper), then this contains the number of virtual instructions after this
+    /// one in the sequence. Used to distinguish the instructions in a virtual sequence,
+    /// which all share the same ELF address.
+    virtual_sequence_remaining: Option<usize>,
 }
 
 impl BytecodeRow {
@@ -61,6 +71,7 @@ impl BytecodeRow {
             rs1,
             rs2,
             imm,
+            virtual_sequence_remaining: None,
         }
     }
 
@@ -72,6 +83,7 @@ impl BytecodeRow {
             rs1: 0,
             rs2: 0,
             imm: 0,
+            virtual_sequence_remaining: None,
         }
     }
 
@@ -83,9 +95,32 @@ impl BytecodeRow {
             rs1: rng.next_u64() % REGISTER_COUNT,
             rs2: rng.next_u64() % REGISTER_COUNT,
             imm: rng.next_u64() % (1 << 20), // U-format instructions have 20-bit imm values
+            virtual_sequence_remaining: None,
         }
     }
 
+    /// Fixed-width little-endian encoding of this row, hashed into the program digest.
+    pub fn to_bytes(&self) -> [u8; 56] {
+        // `None` and `Some(n)` are encoded as 0 and n + 1 respectively
+        let virtual_sequence_remaining = self
+            .virtual_sequence_remaining
+            .map_or(0, |remaining| remaining as u64 + 1);
+        let fields = [
+            self.address as u64,
+            self.bitflags,
+            self.rd,
+            self.rs1,
+            self.rs2,
+            self.imm,
+            virtual_sequence_remaining,
+        ];
+        let mut bytes = [0u8; 56];
+        for (chunk, field) in bytes.chunks_exact_mut(8).zip(fields.iter()) {
+            chunk.copy_from_slice(&field.to_le_bytes());
+        }
+        bytes
+    }
+
     /// Packs the instruction's circuit flags and instruction flags into a single u64 bitvector.
     /// The layout is:
     ///     circuit flags || instruction flags
@@ -125,6 +160,7 @@ impl BytecodeRow {
             rs1: instruction.rs1.unwrap_or(0),
             rs2: instruction.rs2.unwrap_or(0),
             imm: instruction.imm.unwrap_or(0) as u64, // imm is always cast to its 32-bit repr, signed or unsigned
+            virtual_sequence_remaining: instruction.virtual_sequence_remaining,
         }
     }
 }
@@ -154,9 +190,12 @@ pub struct BytecodePolynomials<F: JoltField, C: CommitmentScheme<Field = F>> {
     pub(super) t_read: DensePolynomial<F>,
     /// MLE of the final timestamps.
     pub(super) t_final: DensePolynomial<F>,
+    /// MLE of init/final values, copied from the preprocessing. The prover opens these alongside
+    /// `t_final` so that the verifier only needs their commitments (see `JoltVerifierKey`).
+    pub(super) v_init_final: [DensePolynomial<F>; 6],
 }
 
-#[derive(Clone)]
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct BytecodePreprocessing<F: JoltField> {
     /// Size of the (padded) bytecode.
     code_size: usize,
@@ -167,22 +206,32 @@ pub struct BytecodePreprocessing<F: JoltField> {
     /// Maps the memory address of each instruction in the bytecode to its "virtual" address.
     /// See Section 6.1 of the Jolt paper, "Reflecting the program counter". The virtual address
     /// is the one used to keep track of the next (potentially virtual) instruction to execute.
-    virtual_address_map: HashMap<usize, usize>,
+    /// Key: (ELF address, number of remaining instructions in virtual sequence)
+    virtual_address_map: BTreeMap<(usize, usize), usize>,
+    /// Address at which the program's RAM, and hence its code, starts. ELF addresses are
+    /// compressed relative to it.
+    ram_start: u64,
 }
 
 impl<F: JoltField> BytecodePreprocessing<F> {
     #[tracing::instrument(skip_all, name = "BytecodePreprocessing::preprocess")]
-    pub fn preprocess(mut bytecode: Vec<BytecodeRow>) -> Self {
-        let mut virtual_address_map = HashMap::new();
+    pub fn preprocess(mut bytecode: Vec<BytecodeRow>, ram_start: u64) -> Self {
+        let mut virtual_address_map = BTreeMap::new();
         let mut virtual_address = 1; // Account for no-op instruction prepended to bytecode
         for instruction in bytecode.iter_mut() {
-            assert!(instruction.address >= RAM_START_ADDRESS as usize);
+            assert!(instruction.address >= ram_start as usize);
             assert!(instruction.address % BYTES_PER_INSTRUCTION == 0);
             // Compress instruction address for more efficient commitment:
             instruction.address =
-                1 + (instruction.address - RAM_START_ADDRESS as usize) / BYTES_PER_INSTRUCTION;
+                1 + (instruction.address - ram_start as usize) / BYTES_PER_INSTRUCTION;
             assert_eq!(
-                virtual_address_map.insert(instruction.address, virtual_address),
+                virtual_address_map.insert(
+                    (
+                        instruction.address,
+                        instruction.virtual_sequence_remaining.unwrap_or(0)
+                    ),
+                    virtual_address
+                ),
                 None
             );
             virtual_address += 1;
@@ -190,7 +239,7 @@ impl<F: JoltField> BytecodePreprocessing<F> {
 
         // Bytecode: Prepend a single no-op instruction
         bytecode.insert(0, BytecodeRow::no_op(0));
-        assert_eq!(virtual_address_map.insert(0, 0), None);
+        assert_eq!(virtual_address_map.insert((0, 0), 0), None);
 
         // Bytecode: Pad to nearest power of 2
         let code_size = bytecode.len().next_power_of_two();
@@ -225,8 +274,18 @@ impl<F: JoltField> BytecodePreprocessing<F> {
             v_init_final,
             code_size,
             virtual_address_map,
+            ram_start,
         }
     }
+
+    /// Commits to the bytecode's init/final values. These commitments are all the
+    /// verifier needs to know about the bytecode.
+    pub fn commit<C: CommitmentScheme<Field = F>>(
+        &self,
+        generators: &C::Setup,
+    ) -> Vec<C::Commitment> {
+        C::batch_commit_polys(&self.v_init_final, generators, BatchType::Small)
+    }
 }
 
 impl<F: JoltField, C: CommitmentScheme<Field = F>> BytecodePolynomials<F, C> {
@@ -243,17 +302,20 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> BytecodePolynomials<F, C> {
 
         for (step_index, step) in trace.iter_mut().enumerate() {
             if !step.bytecode_row.address.is_zero() {
-                assert!(step.bytecode_row.address >= RAM_START_ADDRESS as usize);
+                let ram_start = preprocessing.ram_start as usize;
+                assert!(step.bytecode_row.address >= ram_start);
                 assert!(step.bytecode_row.address % BYTES_PER_INSTRUCTION == 0);
                 // Compress instruction address for more efficient commitment:
-                step.bytecode_row.address = 1
-                    + (step.bytecode_row.address - RAM_START_ADDRESS as usize)
-                        / BYTES_PER_INSTRUCTION;
+                step.bytecode_row.address =
+                    1 + (step.bytecode_row.address - ram_start) / BYTES_PER_INSTRUCTION;
             }
 
             let virtual_address = preprocessing
                 .virtual_address_map
-                .get(&step.bytecode_row.address)
+                .get(&(
+                    step.bytecode_row.address,
+                    step.bytecode_row.virtual_sequence_remaining.unwrap_or(0),
+                ))
                 .unwrap();
             a_read_write_usize[step_index] = *virtual_address;
             let counter = final_cts[*virtual_address];
@@ -296,6 +358,7 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> BytecodePolynomials<F, C> {
             v_read_write,
             t_read,
             t_final,
+            v_init_final: preprocessing.v_init_final.clone(),
         }
     }
 
@@ -311,16 +374,25 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> BytecodePolynomials<F, C> {
 
     #[tracing::instrument(skip_all, name = "BytecodePolynomials::validate_bytecode")]
     pub fn validate_bytecode(bytecode: &[BytecodeRow], trace: &[BytecodeRow]) {
-        let mut bytecode_map: HashMap<usize, &BytecodeRow> = HashMap::new();
+        let mut bytecode_map: HashMap<(usize, usize), &BytecodeRow> = HashMap::new();
 
         for bytecode_row in bytecode.iter() {
-            bytecode_map.insert(bytecode_row.address, bytecode_row);
+            bytecode_map.insert(
+                (
+                    bytecode_row.address,
+                    bytecode_row.virtual_sequence_remaining.unwrap_or(0),
+                ),
+                bytecode_row,
+            );
         }
 
         for trace_row in trace {
             assert_eq!(
                 **bytecode_map
-                    .get(&trace_row.address)
+                    .get(&(
+                        trace_row.address,
+                        trace_row.virtual_sequence_remaining.unwrap_or(0)
+                    ))
                     .expect("couldn't find in bytecode"),
                 *trace_row
             );
@@ -347,6 +419,9 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> BytecodePolynomials<F, C> {
 pub struct BytecodeCommitment<C: CommitmentScheme> {
     pub trace_commitments: Vec<C::Commitment>,
     pub t_final_commitment: C::Commitment,
+    /// Commitments to the preprocessed bytecode. These depend only on the program, so the
+    /// verifier checks them against its `JoltVerifierKey` rather than trusting the prover.
+    pub v_init_final_commitments: Vec<C::Commitment>,
 }
 
 impl<C: CommitmentScheme> AppendToTranscript for BytecodeCommitment<C> {
@@ -358,6 +433,10 @@ impl<C: CommitmentScheme> AppendToTranscript for BytecodeCommitment<C> {
         }
 
         self.t_final_commitment.append_to_transcript(transcript);
+
+        for commitment in &self.v_init_final_commitments {
+            commitment.append_to_transcript(transcript);
+        }
     }
 }
 
@@ -383,10 +462,13 @@ where
         let trace_commitments = C::batch_commit_polys_ref(&trace_polys, generators, BatchType::Big);
 
         let t_final_commitment = C::commit(&self.t_final, generators);
+        let v_init_final_commitments =
+            C::batch_commit_polys(&self.v_init_final, generators, BatchType::Small);
 
         Self::Commitment {
             trace_commitments,
             t_final_commitment,
+            v_init_final_commitments,
         }
     }
 }
@@ -396,7 +478,6 @@ where
     F: JoltField,
     C: CommitmentScheme<Field = F>,
 {
-    type Preprocessing = BytecodePreprocessing<F>;
     type ReadWriteOpenings = BytecodeReadWriteOpenings<F>;
     type InitFinalOpenings = BytecodeInitFinalOpenings<F>;
 
@@ -415,13 +496,13 @@ where
 
     #[tracing::instrument(skip_all, name = "BytecodePolynomials::compute_leaves")]
     fn compute_leaves(
-        preprocessing: &BytecodePreprocessing<F>,
+        _: &NoPreprocessing,
         polynomials: &BytecodePolynomials<F, C>,
         gamma: &F,
         tau: &F,
     ) -> (Vec<Vec<F>>, Vec<Vec<F>>) {
         let num_ops = polynomials.a_read_write.len();
-        let bytecode_size = preprocessing.v_init_final[0].len();
+        let bytecode_size = polynomials.v_init_final[0].len();
 
         let read_leaves = (0..num_ops)
             .into_par_iter()
@@ -449,12 +530,12 @@ where
                 Self::fingerprint(
                     &[
                         F::from_u64(i as u64).unwrap(),
-                        preprocessing.v_init_final[0][i],
-                        preprocessing.v_init_final[1][i],
-                        preprocessing.v_init_final[2][i],
-                        preprocessing.v_init_final[3][i],
-                        preprocessing.v_init_final[4][i],
-                        preprocessing.v_init_final[5][i],
+                        polynomials.v_init_final[0][i],
+                        polynomials.v_init_final[1][i],
+                        polynomials.v_init_final[2][i],
+                        polynomials.v_init_final[3][i],
+                        polynomials.v_init_final[4][i],
+                        polynomials.v_init_final[5][i],
                         F::zero(),
                     ],
                     gamma,
@@ -491,12 +572,12 @@ where
                 Self::fingerprint(
                     &[
                         F::from_u64(i as u64).unwrap(),
-                        preprocessing.v_init_final[0][i],
-                        preprocessing.v_init_final[1][i],
-                        preprocessing.v_init_final[2][i],
-                        preprocessing.v_init_final[3][i],
-                        preprocessing.v_init_final[4][i],
-                        preprocessing.v_init_final[5][i],
+                        polynomials.v_init_final[0][i],
+                        polynomials.v_init_final[1][i],
+                        polynomials.v_init_final[2][i],
+                        polynomials.v_init_final[3][i],
+                        polynomials.v_init_final[4][i],
+                        polynomials.v_init_final[5][i],
                         polynomials.t_final[i],
                     ],
                     gamma,
@@ -522,7 +603,7 @@ where
     C: CommitmentScheme<Field = F>,
 {
     fn read_tuples(
-        _: &BytecodePreprocessing<F>,
+        _: &NoPreprocessing,
         openings: &Self::ReadWriteOpenings,
     ) -> Vec<Self::MemoryTuple> {
         vec![[
@@ -537,7 +618,7 @@ where
         ]]
     }
     fn write_tuples(
-        _: &BytecodePreprocessing<F>,
+        _: &NoPreprocessing,
         openings: &Self::ReadWriteOpenings,
     ) -> Vec<Self::MemoryTuple> {
         vec![[
@@ -552,10 +633,10 @@ where
         ]]
     }
     fn init_tuples(
-        _: &BytecodePreprocessing<F>,
+        _: &NoPreprocessing,
         openings: &Self::InitFinalOpenings,
     ) -> Vec<Self::MemoryTuple> {
-        let v_init_final = openings.v_init_final.unwrap();
+        let v_init_final = openings.v_init_final;
         vec![[
             openings.a_init_final.unwrap(),
             v_init_final[0], // address
@@ -568,10 +649,10 @@ where
         ]]
     }
     fn final_tuples(
-        _: &BytecodePreprocessing<F>,
+        _: &NoPreprocessing,
         openings: &Self::InitFinalOpenings,
     ) -> Vec<Self::MemoryTuple> {
-        let v_init_final = openings.v_init_final.unwrap();
+        let v_init_final = openings.v_init_final;
         vec![[
             openings.a_init_final.unwrap(),
             v_init_final[0], // address
@@ -654,7 +735,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &BytecodeCommitment<C>,
         opening_point: &[F],
@@ -669,6 +750,7 @@ where
             opening_point,
             &combined_openings,
             &commitment.trace_commitments.iter().collect::<Vec<_>>(),
+            BatchType::Big,
             transcript,
         )
     }
@@ -681,8 +763,8 @@ where
 {
     /// Evaluation of the a_init_final polynomial at the opening point. Computed by the verifier in `compute_verifier_openings`.
     a_init_final: Option<F>,
-    /// Evaluation of the v_init/final polynomials at the opening point. Computed by the verifier in `compute_verifier_openings`.
-    v_init_final: Option<[F; 6]>,
+    /// Evaluation of the v_init/final polynomials at the opening point.
+    v_init_final: [F; 6],
     /// Evaluation of the t_final polynomial at the opening point.
     t_final: F,
 }
@@ -692,15 +774,21 @@ where
     F: JoltField,
     C: CommitmentScheme<Field = F>,
 {
-    type Preprocessing = BytecodePreprocessing<F>;
-    type Proof = C::Proof;
+    type Proof = C::BatchedProof;
 
     #[tracing::instrument(skip_all, name = "BytecodeInitFinalOpenings::open")]
     fn open(polynomials: &BytecodePolynomials<F, C>, opening_point: &[F]) -> Self {
+        let chis = EqPolynomial::evals(opening_point);
         Self {
             a_init_final: None,
-            v_init_final: None,
-            t_final: polynomials.t_final.evaluate(opening_point),
+            v_init_final: polynomials
+                .v_init_final
+                .par_iter()
+                .map(|poly| poly.evaluate_at_chi(&chis))
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+            t_final: polynomials.t_final.evaluate_at_chi(&chis),
         }
     }
 
@@ -709,47 +797,57 @@ where
         generators: &C::Setup,
         polynomials: &BytecodePolynomials<F, C>,
         opening_point: &[F],
-        _openings: &Self,
+        openings: &Self,
         transcript: &mut ProofTranscript,
     ) -> Self::Proof {
-        C::prove(generators, &polynomials.t_final, opening_point, transcript)
+        let mut combined_openings: Vec<F> = vec![openings.t_final];
+        combined_openings.extend(openings.v_init_final.iter());
+
+        C::batch_prove(
+            generators,
+            &[
+                &polynomials.t_final,
+                &polynomials.v_init_final[0],
+                &polynomials.v_init_final[1],
+                &polynomials.v_init_final[2],
+                &polynomials.v_init_final[3],
+                &polynomials.v_init_final[4],
+                &polynomials.v_init_final[5],
+            ],
+            opening_point,
+            &combined_openings,
+            BatchType::Small,
+            transcript,
+        )
     }
 
-    fn compute_verifier_openings(
-        &mut self,
-        preprocessing: &BytecodePreprocessing<F>,
-        opening_point: &[F],
-    ) {
+    fn compute_verifier_openings(&mut self, _: &NoPreprocessing, opening_point: &[F]) {
         self.a_init_final =
             Some(IdentityPolynomial::new(opening_point.len()).evaluate(opening_point));
-
-        let chis = EqPolynomial::evals(opening_point);
-        self.v_init_final = Some(
-            preprocessing
-                .v_init_final
-                .par_iter()
-                .map(|poly| poly.evaluate_at_chi(&chis))
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap(),
-        );
     }
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &BytecodeCommitment<C>,
         opening_point: &[F],
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
-        C::verify(
+        let mut combined_openings: Vec<F> = vec![self.t_final];
+        combined_openings.extend(self.v_init_final.iter());
+
+        C::batch_verify(
             opening_proof,
             generators,
-            transcript,
             opening_point,
-            &self.t_final,
-            &commitment.t_final_commitment,
+            &combined_openings,
+            &[&commitment.t_final_commitment]
+                .into_iter()
+                .chain(commitment.v_init_final_commitments.iter())
+                .collect::<Vec<_>>(),
+            BatchType::Small,
+            transcript,
         )
     }
 }
@@ -760,7 +858,10 @@ mod tests {
 
     use super::*;
     use ark_bn254::{Fr, G1Projective};
-    use common::{constants::MEMORY_OPS_PER_INSTRUCTION, rv_trace::MemoryOp};
+    use common::{
+        constants::{MEMORY_OPS_PER_INSTRUCTION, RAM_START_ADDRESS},
+        rv_trace::MemoryOp,
+    };
     use std::collections::HashSet;
 
     fn get_difference<T: Clone + Eq + std::hash::Hash>(vec1: &[T], vec2: &[T]) -> Vec<T> {
@@ -804,13 +905,13 @@ mod tests {
             )),
         ];
 
-        let preprocessing = BytecodePreprocessing::preprocess(program.clone());
+        let preprocessing = BytecodePreprocessing::preprocess(program.clone(), RAM_START_ADDRESS);
         let polys: BytecodePolynomials<Fr, HyraxScheme<G1Projective>> =
             BytecodePolynomials::new::<RV32I>(&preprocessing, &mut trace);
 
         let (gamma, tau) = (&Fr::from(100), &Fr::from(35));
         let (read_write_leaves, init_final_leaves) =
-            BytecodeProof::compute_leaves(&preprocessing, &polys, gamma, tau);
+            BytecodeProof::compute_leaves(&NoPreprocessing, &polys, gamma, tau);
         let init_leaves = &init_final_leaves[0];
         let read_leaves = &read_write_leaves[0];
         let write_leaves = &read_write_leaves[1];
@@ -853,25 +954,25 @@ mod tests {
             trace.len(),
         );
 
-        let preprocessing = BytecodePreprocessing::preprocess(program.clone());
+        let preprocessing = BytecodePreprocessing::preprocess(program.clone(), RAM_START_ADDRESS);
         let polys: BytecodePolynomials<Fr, HyraxScheme<G1Projective>> =
             BytecodePolynomials::new(&preprocessing, &mut trace);
 
         let mut transcript = ProofTranscript::new(b"test_transcript");
 
-        let generators = HyraxScheme::<G1Projective>::setup(&commitment_shapes);
+        let generators = HyraxScheme::<G1Projective>::setup(&commitment_shapes).unwrap();
         let commitments = polys.commit(&generators);
         let proof = BytecodeProof::prove_memory_checking(
             &generators,
-            &preprocessing,
+            &NoPreprocessing,
             &polys,
             &mut transcript,
         );
 
         let mut transcript = ProofTranscript::new(b"test_transcript");
         BytecodeProof::verify_memory_checking(
-            &preprocessing,
-            &generators,
+            &NoPreprocessing,
+            &HyraxScheme::verifier_setup(&generators),
             proof,
             &commitments,
             &mut transcript,
@@ -920,25 +1021,25 @@ mod tests {
             program.len(),
             trace.len(),
         );
-        let preprocessing = BytecodePreprocessing::preprocess(program.clone());
+        let preprocessing = BytecodePreprocessing::preprocess(program.clone(), RAM_START_ADDRESS);
         let polys: BytecodePolynomials<Fr, HyraxScheme<G1Projective>> =
             BytecodePolynomials::new(&preprocessing, &mut trace);
-        let generators = HyraxScheme::<G1Projective>::setup(&commit_shapes);
+        let generators = HyraxScheme::<G1Projective>::setup(&commit_shapes).unwrap();
         let commitments = polys.commit(&generators);
 
         let mut transcript = ProofTranscript::new(b"test_transcript");
 
         let proof = BytecodeProof::prove_memory_checking(
             &generators,
-            &preprocessing,
+            &NoPreprocessing,
             &polys,
             &mut transcript,
         );
 
         let mut transcript = ProofTranscript::new(b"test_transcript");
         BytecodeProof::verify_memory_checking(
-            &preprocessing,
-            &generators,
+            &NoPreprocessing,
+            &HyraxScheme::verifier_setup(&generators),
             proof,
             &commitments,
             &mut transcript,
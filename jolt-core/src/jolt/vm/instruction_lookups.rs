@@ -177,7 +177,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &InstructionCommitment<C>,
         opening_point: &[F],
@@ -200,6 +200,7 @@ where
             opening_point,
             &primary_sumcheck_openings,
             &primary_sumcheck_commitments,
+            BatchType::Big,
             transcript,
         )
     }
@@ -298,7 +299,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &InstructionCommitment<C>,
         opening_point: &[F],
@@ -319,6 +320,7 @@ where
             &commitment.trace_commitment[..read_write_openings.len()]
                 .iter()
                 .collect::<Vec<_>>(),
+            BatchType::Big,
             transcript,
         )
     }
@@ -400,7 +402,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &InstructionCommitment<C>,
         opening_point: &[F],
@@ -412,6 +414,7 @@ where
             opening_point,
             &self.final_openings,
             &commitment.final_commitment.iter().collect::<Vec<_>>(),
+            BatchType::Big,
             transcript,
         )
     }
@@ -715,7 +718,7 @@ pub struct PrimarySumcheck<F: JoltField, CS: CommitmentScheme<Field = F>> {
     opening_proof: CS::BatchedProof,
 }
 
-#[derive(Clone)]
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct InstructionLookupsPreprocessing<F: JoltField> {
     subtable_to_memory_indices: Vec<Vec<usize>>, // Vec<Range<usize>>?
     instruction_to_memory_indices: Vec<Vec<usize>>,
@@ -794,6 +797,19 @@ impl<F: JoltField> InstructionLookupsPreprocessing<F> {
         }
         subtables
     }
+
+    /// Returns a copy of this preprocessing without the materialized subtables, which are
+    /// only used by the prover. The verifier evaluates subtable MLEs directly.
+    pub fn verifier_preprocessing(&self) -> Self {
+        Self {
+            subtable_to_memory_indices: self.subtable_to_memory_indices.clone(),
+            instruction_to_memory_indices: self.instruction_to_memory_indices.clone(),
+            memory_to_subtable_index: self.memory_to_subtable_index.clone(),
+            memory_to_dimension_index: self.memory_to_dimension_index.clone(),
+            materialized_subtables: vec![],
+            num_memories: self.num_memories,
+        }
+    }
 }
 
 impl<F, CS, InstructionSet, Subtables, const C: usize, const M: usize>
@@ -869,7 +885,7 @@ where
 
     pub fn verify(
         preprocessing: &InstructionLookupsPreprocessing<F>,
-        generators: &CS::Setup,
+        generators: &CS::VerifierSetup,
         proof: InstructionLookupsProof<C, M, F, CS, InstructionSet, Subtables>,
         commitment: &InstructionCommitment<CS>,
         transcript: &mut ProofTranscript,
@@ -2,13 +2,17 @@
 
 use crate::field::JoltField;
 use crate::r1cs::builder::CombinedUniformBuilder;
-use crate::r1cs::jolt_constraints::{construct_jolt_constraints, JoltIn};
+use crate::r1cs::jolt_constraints::{construct_jolt_constraints, JoltIn, PC_BRANCH_AUX_INDEX};
 use crate::r1cs::spartan::{self, UniformSpartanProof};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::log2;
-use common::constants::RAM_START_ADDRESS;
+use common::constants::IO_DIGEST_SIZE;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
 use strum::EnumCount;
 
 use crate::jolt::vm::timestamp_range_check::RangeCheckPolynomials;
@@ -16,17 +20,22 @@ use crate::jolt::{
     instruction::JoltInstruction, subtable::JoltSubtableSet,
     vm::timestamp_range_check::TimestampValidityProof,
 };
-use crate::lasso::memory_checking::{MemoryCheckingProver, MemoryCheckingVerifier};
-use crate::poly::commitment::commitment_scheme::{BatchType, CommitmentScheme};
+use crate::lasso::memory_checking::{
+    MemoryCheckingProver, MemoryCheckingVerifier, NoPreprocessing,
+};
+use crate::poly::commitment::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
+use crate::poly::commitment::kzg::SRSError;
+use crate::poly::commitment::setup_cache;
 use crate::poly::dense_mlpoly::DensePolynomial;
 use crate::poly::structured_poly::StructuredCommitment;
 use crate::r1cs::inputs::{R1CSCommitment, R1CSInputs, R1CSProof};
 use crate::utils::errors::ProofVerifyError;
+use crate::utils::index_to_field_bitvector;
 use crate::utils::thread::{drop_in_background_thread, unsafe_allocate_zero_vec};
 use crate::utils::transcript::{AppendToTranscript, ProofTranscript};
 use common::{
     constants::MEMORY_OPS_PER_INSTRUCTION,
-    rv_trace::{ELFInstruction, JoltDevice, MemoryOp},
+    rv_trace::{ELFInstruction, JoltDevice, MachineSnapshot, MemoryLayout, MemoryOp},
 };
 
 use self::bytecode::BytecodePreprocessing;
@@ -34,7 +43,8 @@ use self::instruction_lookups::{
     InstructionCommitment, InstructionLookupsPreprocessing, InstructionLookupsProof,
 };
 use self::read_write_memory::{
-    MemoryCommitment, ReadWriteMemory, ReadWriteMemoryPreprocessing, ReadWriteMemoryProof,
+    MemoryCommitment, ProgramMemory, ReadWriteMemory, ReadWriteMemoryPreprocessing,
+    ReadWriteMemoryProof,
 };
 use self::timestamp_range_check::RangeCheckCommitment;
 use self::{
@@ -44,7 +54,7 @@ use self::{
 
 use super::instruction::JoltInstructionSet;
 
-#[derive(Clone)]
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct JoltPreprocessing<F, PCS>
 where
     F: JoltField,
@@ -54,6 +64,141 @@ where
     pub instruction_lookups: InstructionLookupsPreprocessing<F>,
     pub bytecode: BytecodePreprocessing<F>,
     pub read_write_memory: ReadWriteMemoryPreprocessing,
+    pub program_memory: ProgramMemory,
+    pub memory_layout: MemoryLayout,
+    /// Identifies the program being proven; see `program_digest`.
+    pub program_digest: [u8; 32],
+}
+
+impl<F, PCS> JoltPreprocessing<F, PCS>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<Field = F>,
+{
+    /// Saves the preprocessing to a file
+    pub fn save_to_file<P: Into<PathBuf>>(&self, path: P) -> eyre::Result<()> {
+        let file = File::create(path.into())?;
+        self.serialize_compressed(BufWriter::new(file))?;
+        Ok(())
+    }
+
+    /// Reads the preprocessing from a file
+    pub fn from_file<P: Into<PathBuf>>(path: P) -> eyre::Result<Self> {
+        let file = File::open(path.into())?;
+        Ok(Self::deserialize_compressed(BufReader::new(file))?)
+    }
+
+    /// Serializes the preprocessing to a byte vector
+    pub fn serialize_to_bytes(&self) -> eyre::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.serialize_compressed(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Deserializes the preprocessing from a byte vector
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> eyre::Result<Self> {
+        Ok(Self::deserialize_compressed(bytes)?)
+    }
+
+    /// Derives the key used by `Jolt::verify`, which omits everything only the prover needs
+    /// (the full commitment scheme setup, materialized subtables, and the bytecode and
+    /// program memory, of which it only holds commitments).
+    #[tracing::instrument(skip_all, name = "JoltPreprocessing::verifier_key")]
+    pub fn verifier_key(&self) -> JoltVerifierKey<F, PCS> {
+        JoltVerifierKey {
+            generators: PCS::verifier_setup(&self.generators),
+            instruction_lookups: self.instruction_lookups.verifier_preprocessing(),
+            bytecode_commitments: self.bytecode.commit::<PCS>(&self.generators),
+            read_write_memory: self.read_write_memory.clone(),
+            program_memory_commitment: PCS::commit(
+                &self.program_memory.polynomial(),
+                &self.generators,
+            ),
+            memory_layout: self.memory_layout.clone(),
+            program_digest: self.program_digest,
+        }
+    }
+}
+
+/// Everything `Jolt::verify` needs to know about a program, derived from its
+/// `JoltPreprocessing` via `JoltPreprocessing::verifier_key`. This is much smaller than
+/// the preprocessing itself, so it is what light clients and the WASM verifier should hold.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct JoltVerifierKey<F, PCS>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<Field = F>,
+{
+    pub generators: PCS::VerifierSetup,
+    pub instruction_lookups: InstructionLookupsPreprocessing<F>,
+    pub bytecode_commitments: Vec<PCS::Commitment>,
+    pub read_write_memory: ReadWriteMemoryPreprocessing,
+    /// Commitment to the program's initial memory; see `ProgramMemory`.
+    pub program_memory_commitment: PCS::Commitment,
+    pub memory_layout: MemoryLayout,
+    /// Identifies the program being verified; see `program_digest`.
+    pub program_digest: [u8; 32],
+}
+
+impl<F, PCS> JoltVerifierKey<F, PCS>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<Field = F>,
+{
+    /// Saves the verifier key to a file
+    pub fn save_to_file<P: Into<PathBuf>>(&self, path: P) -> eyre::Result<()> {
+        let file = File::create(path.into())?;
+        self.serialize_compressed(BufWriter::new(file))?;
+        Ok(())
+    }
+
+    /// Reads the verifier key from a file
+    pub fn from_file<P: Into<PathBuf>>(path: P) -> eyre::Result<Self> {
+        let file = File::open(path.into())?;
+        Ok(Self::deserialize_compressed(BufReader::new(file))?)
+    }
+
+    /// Serializes the verifier key to a byte vector
+    pub fn serialize_to_bytes(&self) -> eyre::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.serialize_compressed(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Deserializes the verifier key from a byte vector
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> eyre::Result<Self> {
+        Ok(Self::deserialize_compressed(bytes)?)
+    }
+}
+
+/// Computes a 32-byte identifier for a guest program: the Keccak-256 hash of its bytecode
+/// rows, initial memory and memory layout. It only depends on the program (not on the
+/// commitment scheme or preprocessing sizes), so it can be published ahead of time and a
+/// proof checked against it via `JoltVerifierKey::program_digest`.
+pub fn program_digest(
+    bytecode: &[BytecodeRow],
+    memory_init: &[(u64, u8)],
+    memory_layout: &MemoryLayout,
+) -> [u8; 32] {
+    let mut memory_init = memory_init.to_vec();
+    memory_init.sort_unstable();
+
+    let mut hasher = Keccak256::new();
+    hasher.update(b"Jolt program digest");
+    hasher.update(memory_layout.ram_start.to_le_bytes());
+    hasher.update(memory_layout.max_input_size.to_le_bytes());
+    hasher.update(memory_layout.max_output_size.to_le_bytes());
+    hasher.update([memory_layout.hash_io as u8]);
+    hasher.update((bytecode.len() as u64).to_le_bytes());
+    for row in bytecode {
+        hasher.update(row.to_bytes());
+    }
+    hasher.update((memory_init.len() as u64).to_le_bytes());
+    for (address, byte) in memory_init {
+        hasher.update(address.to_le_bytes());
+        hasher.update([byte]);
+    }
+    hasher.finalize().into()
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -87,6 +232,31 @@ impl<InstructionSet: JoltInstructionSet> JoltTraceStep<InstructionSet> {
     }
 }
 
+/// One segment of a long-running program's execution, as produced by
+/// `Program::trace_segments`. Segments are proven individually with `Jolt::prove_segment`
+/// and checked together with `Jolt::verify_segments`.
+pub struct JoltSegment<InstructionSet: JoltInstructionSet, F: JoltField> {
+    /// Position of this segment in the execution, starting at 0.
+    pub index: usize,
+    pub trace: Vec<JoltTraceStep<InstructionSet>>,
+    pub circuit_flags: Vec<F>,
+    /// The machine state this segment starts from. Only used for segments after the
+    /// first, whose initial memory can't be derived from the program itself.
+    pub initial_state: MachineSnapshot,
+    /// Program I/O at the end of this segment.
+    pub program_io: JoltDevice,
+    /// Size of the memory witness, which must be the same for all segments.
+    pub memory_size: usize,
+}
+
+impl<InstructionSet: JoltInstructionSet, F: JoltField> JoltSegment<InstructionSet, F> {
+    /// Segment traces are padded to a power of two with at least one no-op step, which lets
+    /// the verifier check that a segment doesn't end in the middle of a virtual sequence.
+    pub fn padded_trace_length(trace_length: usize) -> usize {
+        (trace_length + 1).next_power_of_two()
+    }
+}
+
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct JoltProof<const C: usize, const M: usize, F, PCS, InstructionSet, Subtables>
 where
@@ -103,6 +273,203 @@ where
     pub r1cs: R1CSProof<F, PCS>,
 }
 
+/// A proof of a program's execution on the Jolt VM `V`, along with the commitments it was
+/// produced against.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct JoltVMProof<V, PCS, const C: usize, const M: usize>
+where
+    V: Jolt<PCS::Field, PCS, C, M>,
+    PCS: CommitmentScheme,
+{
+    pub proof: JoltProof<C, M, PCS::Field, PCS, V::InstructionSet, V::Subtables>,
+    pub commitments: JoltCommitments<PCS>,
+}
+
+impl<V, PCS, const C: usize, const M: usize> JoltVMProof<V, PCS, C, M>
+where
+    V: Jolt<PCS::Field, PCS, C, M>,
+    PCS: CommitmentScheme,
+{
+    /// Gets the byte size of the full proof
+    pub fn size(&self) -> eyre::Result<usize> {
+        let mut buffer = Vec::new();
+        self.serialize_compressed(&mut buffer)?;
+        Ok(buffer.len())
+    }
+
+    /// Saves the proof to a file
+    pub fn save_to_file<P: Into<PathBuf>>(&self, path: P) -> eyre::Result<()> {
+        let file = File::create(path.into())?;
+        self.serialize_compressed(file)?;
+        Ok(())
+    }
+
+    /// Reads a proof from a file
+    pub fn from_file<P: Into<PathBuf>>(path: P) -> eyre::Result<Self> {
+        let file = File::open(path.into())?;
+        Ok(Self::deserialize_compressed(file)?)
+    }
+
+    /// Serializes the proof to a byte vector
+    pub fn serialize_to_bytes(&self) -> eyre::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.serialize_compressed(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Deserializes a proof from a byte vector
+    pub fn deserialize_from_bytes(bytes: &[u8]) -> eyre::Result<Self> {
+        Ok(Self::deserialize_compressed(bytes)?)
+    }
+}
+
+/// Openings that connect a continuation segment to its neighbours: the ELF address of its
+/// first instruction, and the PC its last instruction hands over to the next segment.
+///
+/// Segments must start and end outside of virtual sequences, whose virtual registers are not
+/// carried over from one segment to the next. The end of a segment is checked by opening the
+/// step after its last instruction, which must be padding: the R1CS constraints require the
+/// step after a virtual instruction to be at the next virtual address, which padding never is.
+/// The start is checked by opening the first step's `IsVirtual` flag, which must be zero.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct SegmentBoundaryProof<F, PCS>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<Field = F>,
+{
+    /// Compressed ELF address of the first instruction (see `BytecodePolynomials::new`).
+    pub first_elf_address: F,
+    /// Value of the R1CS next-PC auxiliary variable for the last instruction.
+    pub next_pc: F,
+    first_elf_address_proof: PCS::BatchedProof,
+    first_is_virtual_proof: PCS::BatchedProof,
+    padding_elf_address_proof: PCS::BatchedProof,
+    next_pc_proof: PCS::BatchedProof,
+}
+
+impl<F, PCS> SegmentBoundaryProof<F, PCS>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<Field = F>,
+{
+    #[tracing::instrument(skip_all, name = "SegmentBoundaryProof::prove")]
+    fn prove(
+        generators: &PCS::Setup,
+        elf_address: &DensePolynomial<F>,
+        is_virtual: &DensePolynomial<F>,
+        next_pc: &DensePolynomial<F>,
+        trace_length: usize,
+        transcript: &mut ProofTranscript,
+    ) -> Self {
+        let num_vars = elf_address.get_num_vars();
+        let mut open = |poly: &DensePolynomial<F>, index: usize| {
+            let eval = poly[index];
+            transcript.append_scalar(&eval);
+            let proof = PCS::batch_prove(
+                generators,
+                &[poly],
+                &index_to_field_bitvector(index, num_vars),
+                &[eval],
+                BatchType::Big,
+                transcript,
+            );
+            (eval, proof)
+        };
+
+        let (first_elf_address, first_elf_address_proof) = open(elf_address, 0);
+        let (_, first_is_virtual_proof) = open(is_virtual, 0);
+        // The step after the last instruction is padding, so its ELF address is 0
+        let (_, padding_elf_address_proof) = open(elf_address, trace_length);
+        let (next_pc, next_pc_proof) = open(next_pc, trace_length - 1);
+
+        Self {
+            first_elf_address,
+            next_pc,
+            first_elf_address_proof,
+            first_is_virtual_proof,
+            padding_elf_address_proof,
+            next_pc_proof,
+        }
+    }
+
+    fn verify(
+        &self,
+        generators: &PCS::VerifierSetup,
+        commitments: &JoltCommitments<PCS>,
+        trace_length: usize,
+        padded_trace_length: usize,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        if trace_length == 0 {
+            return Err(ProofVerifyError::InternalError);
+        }
+        let num_vars = log2(padded_trace_length) as usize;
+        let mut verify =
+            |proof: &PCS::BatchedProof, commitment: &PCS::Commitment, index: usize, eval: F| {
+                transcript.append_scalar(&eval);
+                PCS::batch_verify(
+                    proof,
+                    generators,
+                    &index_to_field_bitvector(index, num_vars),
+                    &[eval],
+                    &[commitment],
+                    BatchType::Big,
+                    transcript,
+                )
+            };
+
+        // Bytecode trace commitments are ordered [a_read_write, t_read, v_read_write...],
+        // and the first v_read_write polynomial holds the ELF address.
+        let elf_address_commitment = &commitments.bytecode.trace_commitments[2];
+        let r1cs_commitments = commitments
+            .r1cs
+            .as_ref()
+            .ok_or(ProofVerifyError::InternalError)?;
+        verify(
+            &self.first_elf_address_proof,
+            elf_address_commitment,
+            0,
+            self.first_elf_address,
+        )?;
+        verify(
+            &self.first_is_virtual_proof,
+            &r1cs_commitments.circuit_flags[IS_VIRTUAL_FLAG_INDEX],
+            0,
+            F::zero(),
+        )?;
+        verify(
+            &self.padding_elf_address_proof,
+            elf_address_commitment,
+            trace_length,
+            F::zero(),
+        )?;
+        verify(
+            &self.next_pc_proof,
+            &r1cs_commitments.aux[PC_BRANCH_AUX_INDEX],
+            trace_length - 1,
+            self.next_pc,
+        )
+    }
+}
+
+/// Index of the `IsVirtual` flag among the circuit flags committed in `R1CSCommitment`.
+const IS_VIRTUAL_FLAG_INDEX: usize =
+    JoltIn::OpFlags_IsVirtual as usize - JoltIn::OpFlags_IsRs1Rs2 as usize;
+
+/// Proof of one segment of a continuation, produced by `Jolt::prove_segment`.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct SegmentProof<const C: usize, const M: usize, F, PCS, InstructionSet, Subtables>
+where
+    F: JoltField,
+    PCS: CommitmentScheme<Field = F>,
+    InstructionSet: JoltInstructionSet,
+    Subtables: JoltSubtableSet<F>,
+{
+    pub proof: JoltProof<C, M, F, PCS, InstructionSet, Subtables>,
+    pub commitments: JoltCommitments<PCS>,
+    pub boundary: SegmentBoundaryProof<F, PCS>,
+}
+
 pub struct JoltPolynomials<F, PCS>
 where
     F: JoltField,
@@ -209,10 +576,28 @@ where
         let instruction_trace_commitment = trace_comitments;
 
         let bytecode_t_final_commitment = PCS::commit(&self.bytecode.t_final, generators);
+        let bytecode_v_init_final_commitments =
+            PCS::batch_commit_polys(&self.bytecode.v_init_final, generators, BatchType::Small);
         let (memory_v_final_commitment, memory_t_final_commitment) = rayon::join(
             || PCS::commit(&self.read_write_memory.v_final, generators),
             || PCS::commit(&self.read_write_memory.t_final, generators),
         );
+        // Committed the same way as v_final, so that a segment's initial memory commitment
+        // can be compared against the final memory commitment of the segment before it.
+        let memory_v_init_commitment = self
+            .read_write_memory
+            .v_init_committed
+            .then(|| PCS::commit(&self.read_write_memory.v_init, generators));
+        let memory_advice_commitment = self
+            .read_write_memory
+            .advice
+            .as_ref()
+            .map(|advice| PCS::commit(advice, generators));
+        let memory_program_commitment = self
+            .read_write_memory
+            .program
+            .as_ref()
+            .map(|program| PCS::commit(program, generators));
         let instruction_final_commitment = PCS::batch_commit_polys(
             &self.instruction_lookups.final_cts,
             generators,
@@ -223,9 +608,13 @@ where
             bytecode: BytecodeCommitment {
                 trace_commitments: bytecode_trace_commitment,
                 t_final_commitment: bytecode_t_final_commitment,
+                v_init_final_commitments: bytecode_v_init_final_commitments,
             },
             read_write_memory: MemoryCommitment {
                 trace_commitments: memory_trace_commitment,
+                v_init_commitment: memory_v_init_commitment,
+                advice_commitment: memory_advice_commitment,
+                program_commitment: memory_program_commitment,
                 v_final_commitment: memory_v_final_commitment,
                 t_final_commitment: memory_t_final_commitment,
             },
@@ -245,14 +634,40 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
     type InstructionSet: JoltInstructionSet;
     type Subtables: JoltSubtableSet<F>;
 
+    /// Preprocesses the program and sets up the commitment scheme. Fails if the setup can't be
+    /// loaded, e.g. because no powers of tau file is given for a KZG-based scheme (see
+    /// `kzg::PTAU_PATH_ENV`).
     #[tracing::instrument(skip_all, name = "Jolt::preprocess")]
     fn preprocess(
         bytecode: Vec<ELFInstruction>,
         memory_init: Vec<(u64, u8)>,
+        memory_layout: MemoryLayout,
         max_bytecode_size: usize,
         max_memory_address: usize,
         max_trace_length: usize,
-    ) -> JoltPreprocessing<F, PCS> {
+    ) -> Result<JoltPreprocessing<F, PCS>, SRSError> {
+        Self::preprocess_with_setup(
+            bytecode,
+            memory_init,
+            memory_layout,
+            max_bytecode_size,
+            max_memory_address,
+            max_trace_length,
+            setup_cache::setup::<PCS>,
+        )
+    }
+
+    /// `preprocess`, with the commitment scheme set up for the required shapes by `setup`
+    /// rather than from the setup cache, e.g. with `CommitmentScheme::seeded_setup` in tests.
+    fn preprocess_with_setup(
+        bytecode: Vec<ELFInstruction>,
+        memory_init: Vec<(u64, u8)>,
+        memory_layout: MemoryLayout,
+        max_bytecode_size: usize,
+        max_memory_address: usize,
+        max_trace_length: usize,
+        setup: impl FnOnce(&[CommitShape]) -> Result<PCS::Setup, SRSError>,
+    ) -> Result<JoltPreprocessing<F, PCS>, SRSError> {
         let bytecode_commitment_shapes =
             BytecodePolynomials::<F, PCS>::commit_shapes(max_bytecode_size, max_trace_length);
         let ram_commitment_shapes =
@@ -278,13 +693,16 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
             max_trace_length,
         );
 
-        let read_write_memory_preprocessing = ReadWriteMemoryPreprocessing::preprocess(memory_init);
-
         let bytecode_rows: Vec<BytecodeRow> = bytecode
             .iter()
             .map(BytecodeRow::from_instruction::<Self::InstructionSet>)
             .collect();
-        let bytecode_preprocessing = BytecodePreprocessing::<F>::preprocess(bytecode_rows);
+        let program_digest = program_digest(&bytecode_rows, &memory_init, &memory_layout);
+
+        let (read_write_memory_preprocessing, program_memory) =
+            ReadWriteMemoryPreprocessing::preprocess(memory_init, &memory_layout);
+        let bytecode_preprocessing =
+            BytecodePreprocessing::<F>::preprocess(bytecode_rows, memory_layout.ram_start);
 
         let commitment_shapes = [
             bytecode_commitment_shapes,
@@ -293,34 +711,95 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
             instruction_lookups_commitment_shapes,
         ]
         .concat();
-        let generators = PCS::setup(&commitment_shapes);
+        let generators = setup(&commitment_shapes)?;
 
-        JoltPreprocessing {
+        Ok(JoltPreprocessing {
             generators,
             instruction_lookups: instruction_lookups_preprocessing,
             bytecode: bytecode_preprocessing,
             read_write_memory: read_write_memory_preprocessing,
-        }
+            program_memory,
+            memory_layout,
+            program_digest,
+        })
     }
 
     #[tracing::instrument(skip_all, name = "Jolt::prove")]
     fn prove(
         program_io: JoltDevice,
+        trace: Vec<JoltTraceStep<Self::InstructionSet>>,
+        circuit_flags: Vec<F>,
+        preprocessing: JoltPreprocessing<F, PCS>,
+    ) -> (
+        JoltProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
+        JoltCommitments<PCS>,
+    ) {
+        let (jolt_proof, jolt_commitments, _) =
+            Self::prove_trace(program_io, trace, circuit_flags, preprocessing, None);
+        (jolt_proof, jolt_commitments)
+    }
+
+    /// Proves one segment of a continuation (see `Program::trace_segments`). Unlike `prove`,
+    /// the segment's memory may start from where the previous segment left off, and the
+    /// proof includes openings that let `verify_segments` check that segments chain together.
+    #[tracing::instrument(skip_all, name = "Jolt::prove_segment")]
+    fn prove_segment(
+        mut segment: JoltSegment<Self::InstructionSet, F>,
+        preprocessing: JoltPreprocessing<F, PCS>,
+    ) -> SegmentProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables> {
+        let trace = std::mem::take(&mut segment.trace);
+        let circuit_flags = std::mem::take(&mut segment.circuit_flags);
+        let (proof, commitments, boundary) = Self::prove_trace(
+            segment.program_io.clone(),
+            trace,
+            circuit_flags,
+            preprocessing,
+            Some(&segment),
+        );
+        SegmentProof {
+            proof,
+            commitments,
+            boundary: boundary.unwrap(),
+        }
+    }
+
+    fn prove_trace(
+        mut program_io: JoltDevice,
         mut trace: Vec<JoltTraceStep<Self::InstructionSet>>,
         circuit_flags: Vec<F>,
         preprocessing: JoltPreprocessing<F, PCS>,
+        segment: Option<&JoltSegment<Self::InstructionSet, F>>,
     ) -> (
         JoltProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
         JoltCommitments<PCS>,
+        Option<SegmentBoundaryProof<F, PCS>>,
     ) {
         let trace_length = trace.len();
-        let padded_trace_length = trace_length.next_power_of_two();
         println!("Trace length: {}", trace_length);
 
-        JoltTraceStep::pad(&mut trace);
+        assert_eq!(
+            program_io.memory_layout, preprocessing.memory_layout,
+            "program was traced with a different memory layout than it was preprocessed with"
+        );
+        // With hashed I/O, the guest's return value is not part of the statement being proven
+        program_io.truncate_hashed_outputs();
+
+        match segment {
+            Some(_) => trace.resize(
+                JoltSegment::<Self::InstructionSet, F>::padded_trace_length(trace_length),
+                JoltTraceStep::no_op(),
+            ),
+            None => JoltTraceStep::pad(&mut trace),
+        }
+        let padded_trace_length = trace.len();
 
         let mut transcript = ProofTranscript::new(b"Jolt transcript");
-        Self::fiat_shamir_preamble(&mut transcript, &program_io, trace_length);
+        Self::fiat_shamir_preamble(
+            &mut transcript,
+            &preprocessing.program_digest,
+            &program_io,
+            trace_length,
+        );
 
         let instruction_polynomials = InstructionLookupsProof::<
             C,
@@ -334,11 +813,18 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
         );
 
         let load_store_flags = &instruction_polynomials.instruction_flag_polys[5..10];
+        // The first segment starts from the program's initial memory, like a full execution.
+        let initial_state = segment
+            .filter(|segment| segment.index > 0)
+            .map(|segment| &segment.initial_state);
         let (memory_polynomials, read_timestamps) = ReadWriteMemory::new(
             &program_io,
             load_store_flags,
             &preprocessing.read_write_memory,
+            &preprocessing.program_memory,
             &trace,
+            segment.map(|segment| segment.memory_size),
+            initial_state,
         );
 
         let (bytecode_polynomials, range_check_polys) = rayon::join(
@@ -355,7 +841,7 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
 
         let (witness_segments, r1cs_commitments, r1cs_builder) = Self::r1cs_setup(
             padded_trace_length,
-            RAM_START_ADDRESS - program_io.memory_layout.ram_witness_offset,
+            &program_io.memory_layout,
             &trace,
             &jolt_polynomials,
             circuit_flags,
@@ -371,12 +857,29 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
 
         transcript.append_scalar(&spartan_key.vk_digest);
 
+        let num_aux = r1cs_commitments.aux.len();
         jolt_commitments.r1cs = Some(r1cs_commitments);
         jolt_commitments.append_to_transcript(&mut transcript);
 
+        let boundary_proof = segment.map(|_| {
+            let next_pc = DensePolynomial::new(
+                witness_segments[witness_segments.len() - num_aux + PC_BRANCH_AUX_INDEX].clone(),
+            );
+            let is_virtual =
+                DensePolynomial::new(witness_segments[JoltIn::OpFlags_IsVirtual as usize].clone());
+            SegmentBoundaryProof::prove(
+                &preprocessing.generators,
+                &jolt_polynomials.bytecode.v_read_write[0],
+                &is_virtual,
+                &next_pc,
+                trace_length,
+                &mut transcript,
+            )
+        });
+
         let bytecode_proof = BytecodeProof::prove_memory_checking(
             &preprocessing.generators,
-            &preprocessing.bytecode,
+            &NoPreprocessing,
             &jolt_polynomials.bytecode,
             &mut transcript,
         );
@@ -420,58 +923,191 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
             r1cs: r1cs_proof,
         };
 
-        (jolt_proof, jolt_commitments)
+        (jolt_proof, jolt_commitments, boundary_proof)
     }
 
     #[tracing::instrument(skip_all)]
     fn verify(
-        mut preprocessing: JoltPreprocessing<F, PCS>,
+        mut verifier_key: JoltVerifierKey<F, PCS>,
         proof: JoltProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
         commitments: JoltCommitments<PCS>,
     ) -> Result<(), ProofVerifyError> {
+        Self::check_complete_execution(&proof, &commitments)?;
+        Self::verify_proof(&mut verifier_key, proof, commitments, None)
+    }
+
+    /// Checks that `proof` covers an execution from start to finish, as opposed to a segment
+    /// of one.
+    fn check_complete_execution(
+        proof: &JoltProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
+        commitments: &JoltCommitments<PCS>,
+    ) -> Result<(), ProofVerifyError> {
+        // The termination bit is bound to the final memory state by the output check
+        // in `verify_memory`, so a valid proof attests that the guest halted.
+        if !proof.program_io.termination {
+            return Err(ProofVerifyError::ProgramNotTerminated);
+        }
+
+        // Memory must start out containing the program and its inputs, which the verifier
+        // computes itself rather than trusting a commitment from the prover.
+        if commitments.read_write_memory.v_init_commitment.is_some() {
+            return Err(ProofVerifyError::InitialMemoryMismatch);
+        }
+        Ok(())
+    }
+
+    /// Verifies the segment proofs of a continuation, in execution order. Each segment must
+    /// start from the memory state and PC that the previous one ended with, and only the
+    /// last segment may (and must) terminate. No segment may start or end partway through a
+    /// virtual sequence (see `SegmentBoundaryProof`).
+    #[tracing::instrument(skip_all)]
+    fn verify_segments(
+        mut verifier_key: JoltVerifierKey<F, PCS>,
+        segments: Vec<SegmentProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>>,
+    ) -> Result<(), ProofVerifyError> {
+        let last = segments
+            .len()
+            .checked_sub(1)
+            .ok_or(ProofVerifyError::EmptyContinuation)?;
+        if !segments[last].proof.program_io.termination {
+            return Err(ProofVerifyError::ProgramNotTerminated);
+        }
+
+        let pc_start_address = F::from_u64(verifier_key.memory_layout.ram_start).unwrap();
+        let four = F::from_u64(4).unwrap();
+        for (index, segment) in segments.iter().enumerate() {
+            let v_init_commitment = segment
+                .commitments
+                .read_write_memory
+                .v_init_commitment
+                .as_ref();
+            if index == 0 {
+                if v_init_commitment.is_some() {
+                    return Err(ProofVerifyError::InitialMemoryMismatch);
+                }
+                continue;
+            }
+
+            let previous = &segments[index - 1];
+            if v_init_commitment != Some(&previous.commitments.read_write_memory.v_final_commitment)
+            {
+                return Err(ProofVerifyError::InitialMemoryMismatch);
+            }
+            if previous.proof.program_io.termination
+                || previous.proof.program_io.inputs != segment.proof.program_io.inputs
+                || previous.boundary.next_pc
+                    != four * segment.boundary.first_elf_address + pc_start_address
+            {
+                return Err(ProofVerifyError::SegmentChainMismatch(index));
+            }
+        }
+
+        for segment in segments {
+            Self::verify_proof(
+                &mut verifier_key,
+                segment.proof,
+                segment.commitments,
+                Some(&segment.boundary),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn verify_proof(
+        verifier_key: &mut JoltVerifierKey<F, PCS>,
+        proof: JoltProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
+        commitments: JoltCommitments<PCS>,
+        boundary: Option<&SegmentBoundaryProof<F, PCS>>,
+    ) -> Result<(), ProofVerifyError> {
+        let (r1cs_proof, commitments, mut transcript) =
+            Self::verify_until_r1cs(verifier_key, proof, commitments, boundary)?;
+        Self::verify_r1cs(
+            &verifier_key.generators,
+            r1cs_proof,
+            commitments,
+            &mut transcript,
+        )
+    }
+
+    /// Verifies everything in `proof` except its R1CS proof, which is returned along with
+    /// the transcript it must be verified against. This lets the R1CS proof be checked
+    /// elsewhere, e.g. by the EVM verifier in `crate::solidity`.
+    #[allow(clippy::type_complexity)]
+    fn verify_until_r1cs(
+        verifier_key: &mut JoltVerifierKey<F, PCS>,
+        proof: JoltProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
+        commitments: JoltCommitments<PCS>,
+        boundary: Option<&SegmentBoundaryProof<F, PCS>>,
+    ) -> Result<(R1CSProof<F, PCS>, JoltCommitments<PCS>, ProofTranscript), ProofVerifyError> {
+        // The prover's bytecode commitments must be those of the program being verified.
+        if commitments.bytecode.v_init_final_commitments != verifier_key.bytecode_commitments {
+            return Err(ProofVerifyError::BytecodeCommitmentMismatch);
+        }
+        // Likewise for the program's initial memory, if the proof starts from it
+        if commitments
+            .read_write_memory
+            .program_commitment
+            .as_ref()
+            .is_some_and(|commitment| *commitment != verifier_key.program_memory_commitment)
+        {
+            return Err(ProofVerifyError::ProgramMemoryCommitmentMismatch);
+        }
+
+        if proof.program_io.memory_layout != verifier_key.memory_layout {
+            return Err(ProofVerifyError::MemoryLayoutMismatch);
+        }
+
         let mut transcript = ProofTranscript::new(b"Jolt transcript");
-        Self::fiat_shamir_preamble(&mut transcript, &proof.program_io, proof.trace_length);
+        Self::fiat_shamir_preamble(
+            &mut transcript,
+            &verifier_key.program_digest,
+            &proof.program_io,
+            proof.trace_length,
+        );
 
         // append the digest of vk (which includes R1CS matrices) and the RelaxedR1CSInstance to the transcript
         transcript.append_scalar(&proof.r1cs.key.vk_digest);
 
         commitments.append_to_transcript(&mut transcript);
 
+        if let Some(boundary) = boundary {
+            boundary.verify(
+                &verifier_key.generators,
+                &commitments,
+                proof.trace_length,
+                JoltSegment::<Self::InstructionSet, F>::padded_trace_length(proof.trace_length),
+                &mut transcript,
+            )?;
+        }
+
         Self::verify_bytecode(
-            &preprocessing.bytecode,
-            &preprocessing.generators,
+            &verifier_key.generators,
             proof.bytecode,
             &commitments.bytecode,
             &mut transcript,
         )?;
         Self::verify_instruction_lookups(
-            &preprocessing.instruction_lookups,
-            &preprocessing.generators,
+            &verifier_key.instruction_lookups,
+            &verifier_key.generators,
             proof.instruction_lookups,
             &commitments.instruction_lookups,
             &mut transcript,
         )?;
         Self::verify_memory(
-            &mut preprocessing.read_write_memory,
-            &preprocessing.generators,
+            &mut verifier_key.read_write_memory,
+            &verifier_key.generators,
             proof.read_write_memory,
             &commitments,
             proof.program_io,
             &mut transcript,
         )?;
-        Self::verify_r1cs(
-            &preprocessing.generators,
-            proof.r1cs,
-            commitments,
-            &mut transcript,
-        )?;
-        Ok(())
+        Ok((proof.r1cs, commitments, transcript))
     }
 
     #[tracing::instrument(skip_all)]
     fn verify_instruction_lookups(
         preprocessing: &InstructionLookupsPreprocessing<F>,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         proof: InstructionLookupsProof<C, M, F, PCS, Self::InstructionSet, Self::Subtables>,
         commitment: &InstructionCommitment<PCS>,
         transcript: &mut ProofTranscript,
@@ -481,14 +1117,13 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
 
     #[tracing::instrument(skip_all)]
     fn verify_bytecode(
-        preprocessing: &BytecodePreprocessing<F>,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         proof: BytecodeProof<F, PCS>,
         commitment: &BytecodeCommitment<PCS>,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
         BytecodeProof::verify_memory_checking(
-            preprocessing,
+            &NoPreprocessing,
             generators,
             proof,
             commitment,
@@ -499,7 +1134,7 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
     #[tracing::instrument(skip_all)]
     fn verify_memory(
         preprocessing: &mut ReadWriteMemoryPreprocessing,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         proof: ReadWriteMemoryProof<F, PCS>,
         commitment: &JoltCommitments<PCS>,
         program_io: JoltDevice,
@@ -507,6 +1142,10 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
     ) -> Result<(), ProofVerifyError> {
         assert!(program_io.inputs.len() <= program_io.memory_layout.max_input_size as usize);
         assert!(program_io.outputs.len() <= program_io.memory_layout.max_output_size as usize);
+        assert!(
+            !program_io.memory_layout.hash_io
+                || program_io.outputs.len() <= IO_DIGEST_SIZE as usize
+        );
         preprocessing.program_io = Some(program_io);
 
         ReadWriteMemoryProof::verify(proof, generators, preprocessing, commitment, transcript)
@@ -514,7 +1153,7 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
 
     #[tracing::instrument(skip_all)]
     fn verify_r1cs(
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         proof: R1CSProof<F, PCS>,
         commitments: JoltCommitments<PCS>,
         transcript: &mut ProofTranscript,
@@ -527,7 +1166,7 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
     #[tracing::instrument(skip_all, name = "Jolt::r1cs_setup")]
     fn r1cs_setup(
         padded_trace_length: usize,
-        memory_start: u64,
+        memory_layout: &MemoryLayout,
         instructions: &[JoltTraceStep<Self::InstructionSet>],
         polynomials: &JoltPolynomials<F, PCS>,
         circuit_flags: Vec<F>,
@@ -545,7 +1184,7 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
         );
         let mut inputs_flat: Vec<Vec<F>> = inputs.clone_to_trace_len_chunks();
 
-        let builder = construct_jolt_constraints(padded_trace_length, memory_start);
+        let builder = construct_jolt_constraints(padded_trace_length, memory_layout);
         let aux = builder.compute_aux(&inputs_flat);
 
         assert_eq!(inputs.chunks_x.len(), inputs.chunks_y.len());
@@ -679,19 +1318,23 @@ pub trait Jolt<F: JoltField, PCS: CommitmentScheme<Field = F>, const C: usize, c
 
     fn fiat_shamir_preamble(
         transcript: &mut ProofTranscript,
+        program_digest: &[u8; 32],
         program_io: &JoltDevice,
         trace_length: usize,
     ) {
+        transcript.append_bytes(program_digest);
         transcript.append_u64(trace_length as u64);
         transcript.append_u64(C as u64);
         transcript.append_u64(M as u64);
         transcript.append_u64(Self::InstructionSet::COUNT as u64);
         transcript.append_u64(Self::Subtables::COUNT as u64);
+        transcript.append_u64(program_io.memory_layout.ram_start);
         transcript.append_u64(program_io.memory_layout.max_input_size);
         transcript.append_u64(program_io.memory_layout.max_output_size);
         transcript.append_bytes(&program_io.inputs);
         transcript.append_bytes(&program_io.outputs);
         transcript.append_u64(program_io.panic as u64);
+        transcript.append_u64(program_io.termination as u64);
     }
 }
 
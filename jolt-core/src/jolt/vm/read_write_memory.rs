@@ -21,14 +21,17 @@ use crate::{
         structured_poly::StructuredOpeningProof,
     },
     subprotocols::sumcheck::SumcheckInstanceProof,
-    utils::{errors::ProofVerifyError, math::Math, mul_0_optimized, transcript::ProofTranscript},
+    utils::{
+        errors::ProofVerifyError, index_to_field_bitvector, math::Math, mul_0_optimized,
+        transcript::ProofTranscript,
+    },
 };
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use common::constants::{
-    memory_address_to_witness_index, BYTES_PER_INSTRUCTION, MEMORY_OPS_PER_INSTRUCTION,
-    RAM_OPS_PER_INSTRUCTION, RAM_START_ADDRESS, REGISTER_COUNT, REG_OPS_PER_INSTRUCTION,
+    BYTES_PER_INSTRUCTION, IO_DIGEST_SIZE, MEMORY_OPS_PER_INSTRUCTION, RAM_OPS_PER_INSTRUCTION,
+    RAM_START_ADDRESS, REGISTER_COUNT, REG_OPS_PER_INSTRUCTION,
 };
-use common::rv_trace::{JoltDevice, MemoryLayout, MemoryOp};
+use common::rv_trace::{JoltDevice, MachineSnapshot, MemoryLayout, MemoryOp};
 
 use super::JoltTraceStep;
 use super::{timestamp_range_check::TimestampValidityProof, JoltCommitments, JoltPolynomials};
@@ -173,10 +176,15 @@ pub fn random_memory_trace<F: JoltField>(
     )
 }
 
-#[derive(Clone)]
+/// Where the program's initial memory, i.e. its code and data, lies in the memory witness.
+/// It is covered by an aligned block of memory cells, which the prover commits to like the
+/// advice region (see `ProgramMemory`), so the verifier doesn't need the program itself.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ReadWriteMemoryPreprocessing {
-    min_bytecode_address: u64,
-    pub bytecode_bytes: Vec<u8>,
+    /// Index of the block among the blocks of `program_block_size` memory cells.
+    program_block_index: usize,
+    /// Size of the block, a power of two.
+    program_block_size: usize,
     // HACK: The verifier will populate this field by copying it
     // over from the `ReadWriteMemoryProof`. Having `program_io` in
     // this preprocessing struct allows the verifier to access it
@@ -185,40 +193,73 @@ pub struct ReadWriteMemoryPreprocessing {
     pub program_io: Option<JoltDevice>,
 }
 
+/// The contents of the block of initial memory holding the program (see
+/// `ReadWriteMemoryPreprocessing`). Only the prover needs them; the verifier checks the
+/// prover's commitment to them against `JoltVerifierKey::program_memory_commitment`.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProgramMemory(Vec<u8>);
+
+impl ProgramMemory {
+    pub fn polynomial<F: JoltField>(&self) -> DensePolynomial<F> {
+        DensePolynomial::from_u64(&self.0.iter().map(|byte| *byte as u64).collect::<Vec<_>>())
+    }
+}
+
 impl ReadWriteMemoryPreprocessing {
     #[tracing::instrument(skip_all, name = "ReadWriteMemoryPreprocessing::preprocess")]
-    pub fn preprocess(memory_init: Vec<(u64, u8)>) -> Self {
+    pub fn preprocess(
+        memory_init: Vec<(u64, u8)>,
+        memory_layout: &MemoryLayout,
+    ) -> (Self, ProgramMemory) {
         let min_bytecode_address = memory_init
             .iter()
             .map(|(address, _)| *address)
             .min()
-            .unwrap_or(0);
+            .unwrap_or(memory_layout.ram_start);
+        // Anything below RAM would be mapped onto the I/O regions of the witness
+        assert!(
+            min_bytecode_address >= memory_layout.ram_start,
+            "program data at {:#x} lies below RAM, which starts at {:#x}",
+            min_bytecode_address,
+            memory_layout.ram_start
+        );
 
         let max_bytecode_address = memory_init
             .iter()
             .map(|(address, _)| *address)
             .max()
-            .unwrap_or(0)
+            .unwrap_or(memory_layout.ram_start)
             + (BYTES_PER_INSTRUCTION as u64 - 1); // For RV32I, instructions occupy 4 bytes, so the max bytecode address is the max instruction address + 3
 
-        let mut bytecode_bytes =
-            vec![0u8; (max_bytecode_address - min_bytecode_address + 1) as usize];
-        for (address, byte) in memory_init.iter() {
-            let remapped_index = (address - min_bytecode_address) as usize;
-            bytecode_bytes[remapped_index] = *byte;
+        // The smallest aligned block containing the program
+        let start = memory_layout.witness_index(min_bytecode_address);
+        let end = memory_layout.witness_index(max_bytecode_address);
+        let mut program_block_size = 1;
+        while start / program_block_size != end / program_block_size {
+            program_block_size *= 2;
         }
+        let program_block_index = start / program_block_size;
 
-        Self {
-            min_bytecode_address,
-            bytecode_bytes,
-            program_io: None,
+        let block_start = program_block_index * program_block_size;
+        let mut program_memory = vec![0u8; program_block_size];
+        for (address, byte) in memory_init.iter() {
+            program_memory[memory_layout.witness_index(*address) - block_start] = *byte;
         }
+
+        (
+            Self {
+                program_block_index,
+                program_block_size,
+                program_io: None,
+            },
+            ProgramMemory(program_memory),
+        )
     }
 }
 
 fn remap_address(a: u64, memory_layout: &MemoryLayout) -> u64 {
     if a >= memory_layout.input_start {
-        memory_address_to_witness_index(a, memory_layout.ram_witness_offset) as u64
+        memory_layout.witness_index(a) as u64
     } else if a < REGISTER_COUNT {
         // If a < REGISTER_COUNT, it is one of the registers and doesn't
         // need to be remapped
@@ -228,6 +269,23 @@ fn remap_address(a: u64, memory_layout: &MemoryLayout) -> u64 {
     }
 }
 
+/// Witness indices of the advice region.
+fn advice_witness_indices(memory_layout: &MemoryLayout) -> std::ops::Range<u64> {
+    let start = memory_layout.witness_index(memory_layout.advice_start);
+    start as u64..(start + memory_layout.advice_commitment_size()) as u64
+}
+
+/// Witness indices of the part of the output region that holds the guest's return value
+/// when its I/O is hashed, i.e. everything after the digest. Empty otherwise.
+fn hashed_output_witness_indices(memory_layout: &MemoryLayout) -> std::ops::Range<u64> {
+    if !memory_layout.hash_io {
+        return 0..0;
+    }
+    let start = memory_layout.witness_index(memory_layout.output_start + IO_DIGEST_SIZE);
+    let end = memory_layout.witness_index(memory_layout.output_end);
+    start as u64..end as u64
+}
+
 fn remap_address_index(remapped_a: u64) -> usize {
     (remapped_a - REGISTER_COUNT) as usize
 }
@@ -252,8 +310,19 @@ where
     _group: PhantomData<C>,
     /// Size of entire address space (i.e. registers + IO + RAM)
     memory_size: usize,
-    /// MLE of initial memory values. RAM is initialized to contain the program bytecode and inputs.
+    /// MLE of initial memory values. RAM is initialized to contain the program bytecode and inputs,
+    /// or, for a continuation segment, the state the previous segment ended in.
     pub v_init: DensePolynomial<F>,
+    /// Whether `v_init` is committed to by the prover, which is the case when memory starts
+    /// from a `MachineSnapshot`. Otherwise the verifier computes `v_init` itself.
+    pub v_init_committed: bool,
+    /// MLE of the advice region, committed to separately since the verifier doesn't know
+    /// its contents. Present if the memory layout has an advice region and `v_init` is not
+    /// committed to as a whole.
+    pub advice: Option<DensePolynomial<F>>,
+    /// MLE of the block of `v_init` holding the program, committed to separately so that the
+    /// verifier only needs its commitment. Present unless `v_init` is committed to as a whole.
+    pub program: Option<DensePolynomial<F>>,
     /// MLE of read/write addresses. For offline memory checking, each read is paired with a "virtual" write
     /// and vice versa, so the read addresses and write addresses are the same.
     pub a_ram: DensePolynomial<F>,
@@ -300,15 +369,25 @@ fn map_to_polys<F: JoltField, const N: usize>(vals: &[Vec<u64>; N]) -> [DensePol
 }
 
 impl<F: JoltField, C: CommitmentScheme<Field = F>> ReadWriteMemory<F, C> {
+    /// Constructs the memory polynomials for `trace`.
+    ///
+    /// `memory_size` fixes the size of the memory witness; if `None`, it is just large enough
+    /// to fit the addresses accessed in `trace`. If `initial_state` is provided, memory starts
+    /// out in that state rather than containing the program and its inputs.
     #[tracing::instrument(skip_all, name = "ReadWriteMemory::new")]
+    #[allow(clippy::too_many_arguments)]
     pub fn new<InstructionSet: JoltInstructionSet>(
         program_io: &JoltDevice,
         load_store_flags: &[DensePolynomial<F>],
         preprocessing: &ReadWriteMemoryPreprocessing,
+        program_memory: &ProgramMemory,
         trace: &Vec<JoltTraceStep<InstructionSet>>,
+        memory_size: Option<usize>,
+        initial_state: Option<&MachineSnapshot>,
     ) -> (Self, [Vec<u64>; MEMORY_OPS_PER_INSTRUCTION]) {
         assert!(program_io.inputs.len() <= program_io.memory_layout.max_input_size as usize);
         assert!(program_io.outputs.len() <= program_io.memory_layout.max_output_size as usize);
+        assert!(program_io.advice.len() <= program_io.memory_layout.max_advice_size as usize);
 
         let m = trace.len();
         assert!(m.is_power_of_two());
@@ -324,27 +403,30 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> ReadWriteMemory<F, C> {
             .max()
             .unwrap_or(0);
 
-        let memory_size = (program_io.memory_layout.ram_witness_offset + max_trace_address)
-            .next_power_of_two() as usize;
-        let mut v_init: Vec<u64> = vec![0; memory_size];
-        // Copy bytecode
-        let mut v_init_index = memory_address_to_witness_index(
-            preprocessing.min_bytecode_address,
-            program_io.memory_layout.ram_witness_offset,
-        );
-        for byte in preprocessing.bytecode_bytes.iter() {
-            v_init[v_init_index] = *byte as u64;
-            v_init_index += 1;
-        }
-        // Copy input bytes
-        v_init_index = memory_address_to_witness_index(
-            program_io.memory_layout.input_start,
-            program_io.memory_layout.ram_witness_offset,
-        );
-        for byte in program_io.inputs.iter() {
-            v_init[v_init_index] = *byte as u64;
-            v_init_index += 1;
-        }
+        let memory_size = match memory_size {
+            Some(memory_size) => {
+                assert!(memory_size.is_power_of_two());
+                assert!(
+                    memory_size > max_trace_address as usize,
+                    "trace accesses memory beyond the fixed memory size"
+                );
+                memory_size
+            }
+            None => (program_io.memory_layout.ram_witness_offset + max_trace_address)
+                .next_power_of_two() as usize,
+        };
+        let v_init = match initial_state {
+            Some(snapshot) => Self::snapshot_v_init(snapshot, memory_size),
+            None => Self::program_v_init(program_io, preprocessing, program_memory, memory_size),
+        };
+        let advice_size = program_io.memory_layout.advice_commitment_size();
+        let advice = (initial_state.is_none() && advice_size != 0).then(|| {
+            let advice_index = program_io
+                .memory_layout
+                .witness_index(program_io.memory_layout.advice_start);
+            DensePolynomial::from_u64(&v_init[advice_index..advice_index + advice_size])
+        });
+        let program = initial_state.is_none().then(|| program_memory.polynomial());
 
         #[cfg(test)]
         let mut init_tuples: HashSet<(u64, u64, u64)> = HashSet::new();
@@ -895,6 +977,9 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> ReadWriteMemory<F, C> {
                 _group: PhantomData,
                 memory_size,
                 v_init,
+                v_init_committed: initial_state.is_some(),
+                advice,
+                program,
                 a_ram,
                 v_read,
                 v_write_rd,
@@ -908,6 +993,98 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> ReadWriteMemory<F, C> {
         )
     }
 
+    /// Initial memory for a program run from the start: the program bytecode, its inputs
+    /// and its advice.
+    fn program_v_init(
+        program_io: &JoltDevice,
+        preprocessing: &ReadWriteMemoryPreprocessing,
+        program_memory: &ProgramMemory,
+        memory_size: usize,
+    ) -> Vec<u64> {
+        let mut v_init: Vec<u64> = vec![0; memory_size];
+        // Copy the program's block. Any other memory it covers is filled in below.
+        let block_start = preprocessing.program_block_index * preprocessing.program_block_size;
+        for (value, byte) in v_init[block_start..]
+            .iter_mut()
+            .zip(program_memory.0.iter())
+        {
+            *value = *byte as u64;
+        }
+        // Copy input bytes
+        let mut v_init_index = program_io
+            .memory_layout
+            .witness_index(program_io.memory_layout.input_start);
+        for byte in program_io.inputs.iter() {
+            v_init[v_init_index] = *byte as u64;
+            v_init_index += 1;
+        }
+        // Copy advice bytes
+        v_init_index = program_io
+            .memory_layout
+            .witness_index(program_io.memory_layout.advice_start);
+        for byte in program_io.advice.iter() {
+            v_init[v_init_index] = *byte as u64;
+            v_init_index += 1;
+        }
+        v_init
+    }
+
+    /// Initial memory for a continuation segment: registers, program I/O and RAM as captured
+    /// in `snapshot`.
+    fn snapshot_v_init(snapshot: &MachineSnapshot, memory_size: usize) -> Vec<u64> {
+        let memory_layout = &snapshot.device.memory_layout;
+        let mut v_init: Vec<u64> = vec![0; memory_size];
+        // Copy registers
+        assert!(snapshot.registers.len() <= REGISTER_COUNT as usize);
+        for (register, value) in snapshot.registers.iter().enumerate() {
+            v_init[register] = *value;
+        }
+        // Copy input bytes
+        let mut v_init_index = memory_layout.witness_index(memory_layout.input_start);
+        for byte in snapshot.device.inputs.iter() {
+            v_init[v_init_index] = *byte as u64;
+            v_init_index += 1;
+        }
+        // Copy advice bytes
+        v_init_index = memory_layout.witness_index(memory_layout.advice_start);
+        for byte in snapshot.device.advice.iter() {
+            v_init[v_init_index] = *byte as u64;
+            v_init_index += 1;
+        }
+        // Copy output bytes
+        v_init_index = memory_layout.witness_index(memory_layout.output_start);
+        for byte in snapshot.device.outputs.iter() {
+            v_init[v_init_index] = *byte as u64;
+            v_init_index += 1;
+        }
+        // Copy panic and termination bits
+        v_init[memory_layout.witness_index(memory_layout.panic)] = snapshot.device.panic as u64;
+        v_init[memory_layout.witness_index(memory_layout.termination)] =
+            snapshot.device.termination as u64;
+        // The console address holds the last byte written to it
+        v_init[memory_layout.witness_index(memory_layout.console)] =
+            snapshot.device.console.last().copied().unwrap_or(0) as u64;
+        // Likewise for the hint address. Hint requests are zero-terminated, so it holds zero
+        // unless a request is partially written.
+        v_init[memory_layout.witness_index(memory_layout.hint)] =
+            snapshot.device.hint_request.last().copied().unwrap_or(0) as u64;
+        // The guest's panic handler bounds what it writes to the diagnostics address by
+        // `MAX_DIAGNOSTICS_SIZE`, so the last byte kept is the last byte written
+        v_init[memory_layout.witness_index(memory_layout.diagnostics)] =
+            snapshot.device.diagnostics.last().copied().unwrap_or(0) as u64;
+        // Copy RAM
+        for (address, byte) in snapshot.ram.iter() {
+            let index = memory_layout.witness_index(*address);
+            assert!(
+                index < memory_size,
+                "snapshot RAM address {:#x} is out of bounds",
+                address
+            );
+            v_init[index] = *byte as u64;
+        }
+        v_init
+    }
+
     #[tracing::instrument(skip_all, name = "ReadWriteMemory::get_polys_r1cs")]
     pub fn get_polys_r1cs<'a>(&'a self) -> (&'a [F], Vec<&'a F>, Vec<&'a F>) {
         let (a_polys, (v_read_polys, v_write_polys)) = rayon::join(
@@ -959,6 +1136,14 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> ReadWriteMemory<F, C> {
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct MemoryCommitment<C: CommitmentScheme> {
     pub trace_commitments: Vec<C::Commitment>,
+    /// Commitment to `v_init`, present only if memory starts from a `MachineSnapshot`.
+    pub v_init_commitment: Option<C::Commitment>,
+    /// Commitment to the advice region, present only if the program has one and `v_init`
+    /// is not committed to as a whole.
+    pub advice_commitment: Option<C::Commitment>,
+    /// Commitment to the program's block of `v_init`, present unless `v_init` is committed to
+    /// as a whole. Checked against `JoltVerifierKey::program_memory_commitment`.
+    pub program_commitment: Option<C::Commitment>,
     pub v_final_commitment: C::Commitment,
     pub t_final_commitment: C::Commitment,
 }
@@ -969,6 +1154,15 @@ impl<C: CommitmentScheme> AppendToTranscript for MemoryCommitment<C> {
         for commitment in &self.trace_commitments {
             commitment.append_to_transcript(transcript);
         }
+        if let Some(v_init_commitment) = &self.v_init_commitment {
+            v_init_commitment.append_to_transcript(transcript);
+        }
+        if let Some(advice_commitment) = &self.advice_commitment {
+            advice_commitment.append_to_transcript(transcript);
+        }
+        if let Some(program_commitment) = &self.program_commitment {
+            program_commitment.append_to_transcript(transcript);
+        }
         self.v_final_commitment.append_to_transcript(transcript);
         self.t_final_commitment.append_to_transcript(transcript);
         transcript.append_message(b"MemoryCommitment_end");
@@ -1082,7 +1276,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &JoltCommitments<C>,
         opening_point: &[F],
@@ -1105,6 +1299,7 @@ where
                 .iter()
                 .chain(commitment.read_write_memory.trace_commitments.iter())
                 .collect::<Vec<_>>(),
+            BatchType::Big,
             transcript,
         )
     }
@@ -1119,6 +1314,21 @@ where
     a_init_final: Option<F>,
     /// Evaluation of the v_init polynomial at the opening point. Computed by the verifier in `compute_verifier_openings`.
     v_init: Option<F>,
+    /// Evaluation of the v_init polynomial at the opening point, if v_init is committed.
+    /// Provided by the prover and used in place of the verifier's own computation.
+    v_init_committed: Option<F>,
+    /// Evaluation of the advice polynomial at the low-order variables of the opening point,
+    /// if the advice region is committed.
+    advice: Option<F>,
+    /// Size of the committed advice region. Set by the verifier from the memory layout in
+    /// `ReadWriteMemoryProof::verify`.
+    advice_size: Option<usize>,
+    /// Evaluation of the program polynomial at the low-order variables of the opening point,
+    /// if the program is committed.
+    program: Option<F>,
+    /// Size of the program's block of memory. Set by the verifier from the preprocessing in
+    /// `ReadWriteMemoryProof::verify`.
+    program_block_size: Option<usize>,
     /// Evaluation of the v_final polynomial at the opening point.
     v_final: F,
     /// Evaluation of the t_final polynomial at the opening point.
@@ -1132,6 +1342,22 @@ where
     C: CommitmentScheme<Field = F>,
 {
     v_t_opening_proof: C::BatchedProof,
+    advice_opening_proof: Option<C::Proof>,
+    program_opening_proof: Option<C::Proof>,
+}
+
+/// The advice and program polynomials each cover an aligned block of `v_init`, so they are
+/// opened at the low-order variables of `v_init`'s opening point.
+fn block_opening_point<F: JoltField>(opening_point: &[F], block_size: usize) -> &[F] {
+    &opening_point[opening_point.len() - block_size.log_2()..]
+}
+
+/// The factor by which the evaluation of a polynomial covering the `block_index`-th aligned
+/// block of `block_size` entries of `v_init` contributes to `v_init`'s evaluation.
+fn block_eq<F: JoltField>(opening_point: &[F], block_index: usize, block_size: usize) -> F {
+    let num_block_vars = opening_point.len() - block_size.log_2();
+    EqPolynomial::new(opening_point[..num_block_vars].to_vec())
+        .evaluate(&index_to_field_bitvector(block_index, num_block_vars))
 }
 
 impl<F, C> StructuredOpeningProof<F, C, JoltPolynomials<F, C>> for MemoryInitFinalOpenings<F>
@@ -1149,10 +1375,30 @@ where
             || polynomials.read_write_memory.v_final.evaluate_at_chi(&chis),
             || polynomials.read_write_memory.t_final.evaluate_at_chi(&chis),
         );
+        let v_init_committed = if polynomials.read_write_memory.v_init_committed {
+            Some(polynomials.read_write_memory.v_init.evaluate_at_chi(&chis))
+        } else {
+            None
+        };
+        let advice = polynomials
+            .read_write_memory
+            .advice
+            .as_ref()
+            .map(|advice| advice.evaluate(block_opening_point(opening_point, advice.len())));
+        let program = polynomials
+            .read_write_memory
+            .program
+            .as_ref()
+            .map(|program| program.evaluate(block_opening_point(opening_point, program.len())));
 
         Self {
             a_init_final: None,
             v_init: None,
+            v_init_committed,
+            advice,
+            advice_size: None,
+            program,
+            program_block_size: None,
             v_final,
             t_final,
         }
@@ -1166,19 +1412,49 @@ where
         openings: &Self,
         transcript: &mut ProofTranscript,
     ) -> Self::Proof {
+        let mut polys = vec![
+            &polynomials.read_write_memory.v_final,
+            &polynomials.read_write_memory.t_final,
+        ];
+        let mut evals = vec![openings.v_final, openings.t_final];
+        if let Some(v_init) = openings.v_init_committed {
+            polys.push(&polynomials.read_write_memory.v_init);
+            evals.push(v_init);
+        }
         let v_t_opening_proof = C::batch_prove(
             generators,
-            &[
-                &polynomials.read_write_memory.v_final,
-                &polynomials.read_write_memory.t_final,
-            ],
+            &polys,
             opening_point,
-            &[openings.v_final, openings.t_final],
+            &evals,
             BatchType::Small,
             transcript,
         );
+        let advice_opening_proof = polynomials.read_write_memory.advice.as_ref().map(|advice| {
+            C::prove(
+                generators,
+                advice,
+                block_opening_point(opening_point, advice.len()),
+                transcript,
+            )
+        });
+        let program_opening_proof = polynomials
+            .read_write_memory
+            .program
+            .as_ref()
+            .map(|program| {
+                C::prove(
+                    generators,
+                    program,
+                    block_opening_point(opening_point, program.len()),
+                    transcript,
+                )
+            });
 
-        Self::Proof { v_t_opening_proof }
+        Self::Proof {
+            v_t_opening_proof,
+            advice_opening_proof,
+            program_opening_proof,
+        }
     }
 
     fn compute_verifier_openings(
@@ -1189,53 +1465,125 @@ where
         self.a_init_final =
             Some(IdentityPolynomial::new(opening_point.len()).evaluate(opening_point));
 
+        if let Some(v_init) = self.v_init_committed {
+            // Checked against the v_init commitment in `verify_openings`
+            self.v_init = Some(v_init);
+            return;
+        }
+
         let memory_layout = &preprocessing.program_io.as_ref().unwrap().memory_layout;
 
         // TODO(moodlezoup): Compute opening without instantiating v_init polynomial itself
         let memory_size = opening_point.len().pow2();
         let mut v_init: Vec<u64> = vec![0; memory_size];
-        // Copy bytecode
-        let mut v_init_index = memory_address_to_witness_index(
-            preprocessing.min_bytecode_address,
-            memory_layout.ram_witness_offset,
-        );
-        for byte in preprocessing.bytecode_bytes.iter() {
-            v_init[v_init_index] = *byte as u64;
-            v_init_index += 1;
-        }
         // Copy input bytes
-        v_init_index = memory_address_to_witness_index(
-            memory_layout.input_start,
-            memory_layout.ram_witness_offset,
-        );
+        let mut v_init_index = memory_layout.witness_index(memory_layout.input_start);
         for byte in preprocessing.program_io.as_ref().unwrap().inputs.iter() {
             v_init[v_init_index] = *byte as u64;
             v_init_index += 1;
         }
 
-        self.v_init = Some(DensePolynomial::from_u64(&v_init).evaluate(opening_point));
+        let mut v_init_eval = DensePolynomial::from_u64(&v_init).evaluate(opening_point);
+        // The advice region and the program are zero above; add in their contributions from
+        // their openings, which are checked against their commitments in `verify_openings`
+        if let Some(advice) = self.advice {
+            let advice_size = memory_layout.advice_commitment_size();
+            let advice_index = memory_layout.witness_index(memory_layout.advice_start);
+            v_init_eval +=
+                block_eq(opening_point, advice_index / advice_size, advice_size) * advice;
+        }
+        if let Some(program) = self.program {
+            v_init_eval += block_eq(
+                opening_point,
+                preprocessing.program_block_index,
+                preprocessing.program_block_size,
+            ) * program;
+        }
+
+        self.v_init = Some(v_init_eval);
     }
 
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &JoltCommitments<C>,
         opening_point: &[F],
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
+        let mut openings = vec![self.v_final, self.t_final];
+        let mut commitments = vec![
+            &commitment.read_write_memory.v_final_commitment,
+            &commitment.read_write_memory.t_final_commitment,
+        ];
+        match (
+            self.v_init_committed,
+            &commitment.read_write_memory.v_init_commitment,
+        ) {
+            (Some(v_init), Some(v_init_commitment)) => {
+                openings.push(v_init);
+                commitments.push(v_init_commitment);
+            }
+            (None, None) => {}
+            _ => return Err(ProofVerifyError::InitialMemoryMismatch),
+        }
         C::batch_verify(
             &opening_proof.v_t_opening_proof,
             generators,
             opening_point,
-            &[self.v_final, self.t_final],
-            &[
-                &commitment.read_write_memory.v_final_commitment,
-                &commitment.read_write_memory.t_final_commitment,
-            ],
+            &openings,
+            &commitments,
+            BatchType::Small,
             transcript,
         )?;
 
+        // The advice region is committed separately unless v_init is committed as a whole
+        let advice_size = self.advice_size.unwrap();
+        let expects_advice = advice_size != 0 && self.v_init_committed.is_none();
+        match (
+            self.advice,
+            &commitment.read_write_memory.advice_commitment,
+            &opening_proof.advice_opening_proof,
+        ) {
+            (Some(advice), Some(advice_commitment), Some(advice_opening_proof))
+                if expects_advice =>
+            {
+                C::verify(
+                    advice_opening_proof,
+                    generators,
+                    transcript,
+                    block_opening_point(opening_point, advice_size),
+                    &advice,
+                    advice_commitment,
+                )?;
+            }
+            (None, None, None) if !expects_advice => {}
+            _ => return Err(ProofVerifyError::InitialMemoryMismatch),
+        }
+
+        // Likewise for the program
+        let expects_program = self.v_init_committed.is_none();
+        match (
+            self.program,
+            &commitment.read_write_memory.program_commitment,
+            &opening_proof.program_opening_proof,
+        ) {
+            (Some(program), Some(program_commitment), Some(program_opening_proof))
+                if expects_program =>
+            {
+                C::verify(
+                    program_opening_proof,
+                    generators,
+                    transcript,
+                    block_opening_point(opening_point, self.program_block_size.unwrap()),
+                    &program,
+                    program_commitment,
+                )?;
+            }
+            (None, None, None) if !expects_program => {}
+            _ => return Err(ProofVerifyError::InitialMemoryMismatch),
+        }
+
         Ok(())
     }
 }
@@ -1511,10 +1859,29 @@ where
         let r_eq = transcript.challenge_vector(num_rounds);
         let eq: DensePolynomial<F> = DensePolynomial::new(EqPolynomial::evals(&r_eq));
 
+        // Console output, hint requests, diagnostics, advice and hashed outputs are not part
+        // of the program I/O, so they are left unchecked
+        let console_index = program_io
+            .memory_layout
+            .witness_index(program_io.memory_layout.console) as u64;
+        let hint_index = program_io
+            .memory_layout
+            .witness_index(program_io.memory_layout.hint) as u64;
+        let diagnostics_index = program_io
+            .memory_layout
+            .witness_index(program_io.memory_layout.diagnostics)
+            as u64;
+        let advice_indices = advice_witness_indices(&program_io.memory_layout);
+        let hashed_output_indices = hashed_output_witness_indices(&program_io.memory_layout);
         let io_witness_range: Vec<_> = (0..polynomials.memory_size as u64)
             .map(|i| {
                 if i >= program_io.memory_layout.input_start
                     && i < program_io.memory_layout.ram_witness_offset
+                    && i != console_index
+                    && i != hint_index
+                    && i != diagnostics_index
+                    && !advice_indices.contains(&i)
+                    && !hashed_output_indices.contains(&i)
                 {
                     F::one()
                 } else {
@@ -1525,28 +1892,29 @@ where
 
         let mut v_io: Vec<u64> = vec![0; polynomials.memory_size];
         // Copy input bytes
-        let mut input_index = memory_address_to_witness_index(
-            program_io.memory_layout.input_start,
-            program_io.memory_layout.ram_witness_offset,
-        );
+        let mut input_index = program_io
+            .memory_layout
+            .witness_index(program_io.memory_layout.input_start);
         for byte in program_io.inputs.iter() {
             v_io[input_index] = *byte as u64;
             input_index += 1;
         }
         // Copy output bytes
-        let mut output_index = memory_address_to_witness_index(
-            program_io.memory_layout.output_start,
-            program_io.memory_layout.ram_witness_offset,
-        );
+        let mut output_index = program_io
+            .memory_layout
+            .witness_index(program_io.memory_layout.output_start);
         for byte in program_io.outputs.iter() {
             v_io[output_index] = *byte as u64;
             output_index += 1;
         }
         // Copy panic bit
-        v_io[memory_address_to_witness_index(
-            program_io.memory_layout.panic,
-            program_io.memory_layout.ram_witness_offset,
-        )] = program_io.panic as u64;
+        v_io[program_io
+            .memory_layout
+            .witness_index(program_io.memory_layout.panic)] = program_io.panic as u64;
+        // Copy termination bit
+        v_io[program_io
+            .memory_layout
+            .witness_index(program_io.memory_layout.termination)] = program_io.termination as u64;
 
         let mut sumcheck_polys = vec![
             eq,
@@ -1582,7 +1950,7 @@ where
     fn verify(
         proof: &Self,
         preprocessing: &ReadWriteMemoryPreprocessing,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         commitment: &MemoryCommitment<C>,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
@@ -1604,9 +1972,22 @@ where
             "Ram witness offset must be a power of two"
         );
 
+        // Console output, hint requests, diagnostics, advice and hashed outputs are not part
+        // of the program I/O, so they are left unchecked
+        let console_index = memory_layout.witness_index(memory_layout.console) as u64;
+        let hint_index = memory_layout.witness_index(memory_layout.hint) as u64;
+        let diagnostics_index = memory_layout.witness_index(memory_layout.diagnostics) as u64;
+        let advice_indices = advice_witness_indices(memory_layout);
+        let hashed_output_indices = hashed_output_witness_indices(memory_layout);
         let io_witness_range: Vec<_> = (0..nonzero_memory_size as u64)
             .map(|i| {
-                if i >= memory_layout.input_start {
+                if i >= memory_layout.input_start
+                    && i != console_index
+                    && i != hint_index
+                    && i != diagnostics_index
+                    && !advice_indices.contains(&i)
+                    && !hashed_output_indices.contains(&i)
+                {
                     F::one()
                 } else {
                     F::zero()
@@ -1621,28 +2002,23 @@ where
 
         let mut v_io: Vec<u64> = vec![0; nonzero_memory_size];
         // Copy input bytes
-        let mut input_index = memory_address_to_witness_index(
-            memory_layout.input_start,
-            memory_layout.ram_witness_offset,
-        );
+        let mut input_index = memory_layout.witness_index(memory_layout.input_start);
         for byte in preprocessing.program_io.as_ref().unwrap().inputs.iter() {
             v_io[input_index] = *byte as u64;
             input_index += 1;
         }
         // Copy output bytes
-        let mut output_index = memory_address_to_witness_index(
-            memory_layout.output_start,
-            memory_layout.ram_witness_offset,
-        );
+        let mut output_index = memory_layout.witness_index(memory_layout.output_start);
         for byte in preprocessing.program_io.as_ref().unwrap().outputs.iter() {
             v_io[output_index] = *byte as u64;
             output_index += 1;
         }
         // Copy panic bit
-        v_io[memory_address_to_witness_index(
-            memory_layout.panic,
-            memory_layout.ram_witness_offset,
-        )] = preprocessing.program_io.as_ref().unwrap().panic as u64;
+        v_io[memory_layout.witness_index(memory_layout.panic)] =
+            preprocessing.program_io.as_ref().unwrap().panic as u64;
+        // Copy termination bit
+        v_io[memory_layout.witness_index(memory_layout.termination)] =
+            preprocessing.program_io.as_ref().unwrap().termination as u64;
         let mut v_io_eval =
             DensePolynomial::from_u64(&v_io).evaluate(&r_sumcheck[..log_nonzero_memory_size]);
         v_io_eval *= r_prod;
@@ -1724,11 +2100,22 @@ where
 
     pub fn verify(
         mut self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         preprocessing: &ReadWriteMemoryPreprocessing,
         commitment: &JoltCommitments<C>,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
+        self.memory_checking_proof.init_final_openings.advice_size = Some(
+            preprocessing
+                .program_io
+                .as_ref()
+                .unwrap()
+                .memory_layout
+                .advice_commitment_size(),
+        );
+        self.memory_checking_proof
+            .init_final_openings
+            .program_block_size = Some(preprocessing.program_block_size);
         ReadWriteMemoryProof::verify_memory_checking(
             preprocessing,
             generators,
@@ -3,9 +3,10 @@ use crate::jolt::instruction::virtual_assert_valid_div0::AssertValidDiv0Instruct
 use crate::jolt::instruction::virtual_assert_valid_unsigned_remainder::AssertValidUnsignedRemainderInstruction;
 use crate::jolt::subtable::div_by_zero::DivByZeroSubtable;
 use crate::jolt::subtable::right_is_zero::RightIsZeroSubtable;
+use crate::poly::commitment::hyperkzg::HyperKZG;
 use crate::poly::commitment::hyrax::HyraxScheme;
-use ark_bn254::{Fr, G1Projective};
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use crate::poly::commitment::zeromorph::Zeromorph;
+use ark_bn254::{Bn254, G1Projective};
 use enum_dispatch::enum_dispatch;
 use rand::{prelude::StdRng, RngCore};
 use serde::{Deserialize, Serialize};
@@ -13,7 +14,7 @@ use std::any::TypeId;
 use strum::{EnumCount, IntoEnumIterator};
 use strum_macros::{EnumCount as EnumCountMacro, EnumIter};
 
-use super::{Jolt, JoltCommitments, JoltProof};
+use super::{Jolt, JoltProof, JoltVMProof};
 use crate::jolt::instruction::{
     add::ADDInstruction, and::ANDInstruction, beq::BEQInstruction, bge::BGEInstruction,
     bgeu::BGEUInstruction, bne::BNEInstruction, lb::LBInstruction, lh::LHInstruction,
@@ -172,52 +173,15 @@ where
 
 pub type RV32IJoltProof<F, CS> = JoltProof<C, M, F, CS, RV32I, RV32ISubtables<F>>;
 
-use eyre::Result;
-use std::fs::File;
-use std::path::PathBuf;
-
 pub type PCS = HyraxScheme<G1Projective>;
 
-#[derive(CanonicalSerialize, CanonicalDeserialize)]
-pub struct RV32IHyraxProof {
-    pub proof: RV32IJoltProof<Fr, PCS>,
-    pub commitments: JoltCommitments<PCS>,
-}
-
-impl RV32IHyraxProof {
-    /// Gets the byte size of the full proof
-    pub fn size(&self) -> Result<usize> {
-        let mut buffer = Vec::new();
-        self.serialize_compressed(&mut buffer)?;
-        Ok(buffer.len())
-    }
-
-    /// Saves the proof to a file
-    pub fn save_to_file<P: Into<PathBuf>>(&self, path: P) -> Result<()> {
-        let file = File::create(path.into())?;
-        self.serialize_compressed(file)?;
-        Ok(())
-    }
+/// A Jolt proof of an RV32I program, along with the commitments it was
+/// produced against, for any commitment scheme over the BN254 scalar field.
+pub type RV32IProof<CS> = JoltVMProof<RV32IJoltVM, CS, C, M>;
 
-    /// Reads a proof from a file
-    pub fn from_file<P: Into<PathBuf>>(path: P) -> Result<Self> {
-        let file = File::open(path.into())?;
-        Ok(RV32IHyraxProof::deserialize_compressed(file)?)
-    }
-
-    /// Serializes the proof to a byte vector
-    pub fn serialize_to_bytes(&self) -> Result<Vec<u8>> {
-        let mut buffer = Vec::new();
-        self.serialize_compressed(&mut buffer)?;
-        Ok(buffer)
-    }
-
-    /// Deserializes a proof from a byte vector
-    pub fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self> {
-        let cursor = std::io::Cursor::new(bytes);
-        Ok(RV32IHyraxProof::deserialize_compressed(cursor)?)
-    }
-}
+pub type RV32IHyraxProof = RV32IProof<HyraxScheme<G1Projective>>;
+pub type RV32IHyperKZGProof = RV32IProof<HyperKZG<Bn254>>;
+pub type RV32IZeromorphProof = RV32IProof<Zeromorph<Bn254>>;
 
 // ==================== TEST ====================
 
@@ -230,12 +194,25 @@ mod tests {
     use crate::field::JoltField;
     use crate::host;
     use crate::jolt::instruction::JoltInstruction;
-    use crate::jolt::vm::rv32i_vm::{Jolt, RV32IJoltVM, C, M};
+    use crate::jolt::vm::bytecode::BytecodeRow;
+    use crate::jolt::vm::rv32i_vm::{
+        Jolt, RV32IJoltProof, RV32IJoltVM, RV32IProof, RV32ISubtables, C, M, RV32I,
+    };
+    use crate::jolt::vm::{
+        program_digest, JoltCommitments, JoltPreprocessing, JoltVerifierKey, SegmentProof,
+    };
     use crate::poly::commitment::commitment_scheme::CommitmentScheme;
     use crate::poly::commitment::hyperkzg::HyperKZG;
     use crate::poly::commitment::hyrax::HyraxScheme;
+    use crate::poly::commitment::kzg::SRSError;
     use crate::poly::commitment::mock::MockCommitScheme;
     use crate::poly::commitment::zeromorph::Zeromorph;
+    use crate::solidity::evm::{self, EvmError};
+    use crate::utils::errors::ProofVerifyError;
+    use ark_std::One;
+    use common::constants::{DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, RAM_START_ADDRESS};
+    use common::rv_trace::{ELFInstruction, MemoryLayout, RV32IM};
+    use common::to_ram_address;
     use std::sync::Mutex;
     use strum::{EnumCount, IntoEnumIterator};
 
@@ -245,6 +222,27 @@ mod tests {
         static ref SHA3_FILE_LOCK: Mutex<()> = Mutex::new(());
     }
 
+    /// `RV32IJoltVM::preprocess` with `CommitmentScheme::seeded_setup`, so that the KZG-based
+    /// schemes don't need a ceremony file.
+    fn preprocess<F: JoltField, PCS: CommitmentScheme<Field = F>>(
+        bytecode: Vec<ELFInstruction>,
+        memory_init: Vec<(u64, u8)>,
+        memory_layout: MemoryLayout,
+        max_bytecode_size: usize,
+        max_memory_address: usize,
+        max_trace_length: usize,
+    ) -> Result<JoltPreprocessing<F, PCS>, SRSError> {
+        <RV32IJoltVM as Jolt<F, PCS, C, M>>::preprocess_with_setup(
+            bytecode,
+            memory_init,
+            memory_layout,
+            max_bytecode_size,
+            max_memory_address,
+            max_trace_length,
+            |shapes| Ok(PCS::seeded_setup(shapes)),
+        )
+    }
+
     fn test_instruction_set_subtables<PCS: CommitmentScheme>() {
         let mut subtable_set: HashSet<_> = HashSet::new();
         for instruction in <RV32IJoltVM as Jolt<_, PCS, C, M>>::InstructionSet::iter() {
@@ -269,23 +267,130 @@ mod tests {
         test_instruction_set_subtables::<HyperKZG<Bn254>>();
     }
 
+    #[test]
+    fn preprocessing_serialization() {
+        let bytecode = vec![ELFInstruction {
+            address: RAM_START_ADDRESS,
+            opcode: RV32IM::ADD,
+            rs1: Some(1),
+            rs2: Some(2),
+            rd: Some(3),
+            imm: None,
+            virtual_sequence_remaining: None,
+        }];
+        let memory_init = vec![(RAM_START_ADDRESS, 0xb3), (RAM_START_ADDRESS + 1, 0x01)];
+        let memory_layout = MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, 0);
+        let preprocessing: JoltPreprocessing<Fr, HyraxScheme<G1Projective>> = preprocess(
+            bytecode,
+            memory_init,
+            memory_layout,
+            1 << 10,
+            1 << 10,
+            1 << 10,
+        )
+        .unwrap();
+
+        let bytes = preprocessing.serialize_to_bytes().unwrap();
+        let deserialized =
+            JoltPreprocessing::<Fr, HyraxScheme<G1Projective>>::deserialize_from_bytes(&bytes)
+                .unwrap();
+        assert_eq!(bytes, deserialized.serialize_to_bytes().unwrap());
+    }
+
+    #[test]
+    fn verifier_key_size_independent_of_program_size() {
+        let bytecode = vec![ELFInstruction {
+            address: RAM_START_ADDRESS,
+            opcode: RV32IM::ADD,
+            rs1: Some(1),
+            rs2: Some(2),
+            rd: Some(3),
+            imm: None,
+            virtual_sequence_remaining: None,
+        }];
+        let verifier_key_size = |program_size: u64| {
+            let memory_init = (0..program_size)
+                .map(|i| (RAM_START_ADDRESS + i, i as u8))
+                .collect();
+            let memory_layout =
+                MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, 0);
+            let preprocessing: JoltPreprocessing<Fr, HyperKZG<Bn254>> = preprocess(
+                bytecode.clone(),
+                memory_init,
+                memory_layout,
+                1 << 10,
+                1 << 16,
+                1 << 10,
+            )
+            .unwrap();
+            preprocessing
+                .verifier_key()
+                .serialize_to_bytes()
+                .unwrap()
+                .len()
+        };
+        assert_eq!(verifier_key_size(1 << 4), verifier_key_size(1 << 10));
+    }
+
+    #[test]
+    fn program_digest_binds_program() {
+        let bytecode = vec![
+            BytecodeRow::new(to_ram_address(0), 2, 3, 1, 2, 0),
+            BytecodeRow::new(to_ram_address(1), 4, 5, 3, 0, 42),
+        ];
+        let memory_init = vec![(RAM_START_ADDRESS, 0xb3), (RAM_START_ADDRESS + 1, 0x01)];
+        let memory_layout = MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE, DEFAULT_MAX_OUTPUT_SIZE, 0);
+        let digest = program_digest(&bytecode, &memory_init, &memory_layout);
+
+        // Independent of the order memory is initialized in
+        let reversed_init: Vec<_> = memory_init.iter().rev().cloned().collect();
+        assert_eq!(
+            digest,
+            program_digest(&bytecode, &reversed_init, &memory_layout)
+        );
+
+        // Binds the bytecode, initial memory and memory layout
+        assert_ne!(
+            digest,
+            program_digest(&bytecode[..1], &memory_init, &memory_layout)
+        );
+        assert_ne!(
+            digest,
+            program_digest(&bytecode, &memory_init[..1], &memory_layout)
+        );
+        let other_layout =
+            MemoryLayout::new(DEFAULT_MAX_INPUT_SIZE * 2, DEFAULT_MAX_OUTPUT_SIZE, 0);
+        assert_ne!(
+            digest,
+            program_digest(&bytecode, &memory_init, &other_layout)
+        );
+    }
+
     fn fib_e2e<F: JoltField, PCS: CommitmentScheme<Field = F>>() {
         let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
         let mut program = host::Program::new("fibonacci-guest");
         program.set_input(&9u32);
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
         drop(artifact_guard);
 
-        let preprocessing =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 20);
+        let preprocessing = preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        )
+        .unwrap();
         let (proof, commitments) = <RV32IJoltVM as Jolt<F, PCS, C, M>>::prove(
             io_device,
             trace,
             circuit_flags,
             preprocessing.clone(),
         );
-        let verification_result = RV32IJoltVM::verify(preprocessing, proof, commitments);
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing.verifier_key(), proof, commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
@@ -313,6 +418,295 @@ mod tests {
         fib_e2e::<Fr, HyperKZG<Bn254>>();
     }
 
+    #[test]
+    fn fib_e2e_serialized_verifier_key() {
+        type PCS = HyraxScheme<G1Projective>;
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = preprocess(
+            bytecode,
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        )
+        .unwrap();
+        let (proof, commitments) = <RV32IJoltVM as Jolt<Fr, PCS, C, M>>::prove(
+            io_device,
+            trace,
+            circuit_flags,
+            preprocessing.clone(),
+        );
+
+        let bytes = preprocessing.verifier_key().serialize_to_bytes().unwrap();
+        let verifier_key = JoltVerifierKey::<Fr, PCS>::deserialize_from_bytes(&bytes).unwrap();
+        let verification_result = RV32IJoltVM::verify(verifier_key, proof, commitments);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn fib_console_not_program_io() {
+        type PCS = HyraxScheme<G1Projective>;
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (mut io_device, trace, circuit_flags) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = preprocess(
+            bytecode,
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        )
+        .unwrap();
+        io_device.console = b"debug output".to_vec();
+        let (proof, commitments) = <RV32IJoltVM as Jolt<Fr, PCS, C, M>>::prove(
+            io_device,
+            trace,
+            circuit_flags,
+            preprocessing.clone(),
+        );
+
+        // Console output is dropped from serialized proofs, which still verify
+        let bytes = RV32IProof { proof, commitments }
+            .serialize_to_bytes()
+            .unwrap();
+        let deserialized = RV32IProof::<PCS>::deserialize_from_bytes(&bytes).unwrap();
+        assert!(deserialized.proof.program_io.console.is_empty());
+        let verification_result = RV32IJoltVM::verify(
+            preprocessing.verifier_key(),
+            deserialized.proof,
+            deserialized.commitments,
+        );
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn fib_e2e_ram_start() {
+        type PCS = HyraxScheme<G1Projective>;
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_ram_start(0x4000_0000);
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
+        drop(artifact_guard);
+        assert_eq!(io_device.memory_layout.ram_start, 0x4000_0000);
+        assert!(bytecode
+            .iter()
+            .all(|instruction| instruction.address >= 0x4000_0000));
+
+        let preprocessing = preprocess(
+            bytecode,
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        )
+        .unwrap();
+        let (proof, commitments) = <RV32IJoltVM as Jolt<Fr, PCS, C, M>>::prove(
+            io_device,
+            trace,
+            circuit_flags,
+            preprocessing.clone(),
+        );
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing.verifier_key(), proof, commitments);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    fn prove_fib_hyperkzg() -> (
+        JoltVerifierKey<Fr, HyperKZG<Bn254>>,
+        RV32IJoltProof<Fr, HyperKZG<Bn254>>,
+        JoltCommitments<HyperKZG<Bn254>>,
+    ) {
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        let (bytecode, memory_init) = program.decode();
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
+        drop(artifact_guard);
+
+        let preprocessing = preprocess(
+            bytecode,
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        )
+        .unwrap();
+        let (proof, commitments) = <RV32IJoltVM as Jolt<Fr, HyperKZG<Bn254>, C, M>>::prove(
+            io_device,
+            trace,
+            circuit_flags,
+            preprocessing.clone(),
+        );
+        (preprocessing.verifier_key(), proof, commitments)
+    }
+
+    #[test]
+    #[ignore = "requires solc and go-ethereum's evm"]
+    fn fib_e2e_evm() {
+        let (verifier_key, proof, commitments) = prove_fib_hyperkzg();
+        let verification_result =
+            evm::verify_jolt_proof::<C, M, RV32IJoltVM>(verifier_key, proof, commitments);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    #[ignore = "requires solc and go-ethereum's evm"]
+    fn fib_e2e_evm_rejects_wrong_r1cs_evals() {
+        let (verifier_key, mut proof, commitments) = prove_fib_hyperkzg();
+        proof.r1cs.proof.claimed_witness_evals[0] += Fr::one();
+        assert!(matches!(
+            evm::verify_jolt_proof::<C, M, RV32IJoltVM>(verifier_key, proof, commitments),
+            Err(EvmError::Reverted(_))
+        ));
+    }
+
+    type SegmentProofs =
+        Vec<SegmentProof<C, M, Fr, HyraxScheme<G1Projective>, RV32I, RV32ISubtables<Fr>>>;
+
+    fn prove_fib_segments() -> (
+        JoltVerifierKey<Fr, HyraxScheme<G1Projective>>,
+        SegmentProofs,
+    ) {
+        type PCS = HyraxScheme<G1Projective>;
+        let artifact_guard = FIB_FILE_LOCK.lock().unwrap();
+        let mut program = host::Program::new("fibonacci-guest");
+        program.set_input(&9u32);
+        program.set_memory_size(1 << 16);
+        let (bytecode, memory_init) = program.decode();
+        let memory_layout = program.memory_layout();
+        let segments: Vec<_> = program
+            .trace_segments::<_, Fr>(1 << 6)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        drop(artifact_guard);
+        assert!(segments.len() > 2);
+
+        let preprocessing = preprocess(
+            bytecode,
+            memory_init,
+            memory_layout,
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        )
+        .unwrap();
+        let proofs = segments
+            .into_iter()
+            .map(|segment| {
+                <RV32IJoltVM as Jolt<Fr, PCS, C, M>>::prove_segment(segment, preprocessing.clone())
+            })
+            .collect();
+        (preprocessing.verifier_key(), proofs)
+    }
+
+    #[test]
+    fn fib_segments_e2e_hyrax() {
+        let (verifier_key, proofs) = prove_fib_segments();
+        let verification_result = RV32IJoltVM::verify_segments(verifier_key, proofs);
+        assert!(
+            verification_result.is_ok(),
+            "Verification failed with error: {:?}",
+            verification_result.err()
+        );
+    }
+
+    #[test]
+    fn fib_segments_reordered() {
+        let (verifier_key, mut proofs) = prove_fib_segments();
+        proofs.swap(1, 2);
+        assert!(matches!(
+            RV32IJoltVM::verify_segments(verifier_key, proofs),
+            Err(ProofVerifyError::InitialMemoryMismatch)
+        ));
+    }
+
+    #[test]
+    fn fib_segments_dropped() {
+        let (verifier_key, mut proofs) = prove_fib_segments();
+        proofs.remove(1);
+        assert!(matches!(
+            RV32IJoltVM::verify_segments(verifier_key, proofs),
+            Err(ProofVerifyError::InitialMemoryMismatch)
+        ));
+    }
+
+    #[test]
+    fn fib_segments_not_terminated() {
+        let (verifier_key, mut proofs) = prove_fib_segments();
+        proofs.pop();
+        assert!(matches!(
+            RV32IJoltVM::verify_segments(verifier_key, proofs),
+            Err(ProofVerifyError::ProgramNotTerminated)
+        ));
+    }
+
+    #[test]
+    fn fib_prefix_not_terminated() {
+        // The first segment proves a prefix of the execution, which never reaches the
+        // termination address.
+        let (verifier_key, mut proofs) = prove_fib_segments();
+        let prefix = proofs.remove(0);
+        assert!(!prefix.proof.program_io.termination);
+        assert!(matches!(
+            RV32IJoltVM::verify(verifier_key, prefix.proof, prefix.commitments),
+            Err(ProofVerifyError::ProgramNotTerminated)
+        ));
+    }
+
+    #[test]
+    fn fib_segments_wrong_initial_memory() {
+        let (verifier_key, mut proofs) = prove_fib_segments();
+        // Start the last segment from the memory state the first one ended with
+        let v_final_commitment = proofs[0]
+            .commitments
+            .read_write_memory
+            .v_final_commitment
+            .clone();
+        proofs
+            .last_mut()
+            .unwrap()
+            .commitments
+            .read_write_memory
+            .v_init_commitment = Some(v_final_commitment);
+        assert!(matches!(
+            RV32IJoltVM::verify_segments(verifier_key, proofs),
+            Err(ProofVerifyError::InitialMemoryMismatch)
+        ));
+    }
+
     // TODO(sragss): Finish Binius.
     // #[test]
     // fn fib_e2e_binius() {
@@ -327,10 +721,17 @@ mod tests {
         let mut program = host::Program::new("sha3-guest");
         program.set_input(&[5u8; 32]);
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
-
-        let preprocessing =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 20);
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
+
+        let preprocessing = preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        )
+        .unwrap();
         let (jolt_proof, jolt_commitments) =
             <RV32IJoltVM as Jolt<_, HyraxScheme<G1Projective>, C, M>>::prove(
                 io_device,
@@ -339,7 +740,8 @@ mod tests {
                 preprocessing.clone(),
             );
 
-        let verification_result = RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments);
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing.verifier_key(), jolt_proof, jolt_commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
@@ -354,10 +756,17 @@ mod tests {
         let mut program = host::Program::new("sha3-guest");
         program.set_input(&[5u8; 32]);
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
-
-        let preprocessing =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 20);
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
+
+        let preprocessing = preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        )
+        .unwrap();
         let (jolt_proof, jolt_commitments) =
             <RV32IJoltVM as Jolt<_, Zeromorph<Bn254>, C, M>>::prove(
                 io_device,
@@ -366,7 +775,8 @@ mod tests {
                 preprocessing.clone(),
             );
 
-        let verification_result = RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments);
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing.verifier_key(), jolt_proof, jolt_commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
@@ -381,10 +791,17 @@ mod tests {
         let mut program = host::Program::new("sha3-guest");
         program.set_input(&[5u8; 32]);
         let (bytecode, memory_init) = program.decode();
-        let (io_device, trace, circuit_flags) = program.trace();
-
-        let preprocessing =
-            RV32IJoltVM::preprocess(bytecode.clone(), memory_init, 1 << 20, 1 << 20, 1 << 20);
+        let (io_device, trace, circuit_flags) = program.trace().unwrap();
+
+        let preprocessing = preprocess(
+            bytecode.clone(),
+            memory_init,
+            io_device.memory_layout.clone(),
+            1 << 20,
+            1 << 20,
+            1 << 20,
+        )
+        .unwrap();
         let (jolt_proof, jolt_commitments) = <RV32IJoltVM as Jolt<_, HyperKZG<Bn254>, C, M>>::prove(
             io_device,
             trace,
@@ -392,7 +809,8 @@ mod tests {
             preprocessing.clone(),
         );
 
-        let verification_result = RV32IJoltVM::verify(preprocessing, jolt_proof, jolt_commitments);
+        let verification_result =
+            RV32IJoltVM::verify(preprocessing.verifier_key(), jolt_proof, jolt_commitments);
         assert!(
             verification_result.is_ok(),
             "Verification failed with error: {:?}",
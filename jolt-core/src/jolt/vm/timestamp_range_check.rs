@@ -245,7 +245,7 @@ where
 
     fn verify_openings(
         &self,
-        _generators: &C::Setup,
+        _generators: &C::VerifierSetup,
         _opening_proof: &Self::Proof,
         _commitment: &RangeCheckCommitment<C>,
         _opening_point: &[F],
@@ -465,7 +465,7 @@ where
 {
     fn verify_memory_checking(
         _: &NoPreprocessing,
-        _: &C::Setup,
+        _: &C::VerifierSetup,
         mut _proof: MemoryCheckingProof<
             F,
             C,
@@ -590,7 +590,7 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> BatchedGrandProduct<F, C> for
         _proof: &BatchedGrandProductProof<C>,
         _claims: &Vec<F>,
         _transcript: &mut ProofTranscript,
-        _setup: Option<&C::Setup>,
+        _setup: Option<&C::VerifierSetup>,
     ) -> (Vec<F>, Vec<F>) {
         unimplemented!("init/final grand products are batched with read/write grand products")
     }
@@ -713,7 +713,7 @@ where
 
     pub fn verify(
         &mut self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         range_check_commitment: &RangeCheckCommitment<C>,
         memory_commitment: &MemoryCommitment<C>,
         transcript: &mut ProofTranscript,
@@ -770,6 +770,7 @@ where
             &r_grand_product,
             &openings,
             &commitments,
+            BatchType::Big,
             transcript,
         )?;
 
@@ -321,7 +321,7 @@ where
     /// Verifies a memory checking proof, given its associated polynomial `commitment`.
     fn verify_memory_checking(
         preprocessing: &Self::Preprocessing,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         mut proof: MemoryCheckingProof<
             F,
             C,
@@ -108,7 +108,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &SurgeCommitment<PCS>,
         opening_point: &[F],
@@ -120,6 +120,7 @@ where
             opening_point,
             self,
             &commitment.E_commitment.iter().collect::<Vec<_>>(),
+            BatchType::SurgeReadWrite,
             transcript,
         )
     }
@@ -186,7 +187,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &SurgeCommitment<PCS>,
         opening_point: &[F],
@@ -208,6 +209,7 @@ where
                 .iter()
                 .chain(commitment.E_commitment.iter())
                 .collect::<Vec<_>>(),
+            BatchType::SurgeReadWrite,
             transcript,
         )
     }
@@ -283,7 +285,7 @@ where
 
     fn verify_openings(
         &self,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &SurgeCommitment<PCS>,
         opening_point: &[F],
@@ -295,6 +297,7 @@ where
             opening_point,
             &self.final_openings,
             &commitment.final_commitment.iter().collect::<Vec<_>>(),
+            BatchType::SurgeInitFinal,
             transcript,
         )
     }
@@ -631,7 +634,7 @@ where
 
     pub fn verify(
         preprocessing: &SurgePreprocessing<F, Instruction, C, M>,
-        generators: &PCS::Setup,
+        generators: &PCS::VerifierSetup,
         proof: SurgeProof<F, PCS, Instruction, C, M>,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
@@ -785,7 +788,9 @@ mod tests {
     use crate::{
         jolt::instruction::xor::XORInstruction,
         lasso::surge::SurgeProof,
-        poly::{commitment::hyrax::HyraxScheme, commitment::pedersen::PedersenGenerators},
+        poly::commitment::{
+            commitment_scheme::CommitmentScheme, hyrax::HyraxScheme, pedersen::PedersenGenerators,
+        },
         utils::transcript::ProofTranscript,
     };
     use ark_bn254::{Fr, G1Projective};
@@ -815,7 +820,8 @@ mod tests {
         );
 
         let mut transcript = ProofTranscript::new(b"test_transcript");
-        SurgeProof::verify(&preprocessing, &generators, proof, &mut transcript)
+        let verifier_setup = HyraxScheme::verifier_setup(&generators);
+        SurgeProof::verify(&preprocessing, &verifier_setup, proof, &mut transcript)
             .expect("should work");
     }
 
@@ -845,7 +851,8 @@ mod tests {
         );
 
         let mut transcript = ProofTranscript::new(b"test_transcript");
-        SurgeProof::verify(&preprocessing, &generators, proof, &mut transcript)
+        let verifier_setup = HyraxScheme::verifier_setup(&generators);
+        SurgeProof::verify(&preprocessing, &verifier_setup, proof, &mut transcript)
             .expect("should work");
     }
 }
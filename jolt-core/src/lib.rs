@@ -22,5 +22,6 @@ pub mod lasso;
 pub mod msm;
 pub mod poly;
 pub mod r1cs;
+pub mod solidity;
 pub mod subprotocols;
 pub mod utils;
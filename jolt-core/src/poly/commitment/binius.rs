@@ -1,99 +1,831 @@
-#![allow(dead_code)]
-
+use crate::field::JoltField;
 use crate::poly::commitment::commitment_scheme::BatchType;
 use crate::poly::commitment::commitment_scheme::CommitShape;
 use crate::poly::commitment::commitment_scheme::CommitmentScheme;
+use crate::poly::commitment::hyrax::{batch_type_to_ratio, matrix_dimensions};
+use crate::poly::commitment::kzg::SRSError;
 use crate::poly::dense_mlpoly::DensePolynomial;
+use crate::poly::eq_poly::EqPolynomial;
 use crate::utils::errors::ProofVerifyError;
+use crate::utils::math::Math;
 use crate::utils::transcript::{AppendToTranscript, ProofTranscript};
+use crate::utils::{compute_dotproduct, mul_0_1_optimized};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::One;
+use rayon::prelude::*;
+use sha3::{Digest, Keccak256};
+
+/// Base-2 logarithm of the inverse Reed-Solomon code rate.
+const LOG_INV_RATE: usize = 2;
+/// Target soundness of the proximity test, in bits.
+const SECURITY_BITS: usize = 100;
 
+/// Tensor-code polynomial commitment over the 128-bit binary tower field, following the
+/// construction of Diamond and Posen, "Succinct Arguments over Towers of Binary Fields".
+///
+/// The evaluations of a multilinear polynomial are arranged into a matrix (using the same
+/// dimensions as Hyrax), each row is Reed-Solomon encoded with an additive NTT, and the
+/// columns of the encoded matrix are committed to in a Merkle tree. An opening consists of
+/// the tensor-combined rows for the evaluation point and for a random proximity point,
+/// together with Merkle-authenticated columns at positions sampled from the transcript.
 #[derive(Clone)]
 pub struct Binius128Scheme {}
 
-#[derive(CanonicalSerialize, CanonicalDeserialize)]
-pub struct BiniusCommitment {}
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BiniusParams {
+    pub log_inv_rate: usize,
+    pub num_queries: usize,
+}
+
+impl BiniusParams {
+    pub fn new(log_inv_rate: usize, security_bits: usize) -> Self {
+        assert!(log_inv_rate > 0);
+        // Each query catches a codeword that is far from the code with probability at least
+        // d/3, where d = 1 - 2^{-log_inv_rate} is the relative distance of the code.
+        let distance = 1.0 - 1.0 / (1u64 << log_inv_rate) as f64;
+        let bits_per_query = -(1.0 - distance / 3.0).log2();
+        let num_queries = (security_bits as f64 / bits_per_query).ceil() as usize;
+        Self {
+            log_inv_rate,
+            num_queries,
+        }
+    }
+}
+
+impl Default for BiniusParams {
+    fn default() -> Self {
+        Self::new(LOG_INV_RATE, SECURITY_BITS)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BiniusCommitment {
+    /// Merkle root over the columns of the encoded matrix.
+    pub root: [u8; 32],
+}
 
 impl AppendToTranscript for BiniusCommitment {
-    fn append_to_transcript(&self, _transcript: &mut ProofTranscript) {
-        todo!()
+    fn append_to_transcript(&self, transcript: &mut ProofTranscript) {
+        transcript.append_message(b"poly_commitment_begin");
+        transcript.append_bytes(&self.root);
+        transcript.append_message(b"poly_commitment_end");
     }
 }
 
-#[derive(CanonicalSerialize, CanonicalDeserialize)]
-pub struct BiniusProof {}
+/// Opening of a single column of each committed matrix at one queried position.
+#[derive(Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ColumnOpening<F: JoltField> {
+    pub columns: Vec<Vec<F>>,
+    pub paths: Vec<Vec<[u8; 32]>>,
+}
 
-#[derive(CanonicalSerialize, CanonicalDeserialize)]
-pub struct BiniusBatchedProof {}
+#[derive(Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BiniusProof<F: JoltField> {
+    /// The matrix rows combined by the row half of the opening point's eq-polynomial.
+    pub eval_row: Vec<F>,
+    /// The matrix rows combined by the eq-polynomial of a random point.
+    pub proximity_row: Vec<F>,
+    pub queries: Vec<ColumnOpening<F>>,
+}
 
-#[derive(Clone)]
-pub struct None {}
+type Field128 = crate::field::binius::BiniusField<binius_field::BinaryField128bPolyval>;
 
 impl CommitmentScheme for Binius128Scheme {
-    type Field = crate::field::binius::BiniusField<binius_field::BinaryField128bPolyval>;
-    type Setup = None;
+    type Field = Field128;
+    type Setup = BiniusParams;
+    type VerifierSetup = BiniusParams;
     type Commitment = BiniusCommitment;
-    type Proof = BiniusProof;
-    type BatchedProof = BiniusBatchedProof;
+    type Proof = BiniusProof<Self::Field>;
+    type BatchedProof = BiniusProof<Self::Field>;
 
-    fn setup(_shapes: &[CommitShape]) -> Self::Setup {
-        None {}
+    fn setup(_shapes: &[CommitShape]) -> Result<Self::Setup, SRSError> {
+        Ok(BiniusParams::default())
     }
-    fn commit(_poly: &DensePolynomial<Self::Field>, _setup: &Self::Setup) -> Self::Commitment {
-        todo!()
+    fn setup_size(_shapes: &[CommitShape]) -> usize {
+        0
     }
+    fn trim_setup(setup: &Self::Setup, _size: usize) -> Option<Self::Setup> {
+        Some(setup.clone())
+    }
+    fn verifier_setup(setup: &Self::Setup) -> Self::VerifierSetup {
+        setup.clone()
+    }
+    fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment {
+        Self::commit_slice(poly.evals_ref(), setup)
+    }
+    #[tracing::instrument(skip_all, name = "Binius128Scheme::batch_commit")]
     fn batch_commit(
-        _evals: &[&[Self::Field]],
-        _gens: &Self::Setup,
-        _batch_type: BatchType,
+        evals: &[&[Self::Field]],
+        setup: &Self::Setup,
+        batch_type: BatchType,
     ) -> Vec<Self::Commitment> {
-        todo!()
+        let ratio = batch_type_to_ratio(&batch_type);
+        evals
+            .iter()
+            .map(|evals| BiniusCommitment {
+                root: EncodedMatrix::new(evals, ratio, setup).tree.root(),
+            })
+            .collect()
     }
-    fn commit_slice(_evals: &[Self::Field], _setup: &Self::Setup) -> Self::Commitment {
-        todo!()
+    #[tracing::instrument(skip_all, name = "Binius128Scheme::commit_slice")]
+    fn commit_slice(evals: &[Self::Field], setup: &Self::Setup) -> Self::Commitment {
+        BiniusCommitment {
+            root: EncodedMatrix::new(evals, 1, setup).tree.root(),
+        }
     }
     fn prove(
-        _none: &Self::Setup,
-        _poly: &DensePolynomial<Self::Field>,
-        _opening_point: &[Self::Field],
-        _transcript: &mut ProofTranscript,
+        setup: &Self::Setup,
+        poly: &DensePolynomial<Self::Field>,
+        opening_point: &[Self::Field],
+        transcript: &mut ProofTranscript,
     ) -> Self::Proof {
-        todo!()
+        transcript.append_protocol_name(Self::protocol_name());
+        // Implicitly prove is "prove_single", with a ratio = 1
+        BiniusProof::prove(
+            setup,
+            &[poly.evals_ref()],
+            poly.evals_ref(),
+            opening_point,
+            1,
+            transcript,
+        )
     }
+    #[tracing::instrument(skip_all, name = "Binius128Scheme::batch_prove")]
     fn batch_prove(
-        _none: &Self::Setup,
-        _polynomials: &[&DensePolynomial<Self::Field>],
-        _opening_point: &[Self::Field],
-        _openings: &[Self::Field],
-        _batch_type: BatchType,
-        _transcript: &mut ProofTranscript,
+        setup: &Self::Setup,
+        polynomials: &[&DensePolynomial<Self::Field>],
+        opening_point: &[Self::Field],
+        openings: &[Self::Field],
+        batch_type: BatchType,
+        transcript: &mut ProofTranscript,
     ) -> Self::BatchedProof {
-        todo!()
+        transcript.append_protocol_name(Self::protocol_name());
+
+        // append the claimed evaluations to transcript
+        transcript.append_scalars(openings);
+
+        let rlc_coefficients: Vec<Self::Field> = transcript.challenge_vector(polynomials.len());
+
+        let poly_len = polynomials[0].len();
+        let rlc_poly: Vec<Self::Field> = (0..poly_len)
+            .into_par_iter()
+            .map(|i| {
+                rlc_coefficients
+                    .iter()
+                    .zip(polynomials.iter())
+                    .map(|(coeff, poly)| mul_0_1_optimized(&poly.evals_ref()[i], coeff))
+                    .sum()
+            })
+            .collect();
+
+        let evals: Vec<&[Self::Field]> = polynomials.iter().map(|poly| poly.evals_ref()).collect();
+        BiniusProof::prove(
+            setup,
+            &evals,
+            &rlc_poly,
+            opening_point,
+            batch_type_to_ratio(&batch_type),
+            transcript,
+        )
     }
 
     fn verify(
-        _proof: &Self::Proof,
-        _setup: &Self::Setup,
-        _transcript: &mut ProofTranscript,
-        _opening_point: &[Self::Field],
-        _opening: &Self::Field,
-        _commitment: &Self::Commitment,
+        proof: &Self::Proof,
+        setup: &Self::VerifierSetup,
+        transcript: &mut ProofTranscript,
+        opening_point: &[Self::Field],
+        opening: &Self::Field,
+        commitment: &Self::Commitment,
     ) -> Result<(), ProofVerifyError> {
-        todo!()
+        transcript.append_protocol_name(Self::protocol_name());
+        proof.verify(
+            setup,
+            opening_point,
+            opening,
+            &[Self::Field::one()],
+            &[commitment],
+            1,
+            transcript,
+        )
     }
 
+    #[tracing::instrument(skip_all, name = "Binius128Scheme::batch_verify")]
     fn batch_verify(
-        _batch_proof: &Self::BatchedProof,
-        _setup: &Self::Setup,
-        _opening_point: &[Self::Field],
-        _openings: &[Self::Field],
-        _commitments: &[&Self::Commitment],
-        _transcript: &mut ProofTranscript,
+        batch_proof: &Self::BatchedProof,
+        setup: &Self::VerifierSetup,
+        opening_point: &[Self::Field],
+        openings: &[Self::Field],
+        commitments: &[&Self::Commitment],
+        batch_type: BatchType,
+        transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
-        todo!()
+        if openings.len() != commitments.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                commitments.len(),
+                openings.len(),
+            ));
+        }
+        transcript.append_protocol_name(Self::protocol_name());
+
+        // append the claimed evaluations to transcript
+        transcript.append_scalars(openings);
+
+        let rlc_coefficients: Vec<Self::Field> = transcript.challenge_vector(openings.len());
+        let rlc_eval = compute_dotproduct(&rlc_coefficients, openings);
+
+        batch_proof.verify(
+            setup,
+            opening_point,
+            &rlc_eval,
+            &rlc_coefficients,
+            commitments,
+            batch_type_to_ratio(&batch_type),
+            transcript,
+        )
     }
 
     fn protocol_name() -> &'static [u8] {
-        b"binius_commit"
+        b"Jolt Binius128 opening proof"
+    }
+}
+
+impl<F: JoltField> BiniusProof<F> {
+    /// Opens the random linear combination `combined` of the committed `polynomials` at
+    /// `opening_point`. The encoded matrices are recomputed from the polynomials, one at a
+    /// time, to extract the queried columns.
+    #[tracing::instrument(skip_all, name = "BiniusProof::prove")]
+    fn prove(
+        params: &BiniusParams,
+        polynomials: &[&[F]],
+        combined: &[F],
+        opening_point: &[F],
+        ratio: usize,
+        transcript: &mut ProofTranscript,
+    ) -> Self {
+        let num_vars = combined.len().log_2();
+        assert_eq!(num_vars, opening_point.len());
+        let (L_size, R_size) = matrix_dimensions(num_vars, ratio);
+
+        let (L, _R) = EqPolynomial::new(opening_point.to_vec()).compute_factored_evals(L_size);
+        let eval_row = vector_matrix_product(combined, &L, R_size);
+        transcript.append_scalars(&eval_row);
+
+        let r_proximity: Vec<F> = transcript.challenge_vector(L_size.log_2());
+        let proximity_row =
+            vector_matrix_product(combined, &EqPolynomial::evals(&r_proximity), R_size);
+        transcript.append_scalars(&proximity_row);
+
+        let codeword_len = R_size << params.log_inv_rate;
+        let indices = challenge_indices::<F>(transcript, params.num_queries, codeword_len);
+
+        let mut queries: Vec<ColumnOpening<F>> = indices
+            .iter()
+            .map(|_| ColumnOpening {
+                columns: Vec::with_capacity(polynomials.len()),
+                paths: Vec::with_capacity(polynomials.len()),
+            })
+            .collect();
+        for poly in polynomials {
+            let encoded = EncodedMatrix::new(poly, ratio, params);
+            for (query, &index) in queries.iter_mut().zip(indices.iter()) {
+                query.columns.push(encoded.column(index));
+                query.paths.push(encoded.tree.path(index));
+            }
+        }
+
+        Self {
+            eval_row,
+            proximity_row,
+            queries,
+        }
+    }
+
+    /// Verifies an opening of the linear combination of `commitments` by `coefficients` to
+    /// `opening` at `opening_point`. The shape of the committed matrices is determined by the
+    /// number of variables and the batch `ratio`, as when committing, not by the proof.
+    #[allow(clippy::too_many_arguments)]
+    fn verify(
+        &self,
+        params: &BiniusParams,
+        opening_point: &[F],
+        opening: &F,
+        coefficients: &[F],
+        commitments: &[&BiniusCommitment],
+        ratio: usize,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        let num_vars = opening_point.len();
+        let (L_size, R_size) = matrix_dimensions(num_vars, ratio);
+        if self.eval_row.len() != R_size {
+            return Err(ProofVerifyError::InvalidInputLength(
+                R_size,
+                self.eval_row.len(),
+            ));
+        }
+        if self.proximity_row.len() != R_size {
+            return Err(ProofVerifyError::InvalidInputLength(
+                R_size,
+                self.proximity_row.len(),
+            ));
+        }
+
+        let (L, R) = EqPolynomial::new(opening_point.to_vec()).compute_factored_evals(L_size);
+        if compute_dotproduct(&self.eval_row, &R) != *opening {
+            return Err(ProofVerifyError::InternalError);
+        }
+        transcript.append_scalars(&self.eval_row);
+
+        let r_proximity: Vec<F> = transcript.challenge_vector(L_size.log_2());
+        let L_proximity = EqPolynomial::evals(&r_proximity);
+        transcript.append_scalars(&self.proximity_row);
+
+        let codeword_len = R_size << params.log_inv_rate;
+        let indices = challenge_indices::<F>(transcript, params.num_queries, codeword_len);
+        if self.queries.len() != indices.len() {
+            return Err(ProofVerifyError::InvalidInputLength(
+                indices.len(),
+                self.queries.len(),
+            ));
+        }
+
+        let ntt = AdditiveNtt::new(codeword_len.log_2());
+        let encoded_eval_row = ntt.encode(&self.eval_row);
+        let encoded_proximity_row = ntt.encode(&self.proximity_row);
+
+        for (query, &index) in self.queries.iter().zip(indices.iter()) {
+            if query.columns.len() != commitments.len() || query.paths.len() != commitments.len() {
+                return Err(ProofVerifyError::InvalidInputLength(
+                    commitments.len(),
+                    query.columns.len(),
+                ));
+            }
+            let mut column = vec![F::zero(); L_size];
+            for (((opened, path), commitment), coeff) in query
+                .columns
+                .iter()
+                .zip(query.paths.iter())
+                .zip(commitments.iter())
+                .zip(coefficients.iter())
+            {
+                if opened.len() != L_size
+                    || path.len() != codeword_len.log_2()
+                    || !MerkleTree::verify_path(&commitment.root, hash_column(opened), index, path)
+                {
+                    return Err(ProofVerifyError::InternalError);
+                }
+                column
+                    .iter_mut()
+                    .zip(opened.iter())
+                    .for_each(|(acc, value)| *acc += *coeff * *value);
+            }
+
+            if compute_dotproduct(&L, &column) != encoded_eval_row[index]
+                || compute_dotproduct(&L_proximity, &column) != encoded_proximity_row[index]
+            {
+                return Err(ProofVerifyError::InternalError);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn vector_matrix_product<F: JoltField>(evals: &[F], L: &[F], R_size: usize) -> Vec<F> {
+    evals
+        .par_chunks(R_size)
+        .zip(L.par_iter())
+        .map(|(row, l)| {
+            row.iter()
+                .map(|x| mul_0_1_optimized(l, x))
+                .collect::<Vec<F>>()
+        })
+        .reduce(
+            || vec![F::zero(); R_size],
+            |mut acc: Vec<F>, row| {
+                acc.iter_mut().zip(row).for_each(|(x, y)| *x += y);
+                acc
+            },
+        )
+}
+
+/// Samples `count` positions in a codeword of length `codeword_len` (a power of two).
+fn challenge_indices<F: JoltField>(
+    transcript: &mut ProofTranscript,
+    count: usize,
+    codeword_len: usize,
+) -> Vec<usize> {
+    (0..count)
+        .map(|_| {
+            let challenge: F = transcript.challenge_scalar();
+            let mut bytes = vec![];
+            challenge.serialize_compressed(&mut bytes).unwrap();
+            let value = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+            value as usize & (codeword_len - 1)
+        })
+        .collect()
+}
+
+/// A polynomial's evaluations arranged as a matrix whose rows have been Reed-Solomon
+/// encoded, along with the Merkle tree over its columns.
+struct EncodedMatrix<F: JoltField> {
+    rows: Vec<Vec<F>>,
+    tree: MerkleTree,
+}
+
+impl<F: JoltField> EncodedMatrix<F> {
+    #[tracing::instrument(skip_all, name = "EncodedMatrix::new")]
+    fn new(evals: &[F], ratio: usize, params: &BiniusParams) -> Self {
+        let num_vars = evals.len().log_2();
+        let (L_size, R_size) = matrix_dimensions(num_vars, ratio);
+        assert_eq!(L_size * R_size, evals.len());
+
+        let ntt = AdditiveNtt::new(R_size.log_2() + params.log_inv_rate);
+        let rows: Vec<Vec<F>> = evals
+            .par_chunks(R_size)
+            .map(|row| ntt.encode(row))
+            .collect();
+
+        let codeword_len = R_size << params.log_inv_rate;
+        let leaves: Vec<[u8; 32]> = (0..codeword_len)
+            .into_par_iter()
+            .map(|index| hash_column(&rows.iter().map(|row| row[index]).collect::<Vec<F>>()))
+            .collect();
+
+        Self {
+            rows,
+            tree: MerkleTree::new(leaves),
+        }
+    }
+
+    fn column(&self, index: usize) -> Vec<F> {
+        self.rows.iter().map(|row| row[index]).collect()
+    }
+}
+
+fn hash_column<F: JoltField>(column: &[F]) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(column.len() * F::NUM_BYTES);
+    for value in column {
+        value.serialize_compressed(&mut bytes).unwrap();
+    }
+    Keccak256::digest(&bytes).into()
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Keccak256::new()
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// Binary Merkle tree over a power-of-two number of leaves.
+struct MerkleTree {
+    /// `layers[0]` holds the leaves and the last layer holds the root.
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    fn new(leaves: Vec<[u8; 32]>) -> Self {
+        assert!(leaves.len().is_power_of_two());
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let layer = layers
+                .last()
+                .unwrap()
+                .par_chunks(2)
+                .map(|pair| hash_pair(&pair[0], &pair[1]))
+                .collect();
+            layers.push(layer);
+        }
+        Self { layers }
+    }
+
+    fn root(&self) -> [u8; 32] {
+        self.layers.last().unwrap()[0]
+    }
+
+    /// Sibling hashes from the leaf at `index` up to (but excluding) the root.
+    fn path(&self, mut index: usize) -> Vec<[u8; 32]> {
+        let mut path = Vec::with_capacity(self.layers.len() - 1);
+        for layer in &self.layers[..self.layers.len() - 1] {
+            path.push(layer[index ^ 1]);
+            index >>= 1;
+        }
+        path
+    }
+
+    fn verify_path(root: &[u8; 32], leaf: [u8; 32], mut index: usize, path: &[[u8; 32]]) -> bool {
+        let mut node = leaf;
+        for sibling in path {
+            node = if index & 1 == 0 {
+                hash_pair(&node, sibling)
+            } else {
+                hash_pair(sibling, &node)
+            };
+            index >>= 1;
+        }
+        index == 0 && node == *root
+    }
+}
+
+/// Additive NTT of Lin, Chung and Han ("Novel Polynomial Basis and Its Application to
+/// Reed-Solomon Erasure Codes") over the subspace spanned by `beta_i = from_u64(1 << i)`.
+///
+/// A message of length `2^k` is read as coefficients in the novel polynomial basis and
+/// encoded as the evaluations of that polynomial over the whole subspace. This relies on
+/// `from_u64` embedding integers GF(2)-linearly, which holds for binary fields only.
+struct AdditiveNtt<F: JoltField> {
+    log_domain: usize,
+    /// `twiddles[i][j]` is the normalized subspace polynomial `W_i` evaluated at the offset
+    /// of the `j`-th coset of the `i`-th subspace.
+    twiddles: Vec<Vec<F>>,
+}
+
+impl<F: JoltField> AdditiveNtt<F> {
+    fn new(log_domain: usize) -> Self {
+        assert!(log_domain < 64, "NTT domain too large");
+        let basis: Vec<F> = (0..log_domain)
+            .map(|i| F::from_u64(1 << i).unwrap())
+            .collect();
+
+        // W_{i+1}(x) = W_i(x) * (W_i(x) + W_i(beta_i)), starting from W_0(x) = x.
+        let mut subspace_evals = basis;
+        let mut twiddles = Vec::with_capacity(log_domain);
+        for i in 0..log_domain {
+            let normalization = subspace_evals[i].inverse().unwrap();
+            let mut level = vec![F::zero()];
+            for b in (i + 1)..log_domain {
+                let shift = subspace_evals[b] * normalization;
+                let shifted: Vec<F> = level.iter().map(|t| *t + shift).collect();
+                level.extend(shifted);
+            }
+            twiddles.push(level);
+
+            let w_i = subspace_evals[i];
+            subspace_evals
+                .iter_mut()
+                .for_each(|value| *value = *value * (*value + w_i));
+        }
+
+        Self {
+            log_domain,
+            twiddles,
+        }
+    }
+
+    /// Evaluates the polynomial with novel-basis coefficients `message` over the domain.
+    fn encode(&self, message: &[F]) -> Vec<F> {
+        assert!(message.len() <= 1 << self.log_domain);
+        let mut data = message.to_vec();
+        data.resize(1 << self.log_domain, F::zero());
+
+        for i in (0..self.log_domain).rev() {
+            let half = 1 << i;
+            for (block, twiddle) in data.chunks_mut(2 * half).zip(self.twiddles[i].iter()) {
+                let (lo, hi) = block.split_at_mut(half);
+                for (u, v) in lo.iter_mut().zip(hi.iter_mut()) {
+                    *u += *v * *twiddle;
+                    *v += *u;
+                }
+            }
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+
+    type F = Field128;
+
+    #[test]
+    fn additive_ntt_evaluates_novel_basis() {
+        let log_domain = 5;
+        let mut rng = test_rng();
+        let message: Vec<F> = (0..8).map(|_| F::random(&mut rng)).collect();
+        let encoded = AdditiveNtt::new(log_domain).encode(&message);
+
+        let basis: Vec<F> = (0..log_domain)
+            .map(|i| F::from_u64(1 << i).unwrap())
+            .collect();
+        // Evaluates the normalized subspace polynomials directly at x.
+        let normalized_subspace_evals = |x: F| -> Vec<F> {
+            let mut w_x = x;
+            let mut w_basis = basis.clone();
+            let mut evals = vec![];
+            for i in 0..log_domain {
+                evals.push(w_x / w_basis[i]);
+                let w_i = w_basis[i];
+                w_x = w_x * (w_x + w_i);
+                w_basis.iter_mut().for_each(|w| *w = *w * (*w + w_i));
+            }
+            evals
+        };
+
+        for (index, value) in encoded.iter().enumerate() {
+            let x = F::from_u64(index as u64).unwrap();
+            let w = normalized_subspace_evals(x);
+            let expected: F = message
+                .iter()
+                .enumerate()
+                .map(|(k, coeff)| {
+                    (0..log_domain)
+                        .filter(|i| (k >> i) & 1 == 1)
+                        .fold(*coeff, |acc, i| acc * w[i])
+                })
+                .sum();
+            assert_eq!(*value, expected, "mismatch at index {index}");
+        }
+    }
+
+    fn random_poly<R: rand_core::RngCore>(num_vars: usize, rng: &mut R) -> DensePolynomial<F> {
+        DensePolynomial::new((0..num_vars.pow2()).map(|_| F::random(rng)).collect())
+    }
+
+    #[test]
+    fn prove_verify() {
+        let setup = Binius128Scheme::setup(&[]).unwrap();
+        let mut rng = test_rng();
+        for num_vars in [1, 4, 9] {
+            let poly = random_poly(num_vars, &mut rng);
+            let point: Vec<F> = (0..num_vars).map(|_| F::random(&mut rng)).collect();
+            let eval = poly.evaluate(&point);
+
+            let commitment = Binius128Scheme::commit(&poly, &setup);
+            let mut prover_transcript = ProofTranscript::new(b"example");
+            let proof = Binius128Scheme::prove(&setup, &poly, &point, &mut prover_transcript);
+
+            let mut verifier_transcript = ProofTranscript::new(b"example");
+            assert!(Binius128Scheme::verify(
+                &proof,
+                &setup,
+                &mut verifier_transcript,
+                &point,
+                &eval,
+                &commitment
+            )
+            .is_ok());
+
+            let mut verifier_transcript = ProofTranscript::new(b"example");
+            assert!(Binius128Scheme::verify(
+                &proof,
+                &setup,
+                &mut verifier_transcript,
+                &point,
+                &(eval + F::one()),
+                &commitment
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn batch_prove_verify() {
+        let setup = Binius128Scheme::setup(&[]).unwrap();
+        let num_vars = 10;
+        let mut rng = test_rng();
+        let polys: Vec<DensePolynomial<F>> =
+            (0..3).map(|_| random_poly(num_vars, &mut rng)).collect();
+        let poly_refs: Vec<&DensePolynomial<F>> = polys.iter().collect();
+        let point: Vec<F> = (0..num_vars).map(|_| F::random(&mut rng)).collect();
+        let evals: Vec<F> = polys.iter().map(|poly| poly.evaluate(&point)).collect();
+
+        let commitments = Binius128Scheme::batch_commit_polys(&polys, &setup, BatchType::Big);
+        let commitment_refs: Vec<&BiniusCommitment> = commitments.iter().collect();
+        let mut prover_transcript = ProofTranscript::new(b"example");
+        let proof = Binius128Scheme::batch_prove(
+            &setup,
+            &poly_refs,
+            &point,
+            &evals,
+            BatchType::Big,
+            &mut prover_transcript,
+        );
+
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let proof = BiniusProof::<F>::deserialize_compressed(bytes.as_slice()).unwrap();
+
+        let mut verifier_transcript = ProofTranscript::new(b"example");
+        assert!(Binius128Scheme::batch_verify(
+            &proof,
+            &setup,
+            &point,
+            &evals,
+            &commitment_refs,
+            BatchType::Big,
+            &mut verifier_transcript
+        )
+        .is_ok());
+
+        // A commitment to a different polynomial must not verify.
+        let other = Binius128Scheme::batch_commit_polys(
+            &[random_poly(num_vars, &mut rng)],
+            &setup,
+            BatchType::Big,
+        );
+        let swapped = [commitment_refs[0], &other[0], commitment_refs[2]];
+        let mut verifier_transcript = ProofTranscript::new(b"example");
+        assert!(Binius128Scheme::batch_verify(
+            &proof,
+            &setup,
+            &point,
+            &evals,
+            &swapped,
+            BatchType::Big,
+            &mut verifier_transcript
+        )
+        .is_err());
+
+        // Tampering with an opened column is caught by the Merkle path check.
+        let mut tampered = proof;
+        tampered.queries[0].columns[1][0] += F::one();
+        let mut verifier_transcript = ProofTranscript::new(b"example");
+        assert!(Binius128Scheme::batch_verify(
+            &tampered,
+            &setup,
+            &point,
+            &evals,
+            &commitment_refs,
+            BatchType::Big,
+            &mut verifier_transcript
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn reshaped_proof_is_rejected() {
+        let setup = Binius128Scheme::setup(&[]).unwrap();
+        let num_vars = 10;
+        let mut rng = test_rng();
+        let polys: Vec<DensePolynomial<F>> =
+            (0..2).map(|_| random_poly(num_vars, &mut rng)).collect();
+        let poly_refs: Vec<&DensePolynomial<F>> = polys.iter().collect();
+        let point: Vec<F> = (0..num_vars).map(|_| F::random(&mut rng)).collect();
+        let evals: Vec<F> = polys.iter().map(|poly| poly.evaluate(&point)).collect();
+
+        // Commitments and a proof that are consistent with each other, but for matrices
+        // shaped for small batches
+        let commitments = Binius128Scheme::batch_commit_polys(&polys, &setup, BatchType::Small);
+        let commitment_refs: Vec<&BiniusCommitment> = commitments.iter().collect();
+        let mut prover_transcript = ProofTranscript::new(b"example");
+        let proof = Binius128Scheme::batch_prove(
+            &setup,
+            &poly_refs,
+            &point,
+            &evals,
+            BatchType::Small,
+            &mut prover_transcript,
+        );
+        assert_ne!(
+            matrix_dimensions(num_vars, batch_type_to_ratio(&BatchType::Small)),
+            matrix_dimensions(num_vars, batch_type_to_ratio(&BatchType::Big))
+        );
+
+        let mut verifier_transcript = ProofTranscript::new(b"example");
+        assert!(Binius128Scheme::batch_verify(
+            &proof,
+            &setup,
+            &point,
+            &evals,
+            &commitment_refs,
+            BatchType::Small,
+            &mut verifier_transcript
+        )
+        .is_ok());
+
+        // The verifier derives the shape from the batch type, not from the proof
+        let mut verifier_transcript = ProofTranscript::new(b"example");
+        assert!(matches!(
+            Binius128Scheme::batch_verify(
+                &proof,
+                &setup,
+                &point,
+                &evals,
+                &commitment_refs,
+                BatchType::Big,
+                &mut verifier_transcript
+            ),
+            Err(ProofVerifyError::InvalidInputLength(..))
+        ));
+
+        // Rows of the wrong length are rejected before they are used
+        let mut truncated = proof;
+        truncated.proximity_row.pop();
+        let mut verifier_transcript = ProofTranscript::new(b"example");
+        assert!(matches!(
+            Binius128Scheme::batch_verify(
+                &truncated,
+                &setup,
+                &point,
+                &evals,
+                &commitment_refs,
+                BatchType::Small,
+                &mut verifier_transcript
+            ),
+            Err(ProofVerifyError::InvalidInputLength(..))
+        ));
     }
 }
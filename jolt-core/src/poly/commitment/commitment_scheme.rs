@@ -2,7 +2,7 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 
 use crate::{
     field::JoltField,
-    poly::dense_mlpoly::DensePolynomial,
+    poly::{commitment::kzg::SRSError, dense_mlpoly::DensePolynomial},
     utils::{
         errors::ProofVerifyError,
         transcript::{AppendToTranscript, ProofTranscript},
@@ -34,12 +34,49 @@ pub enum BatchType {
 
 pub trait CommitmentScheme: Clone + Sync + Send + 'static {
     type Field: JoltField + Sized;
-    type Setup: Clone + Sync + Send;
-    type Commitment: Sync + Send + CanonicalSerialize + CanonicalDeserialize + AppendToTranscript;
+    type Setup: Clone + Sync + Send + CanonicalSerialize + CanonicalDeserialize;
+    /// The subset of `Setup` needed to verify openings, e.g. a trimmed SRS.
+    type VerifierSetup: Clone + Sync + Send + CanonicalSerialize + CanonicalDeserialize;
+    type Commitment: Clone
+        + Sync
+        + Send
+        + PartialEq
+        + CanonicalSerialize
+        + CanonicalDeserialize
+        + AppendToTranscript;
     type Proof: Sync + Send + CanonicalSerialize + CanonicalDeserialize;
     type BatchedProof: Sync + Send + CanonicalSerialize + CanonicalDeserialize;
 
-    fn setup(shapes: &[CommitShape]) -> Self::Setup;
+    /// Generates or loads the setup for committing to polynomials of the given shapes. Only
+    /// fails for schemes whose setup comes from a ceremony that can't be loaded.
+    fn setup(shapes: &[CommitShape]) -> Result<Self::Setup, SRSError>;
+    /// An insecure setup for `shapes` whose secrets are derived from a fixed seed, for tests
+    /// of schemes with a trusted setup. `setup` never falls back to it.
+    #[cfg(any(test, feature = "test-srs"))]
+    fn seeded_setup(shapes: &[CommitShape]) -> Self::Setup {
+        Self::setup(shapes).expect("schemes without a trusted setup can't fail to set up")
+    }
+    /// The size of the setup required by `shapes`: setups of equal size are interchangeable,
+    /// and one of a larger size can be cut down to a smaller size with `trim_setup`.
+    fn setup_size(shapes: &[CommitShape]) -> usize {
+        shapes
+            .iter()
+            .map(|shape| shape.input_length)
+            .max()
+            .unwrap_or(0)
+    }
+    /// Identifies what a setup of the given size is derived from, if not only its size, e.g.
+    /// the ceremony file of the KZG-based schemes. Setups with different sources are not
+    /// interchangeable.
+    fn setup_source(_size: usize) -> Result<Option<String>, SRSError> {
+        Ok(None)
+    }
+    /// Derives the setup of the given size from a setup of a larger size, for schemes whose
+    /// setups are nested.
+    fn trim_setup(_setup: &Self::Setup, _size: usize) -> Option<Self::Setup> {
+        None
+    }
+    fn verifier_setup(setup: &Self::Setup) -> Self::VerifierSetup;
     fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment;
     fn batch_commit(
         evals: &[&[Self::Field]],
@@ -80,7 +117,7 @@ pub trait CommitmentScheme: Clone + Sync + Send + 'static {
 
     fn verify(
         proof: &Self::Proof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         transcript: &mut ProofTranscript,
         opening_point: &[Self::Field], // point at which the polynomial is evaluated
         opening: &Self::Field,         // evaluation \widetilde{Z}(r)
@@ -89,10 +126,11 @@ pub trait CommitmentScheme: Clone + Sync + Send + 'static {
 
     fn batch_verify(
         batch_proof: &Self::BatchedProof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         opening_point: &[Self::Field],
         openings: &[Self::Field],
         commitments: &[&Self::Commitment],
+        batch_type: BatchType,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError>;
 
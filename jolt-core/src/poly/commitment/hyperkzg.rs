@@ -9,7 +9,7 @@
 //! and within the KZG commitment scheme implementation itself).
 use super::{
     commitment_scheme::{BatchType, CommitmentScheme},
-    kzg::{KZGProverKey, KZGVerifierKey, UnivariateKZG},
+    kzg::{CeremonySRS, KZGProverKey, KZGVerifierKey, SRSError, UnivariateKZG},
 };
 use crate::field;
 use crate::poly::commitment::commitment_scheme::CommitShape;
@@ -26,8 +26,7 @@ use crate::{
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{One, Zero};
-use rand_chacha::ChaCha20Rng;
-use rand_core::{CryptoRng, RngCore, SeedableRng};
+use rand_core::{CryptoRng, RngCore};
 use rayon::iter::{
     IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
     IntoParallelRefMutIterator, ParallelIterator,
@@ -48,18 +47,18 @@ impl<P: Pairing> HyperKZGSRS<P> {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct HyperKZGProverKey<P: Pairing> {
     pub kzg_pk: KZGProverKey<P>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct HyperKZGVerifierKey<P: Pairing> {
     pub kzg_vk: KZGVerifierKey<P>,
 }
 
-#[derive(Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct HyperKZGCommitment<P: Pairing>(P::G1Affine);
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct HyperKZGCommitment<P: Pairing>(pub(crate) P::G1Affine);
 
 impl<P: Pairing> AppendToTranscript for HyperKZGCommitment<P> {
     fn append_to_transcript(&self, transcript: &mut ProofTranscript) {
@@ -69,9 +68,9 @@ impl<P: Pairing> AppendToTranscript for HyperKZGCommitment<P> {
 
 #[derive(Clone, CanonicalSerialize, CanonicalDeserialize, Debug)]
 pub struct HyperKZGProof<P: Pairing> {
-    com: Vec<P::G1Affine>,
-    w: Vec<P::G1Affine>,
-    v: Vec<Vec<P::ScalarField>>,
+    pub(crate) com: Vec<P::G1Affine>,
+    pub(crate) w: Vec<P::G1Affine>,
+    pub(crate) v: Vec<Vec<P::ScalarField>>,
 }
 
 // On input f(x) and u compute the witness polynomial used to prove
@@ -504,24 +503,41 @@ where
     }
 }
 
-impl<P: Pairing> CommitmentScheme for HyperKZG<P>
+impl<P: CeremonySRS> CommitmentScheme for HyperKZG<P>
 where
     <P as Pairing>::ScalarField: field::JoltField,
 {
     type Field = P::ScalarField;
     type Setup = (HyperKZGProverKey<P>, HyperKZGVerifierKey<P>);
+    type VerifierSetup = HyperKZGVerifierKey<P>;
     type Commitment = HyperKZGCommitment<P>;
     type Proof = HyperKZGProof<P>;
     type BatchedProof = HyperKZGProof<P>;
 
-    fn setup(shapes: &[CommitShape]) -> Self::Setup {
-        let max_len = shapes.iter().map(|shape| shape.input_length).max().unwrap();
+    fn setup(shapes: &[CommitShape]) -> Result<Self::Setup, SRSError> {
+        let max_len = Self::setup_size(shapes);
 
-        HyperKZGSRS(Arc::new(SRS::setup(
-            &mut ChaCha20Rng::from_seed(*b"HyperKZG_POLY_COMMITMENT_SCHEMEE"),
-            max_len,
-        )))
-        .trim(max_len)
+        Ok(HyperKZGSRS(P::ceremony_srs(max_len)?).trim(max_len))
+    }
+
+    #[cfg(any(test, feature = "test-srs"))]
+    fn seeded_setup(shapes: &[CommitShape]) -> Self::Setup {
+        let max_len = Self::setup_size(shapes);
+
+        HyperKZGSRS(Arc::new(SRS::seeded_for_tests(max_len))).trim(max_len)
+    }
+
+    fn setup_source(size: usize) -> Result<Option<String>, SRSError> {
+        P::ceremony_id(size).map(Some)
+    }
+
+    fn trim_setup(setup: &Self::Setup, size: usize) -> Option<Self::Setup> {
+        let srs = setup.0.kzg_pk.srs().truncate(size);
+        Some(HyperKZGSRS(Arc::new(srs)).trim(size))
+    }
+
+    fn verifier_setup(setup: &Self::Setup) -> Self::VerifierSetup {
+        setup.1
     }
 
     fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment {
@@ -588,32 +604,26 @@ where
 
     fn verify(
         proof: &Self::Proof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         transcript: &mut ProofTranscript,
         opening_point: &[Self::Field], // point at which the polynomial is evaluated
         opening: &Self::Field,         // evaluation \widetilde{Z}(r)
         commitment: &Self::Commitment,
     ) -> Result<(), ProofVerifyError> {
-        HyperKZG::<P>::verify(
-            &setup.1,
-            commitment,
-            opening_point,
-            opening,
-            proof,
-            transcript,
-        )
+        HyperKZG::<P>::verify(setup, commitment, opening_point, opening, proof, transcript)
     }
 
     fn batch_verify(
         batch_proof: &Self::BatchedProof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         opening_point: &[Self::Field],
         openings: &[Self::Field],
         commitments: &[&Self::Commitment],
+        _batch_type: BatchType,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
         HyperKZG::<P>::batch_verify(
-            &setup.1,
+            setup,
             commitments,
             opening_point,
             openings,
@@ -685,6 +695,23 @@ mod tests {
         assert!(test_inner(point, eval).is_err());
     }
 
+    #[test]
+    fn test_hyperkzg_setup_serialization() {
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
+        let srs = HyperKZGSRS::setup(&mut rng, 7);
+        let setup: (HyperKZGProverKey<Bn254>, HyperKZGVerifierKey<Bn254>) = srs.trim(7);
+
+        let mut bytes = Vec::new();
+        setup.serialize_compressed(&mut bytes).unwrap();
+        let (pk, vk) =
+            <(HyperKZGProverKey<Bn254>, HyperKZGVerifierKey<Bn254>)>::deserialize_compressed(
+                &bytes[..],
+            )
+            .unwrap();
+        assert_eq!(pk.kzg_pk.g1_powers(), setup.0.kzg_pk.g1_powers());
+        assert_eq!(vk.kzg_vk.beta_g2, setup.1.kzg_vk.beta_g2);
+    }
+
     #[test]
     fn test_hyperkzg_small() {
         let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(0);
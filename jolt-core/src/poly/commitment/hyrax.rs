@@ -1,10 +1,13 @@
 use std::marker::PhantomData;
+use std::ops::Deref;
 
 use super::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
+use super::kzg::SRSError;
 use super::pedersen::{PedersenCommitment, PedersenGenerators};
 use crate::field::JoltField;
 use crate::poly::dense_mlpoly::DensePolynomial;
 use crate::poly::eq_poly::EqPolynomial;
+use crate::subprotocols::dot_product::DotProductProof;
 use crate::utils::errors::ProofVerifyError;
 use crate::utils::math::Math;
 use crate::utils::transcript::{AppendToTranscript, ProofTranscript};
@@ -12,6 +15,7 @@ use crate::utils::{compute_dotproduct, mul_0_1_optimized};
 use ark_ec::CurveGroup;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use num_integer::Roots;
+use rand_core::{CryptoRng, RngCore};
 use rayon::prelude::*;
 use tracing::trace_span;
 
@@ -22,6 +26,56 @@ pub struct HyraxScheme<G: CurveGroup> {
     marker: PhantomData<G>,
 }
 
+const HYRAX_GENERATORS_LABEL: &[u8] = b"Jolt v1 Hyrax generators";
+
+/// The generators the Hyrax verifier needs, which are all of those in the setup. Since they
+/// are derived from a fixed seed, only their number is serialized, and they are derived
+/// again on deserialization. This keeps serialized verifier keys small.
+#[derive(Clone)]
+pub struct HyraxVerifierSetup<G: CurveGroup>(PedersenGenerators<G>);
+
+impl<G: CurveGroup> Deref for HyraxVerifierSetup<G> {
+    type Target = PedersenGenerators<G>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<G: CurveGroup> CanonicalSerialize for HyraxVerifierSetup<G> {
+    fn serialize_with_mode<W: ark_std::io::Write>(
+        &self,
+        writer: W,
+        compress: ark_serialize::Compress,
+    ) -> Result<(), ark_serialize::SerializationError> {
+        (self.0.generators.len() as u64).serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: ark_serialize::Compress) -> usize {
+        0u64.serialized_size(compress)
+    }
+}
+
+impl<G: CurveGroup> CanonicalDeserialize for HyraxVerifierSetup<G> {
+    fn deserialize_with_mode<R: ark_std::io::Read>(
+        reader: R,
+        compress: ark_serialize::Compress,
+        validate: ark_serialize::Validate,
+    ) -> Result<Self, ark_serialize::SerializationError> {
+        let len = u64::deserialize_with_mode(reader, compress, validate)?;
+        Ok(Self(PedersenGenerators::new(
+            len as usize,
+            HYRAX_GENERATORS_LABEL,
+        )))
+    }
+}
+
+impl<G: CurveGroup> ark_serialize::Valid for HyraxVerifierSetup<G> {
+    fn check(&self) -> Result<(), ark_serialize::SerializationError> {
+        Ok(())
+    }
+}
+
 const TRACE_LEN_R1CS_POLYS_BATCH_RATIO: usize = 64;
 const SURGE_RATIO_READ_WRITE: usize = 16;
 const SURGE_RATIO_FINAL: usize = 4;
@@ -50,11 +104,18 @@ pub fn matrix_dimensions(num_vars: usize, ratio: usize) -> (usize, usize) {
 impl<F: JoltField, G: CurveGroup<ScalarField = F>> CommitmentScheme for HyraxScheme<G> {
     type Field = G::ScalarField;
     type Setup = PedersenGenerators<G>;
+    type VerifierSetup = HyraxVerifierSetup<G>;
     type Commitment = HyraxCommitment<G>;
     type Proof = HyraxOpeningProof<G>;
     type BatchedProof = BatchedHyraxOpeningProof<G>;
 
-    fn setup(shapes: &[CommitShape]) -> Self::Setup {
+    fn setup(shapes: &[CommitShape]) -> Result<Self::Setup, SRSError> {
+        Ok(PedersenGenerators::new(
+            Self::setup_size(shapes),
+            HYRAX_GENERATORS_LABEL,
+        ))
+    }
+    fn setup_size(shapes: &[CommitShape]) -> usize {
         let mut max_len: usize = 0;
         for shape in shapes {
             let len = matrix_dimensions(
@@ -66,7 +127,14 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> CommitmentScheme for HyraxSch
                 max_len = len;
             }
         }
-        PedersenGenerators::new(max_len, b"Jolt v1 Hyrax generators")
+        max_len
+    }
+    fn trim_setup(setup: &Self::Setup, size: usize) -> Option<Self::Setup> {
+        // Generators are drawn sequentially from a fixed seed, so smaller setups are prefixes.
+        Some(setup.clone_n(size))
+    }
+    fn verifier_setup(setup: &Self::Setup) -> Self::VerifierSetup {
+        HyraxVerifierSetup(setup.clone())
     }
     fn commit(poly: &DensePolynomial<Self::Field>, gens: &Self::Setup) -> Self::Commitment {
         HyraxCommitment::commit(poly, gens)
@@ -108,7 +176,7 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> CommitmentScheme for HyraxSch
     }
     fn verify(
         proof: &Self::Proof,
-        generators: &Self::Setup,
+        generators: &Self::VerifierSetup,
         transcript: &mut ProofTranscript,
         opening_point: &[Self::Field],
         opening: &Self::Field,
@@ -128,12 +196,17 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> CommitmentScheme for HyraxSch
     #[tracing::instrument(skip_all, name = "HyraxScheme::batch_verify")]
     fn batch_verify(
         batch_proof: &Self::BatchedProof,
-        generators: &Self::Setup,
+        generators: &Self::VerifierSetup,
         opening_point: &[Self::Field],
         openings: &[Self::Field],
         commitments: &[&Self::Commitment],
+        batch_type: BatchType,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
+        // The matrix shape must be the one the polynomials were committed with
+        if batch_proof.ratio != batch_type_to_ratio(&batch_type) {
+            return Err(ProofVerifyError::InternalError);
+        }
         BatchedHyraxOpeningProof::verify(
             batch_proof,
             generators,
@@ -153,7 +226,7 @@ pub struct HyraxGenerators<G: CurveGroup> {
     pub gens: PedersenGenerators<G>,
 }
 
-#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct HyraxCommitment<G: CurveGroup> {
     pub row_commitments: Vec<G>,
 }
@@ -183,6 +256,32 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> HyraxCommitment<G> {
         Self { row_commitments }
     }
 
+    /// Hiding commitment to `poly`, with each row commitment blinded by a fresh random scalar.
+    /// Returns the row blinds along with the commitment; they are needed to open it with
+    /// `HidingHyraxOpeningProof`.
+    #[tracing::instrument(skip_all, name = "HyraxCommitment::commit_hiding")]
+    pub fn commit_hiding<R: RngCore + CryptoRng>(
+        poly: &DensePolynomial<G::ScalarField>,
+        generators: &PedersenGenerators<G>,
+        rng: &mut R,
+    ) -> (Self, Vec<G::ScalarField>) {
+        let (L_size, R_size) = matrix_dimensions(poly.get_num_vars(), 1);
+        assert_eq!(L_size * R_size, poly.len());
+
+        let blinds: Vec<G::ScalarField> = (0..L_size).map(|_| F::random(rng)).collect();
+        let gens = CurveGroup::normalize_batch(&generators.generators[..R_size]);
+        let row_commitments = poly
+            .evals_ref()
+            .par_chunks(R_size)
+            .zip(blinds.par_iter())
+            .map(|(row, blind)| {
+                let commitment: G = PedersenCommitment::commit_vector(row, &gens);
+                commitment + generators.blinding_generator * blind
+            })
+            .collect();
+        (Self { row_commitments }, blinds)
+    }
+
     #[tracing::instrument(skip_all, name = "HyraxCommitment::batch_commit")]
     pub fn batch_commit(
         batch: &[&[G::ScalarField]],
@@ -321,6 +420,84 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> HyraxOpeningProof<G> {
     }
 }
 
+/// Opening proof for a hiding `HyraxCommitment` that reveals nothing about the polynomial
+/// beyond its evaluation. Rather than sending `L * Z` in the clear, the prover shows that the
+/// commitment to it, which the verifier derives from the row commitments, opens to a vector
+/// whose inner product with `R` is the evaluation.
+#[derive(Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct HidingHyraxOpeningProof<G: CurveGroup> {
+    pub dot_product_proof: DotProductProof<G>,
+}
+
+impl<F: JoltField, G: CurveGroup<ScalarField = F>> HidingHyraxOpeningProof<G> {
+    fn protocol_name() -> &'static [u8] {
+        b"Hiding Hyrax opening proof"
+    }
+
+    /// Proves the evaluation of `poly` at `opening_point`, given the row blinds returned by
+    /// `HyraxCommitment::commit_hiding`.
+    #[tracing::instrument(skip_all, name = "HidingHyraxOpeningProof::prove")]
+    pub fn prove<R: RngCore + CryptoRng>(
+        poly: &DensePolynomial<G::ScalarField>,
+        blinds: &[G::ScalarField],
+        opening_point: &[G::ScalarField],
+        generators: &PedersenGenerators<G>,
+        transcript: &mut ProofTranscript,
+        rng: &mut R,
+    ) -> Self {
+        transcript.append_protocol_name(Self::protocol_name());
+        assert_eq!(poly.get_num_vars(), opening_point.len());
+
+        let (L_size, _R_size) = matrix_dimensions(poly.get_num_vars(), 1);
+        assert_eq!(blinds.len(), L_size);
+        let eq = EqPolynomial::new(opening_point.to_vec());
+        let (L, R) = eq.compute_factored_evals(L_size);
+
+        let vector_matrix_product = HyraxOpeningProof::<G>::vector_matrix_product(poly, &L, 1);
+        let blind = compute_dotproduct(&L, blinds);
+        let product_commitment = generators.commit_hiding(&vector_matrix_product, &blind);
+
+        let (dot_product_proof, _) = DotProductProof::prove(
+            generators,
+            &product_commitment,
+            &vector_matrix_product,
+            &blind,
+            &R,
+            transcript,
+            rng,
+        );
+        Self { dot_product_proof }
+    }
+
+    pub fn verify(
+        &self,
+        generators: &PedersenGenerators<G>,
+        transcript: &mut ProofTranscript,
+        opening_point: &[G::ScalarField],
+        opening: &G::ScalarField,
+        commitment: &HyraxCommitment<G>,
+    ) -> Result<(), ProofVerifyError> {
+        transcript.append_protocol_name(Self::protocol_name());
+
+        let (L_size, _R_size) = matrix_dimensions(opening_point.len(), 1);
+        if commitment.row_commitments.len() != L_size {
+            return Err(ProofVerifyError::InvalidInputLength(
+                L_size,
+                commitment.row_commitments.len(),
+            ));
+        }
+        let eq = EqPolynomial::new(opening_point.to_vec());
+        let (L, R) = eq.compute_factored_evals(L_size);
+
+        // Commitment to L * Z, blinded by <L, blinds>
+        let product_commitment: G =
+            VariableBaseMSM::msm(&G::normalize_batch(&commitment.row_commitments), &L).unwrap();
+
+        self.dot_product_proof
+            .verify(generators, &product_commitment, &R, opening, transcript)
+    }
+}
+
 #[derive(Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct BatchedHyraxOpeningProof<G: CurveGroup> {
     pub joint_proof: HyraxOpeningProof<G>,
@@ -473,6 +650,8 @@ impl<F: JoltField, G: CurveGroup<ScalarField = F>> BatchedHyraxOpeningProof<G> {
 mod tests {
     use super::*;
     use ark_bn254::{Fr, G1Projective};
+    use ark_std::rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
 
     #[test]
     fn check_polynomial_commit() {
@@ -520,4 +699,64 @@ mod tests {
             )
             .is_ok());
     }
+    #[test]
+    fn verifier_setup_serialization() {
+        let setup =
+            HyraxScheme::<G1Projective>::setup(&[CommitShape::new(1 << 10, BatchType::Small)])
+                .unwrap();
+        let verifier_setup = HyraxScheme::verifier_setup(&setup);
+
+        let mut bytes = Vec::new();
+        verifier_setup.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 8);
+        let deserialized =
+            HyraxVerifierSetup::<G1Projective>::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(deserialized.generators, setup.generators);
+    }
+
+    #[test]
+    fn check_hiding_polynomial_commit() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let poly = DensePolynomial::new((0..1 << 5).map(|_| Fr::random(&mut rng)).collect());
+        let r: Vec<Fr> = (0..5).map(|_| Fr::random(&mut rng)).collect();
+        let eval = poly.evaluate(&r);
+
+        let generators: PedersenGenerators<G1Projective> =
+            PedersenGenerators::new(1 << 8, b"test-hiding");
+        let (poly_commitment, blinds) =
+            HyraxCommitment::commit_hiding(&poly, &generators, &mut rng);
+        assert_ne!(poly_commitment, HyraxCommitment::commit(&poly, &generators));
+
+        let mut prover_transcript = ProofTranscript::new(b"example");
+        let proof = HidingHyraxOpeningProof::prove(
+            &poly,
+            &blinds,
+            &r,
+            &generators,
+            &mut prover_transcript,
+            &mut rng,
+        );
+
+        let mut verifier_transcript = ProofTranscript::new(b"example");
+        assert!(proof
+            .verify(
+                &generators,
+                &mut verifier_transcript,
+                &r,
+                &eval,
+                &poly_commitment
+            )
+            .is_ok());
+
+        let mut verifier_transcript = ProofTranscript::new(b"example");
+        assert!(proof
+            .verify(
+                &generators,
+                &mut verifier_transcript,
+                &r,
+                &(eval + Fr::from(1u64)),
+                &poly_commitment
+            )
+            .is_err());
+    }
 }
@@ -2,18 +2,28 @@ use crate::field::JoltField;
 use crate::msm::VariableBaseMSM;
 use crate::poly::unipoly::UniPoly;
 use crate::utils::errors::ProofVerifyError;
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G2Affine};
 use ark_ec::scalar_mul::fixed_base::FixedBase;
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
-use ark_ff::PrimeField;
+use ark_ff::{BigInt, BigInteger, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{One, UniformRand, Zero};
-use rand_core::{CryptoRng, RngCore};
+use rand_chacha::ChaCha20Rng;
+use rand_core::{CryptoRng, RngCore, SeedableRng};
+use rayon::prelude::*;
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::marker::PhantomData;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
 
 #[cfg(feature = "ark-msm")]
 use ark_ec::VariableBaseMSM;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct SRS<P: Pairing> {
     pub g1_powers: Vec<P::G1Affine>,
     pub g2_powers: Vec<P::G2Affine>,
@@ -58,6 +68,29 @@ impl<P: Pairing> SRS<P> {
         }
     }
 
+    /// An SRS from a fixed seed, whose toxic waste is public. For tests only: setups never
+    /// fall back to it, it has to be passed to them explicitly.
+    #[cfg(any(test, feature = "test-srs"))]
+    pub fn seeded_for_tests(max_degree: usize) -> Self {
+        Self::setup(
+            &mut ChaCha20Rng::from_seed(*b"HyperKZG_POLY_COMMITMENT_SCHEMEE"),
+            max_degree,
+        )
+    }
+
+    /// The SRS for a smaller degree, consisting of the first `max_degree + 1` powers.
+    pub fn truncate(&self, max_degree: usize) -> Self {
+        assert!(
+            max_degree < self.g1_powers.len(),
+            "SRS length is less than size"
+        );
+        let g2_len = std::cmp::min(self.g2_powers.len(), max_degree + 1);
+        Self {
+            g1_powers: self.g1_powers[..=max_degree].to_vec(),
+            g2_powers: self.g2_powers[..g2_len].to_vec(),
+        }
+    }
+
     pub fn trim(params: Arc<Self>, max_degree: usize) -> (KZGProverKey<P>, KZGVerifierKey<P>) {
         assert!(!params.g1_powers.is_empty(), "max_degree is 0");
         assert!(
@@ -73,7 +106,261 @@ impl<P: Pairing> SRS<P> {
     }
 }
 
-#[derive(Clone, Debug)]
+/// Environment variable holding the path of a powers-of-tau ceremony file (`.ptau`, as
+/// produced by snarkjs and the perpetual powers of tau ceremony) from which the KZG-based
+/// commitment schemes load their structured reference string.
+pub const PTAU_PATH_ENV: &str = "JOLT_PTAU_PATH";
+
+#[derive(Error, Debug)]
+pub enum SRSError {
+    #[error("failed to read powers of tau file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed powers of tau file: {0}")]
+    InvalidFormat(String),
+    #[error("powers of tau file supports degree {0}, but degree {1} is required")]
+    DegreeTooLarge(usize, usize),
+    #[error("point {0} of the powers of tau file is not a valid group element")]
+    InvalidPoint(usize),
+    #[error("powers of tau file failed the pairing consistency check")]
+    PairingCheckFailed,
+    #[error("JOLT_PTAU_PATH must point to a BN254 powers of tau file supporting degree {0}")]
+    CeremonyUnset(usize),
+}
+
+const PTAU_HEADER_SECTION: u32 = 1;
+const PTAU_TAU_G1_SECTION: u32 = 2;
+const PTAU_TAU_G2_SECTION: u32 = 3;
+const PTAU_FIELD_BYTES: usize = 32;
+
+/// Loaded SRSs, keyed by ceremony file and degree.
+type PtauCache = HashMap<(PathBuf, usize), Arc<SRS<Bn254>>>;
+
+lazy_static::lazy_static! {
+    static ref PTAU_CACHE: Mutex<PtauCache> = Mutex::new(HashMap::new());
+    static ref PTAU_DIGESTS: Mutex<HashMap<PathBuf, String>> = Mutex::new(HashMap::new());
+}
+
+impl SRS<Bn254> {
+    /// Loads the SRS from the powers of tau file at `path`, trimmed to `max_degree`. The
+    /// result is cached for the lifetime of the process, so that the file is parsed and
+    /// validated only once per degree.
+    pub fn load_ptau(path: &Path, max_degree: usize) -> Result<Arc<Self>, SRSError> {
+        let key = (path.to_path_buf(), max_degree);
+        if let Some(srs) = PTAU_CACHE.lock().unwrap().get(&key) {
+            return Ok(srs.clone());
+        }
+        let srs = Arc::new(Self::from_ptau(
+            BufReader::new(File::open(path)?),
+            max_degree,
+        )?);
+        PTAU_CACHE.lock().unwrap().insert(key, srs.clone());
+        Ok(srs)
+    }
+
+    /// The hex-encoded Keccak digest of the powers of tau file at `path`, which identifies the
+    /// ceremony an SRS was loaded from. Like the SRS, it is cached for the lifetime of the
+    /// process.
+    pub fn ptau_digest(path: &Path) -> Result<String, SRSError> {
+        if let Some(digest) = PTAU_DIGESTS.lock().unwrap().get(path) {
+            return Ok(digest.clone());
+        }
+        let mut hasher = Keccak256::new();
+        std::io::copy(&mut File::open(path)?, &mut hasher)?;
+        let digest: String = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        PTAU_DIGESTS
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), digest.clone());
+        Ok(digest)
+    }
+
+    /// Reads the first `max_degree + 1` powers of tau in G1 from a `.ptau` file, along with
+    /// the first two powers in G2 (the only ones the KZG verifiers use), and checks that
+    /// they are valid group elements forming a consistent geometric sequence.
+    #[tracing::instrument(skip_all, name = "SRS::from_ptau")]
+    pub fn from_ptau<R: Read + Seek>(mut reader: R, max_degree: usize) -> Result<Self, SRSError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != b"ptau" {
+            return Err(SRSError::InvalidFormat("missing ptau magic".to_string()));
+        }
+        let _version = read_u32(&mut reader)?;
+        let num_sections = read_u32(&mut reader)?;
+
+        // Section type -> (offset, size)
+        let mut sections = HashMap::new();
+        for _ in 0..num_sections {
+            let section_type = read_u32(&mut reader)?;
+            let size = read_u64(&mut reader)?;
+            let offset = reader.stream_position()?;
+            sections.insert(section_type, (offset, size));
+            reader.seek(SeekFrom::Current(size as i64))?;
+        }
+        let mut section = |section_type: u32, len: usize| -> Result<Vec<u8>, SRSError> {
+            let (offset, size) = *sections.get(&section_type).ok_or_else(|| {
+                SRSError::InvalidFormat(format!("missing section {}", section_type))
+            })?;
+            if (len as u64) > size {
+                return Err(SRSError::InvalidFormat(format!(
+                    "section {} is too short",
+                    section_type
+                )));
+            }
+            let mut bytes = vec![0u8; len];
+            reader.seek(SeekFrom::Start(offset))?;
+            reader.read_exact(&mut bytes)?;
+            Ok(bytes)
+        };
+
+        let header = section(PTAU_HEADER_SECTION, 4 + PTAU_FIELD_BYTES + 4)?;
+        let field_bytes = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+        let modulus = &header[4..4 + PTAU_FIELD_BYTES];
+        if field_bytes != PTAU_FIELD_BYTES || modulus != Fq::MODULUS.to_bytes_le() {
+            return Err(SRSError::InvalidFormat("not a BN254 ceremony".to_string()));
+        }
+        let power = u32::from_le_bytes(header[4 + PTAU_FIELD_BYTES..].try_into().unwrap());
+        if power == 0 || power > 32 {
+            return Err(SRSError::InvalidFormat(format!(
+                "unsupported power {}",
+                power
+            )));
+        }
+        let num_g1 = (1usize << (power + 1)) - 1;
+        if max_degree + 1 > num_g1 {
+            return Err(SRSError::DegreeTooLarge(num_g1 - 1, max_degree));
+        }
+        let g1_len = std::cmp::max(max_degree, 1) + 1;
+        let g2_len = 2;
+
+        let g1_bytes = section(PTAU_TAU_G1_SECTION, g1_len * 2 * PTAU_FIELD_BYTES)?;
+        let g2_bytes = section(PTAU_TAU_G2_SECTION, g2_len * 4 * PTAU_FIELD_BYTES)?;
+
+        let g1_powers = g1_bytes
+            .par_chunks(2 * PTAU_FIELD_BYTES)
+            .enumerate()
+            .map(|(i, bytes)| {
+                let point = G1Affine::new_unchecked(
+                    read_fq(&bytes[..PTAU_FIELD_BYTES]).ok_or(SRSError::InvalidPoint(i))?,
+                    read_fq(&bytes[PTAU_FIELD_BYTES..]).ok_or(SRSError::InvalidPoint(i))?,
+                );
+                if point.is_zero()
+                    || !point.is_on_curve()
+                    || !point.is_in_correct_subgroup_assuming_on_curve()
+                {
+                    return Err(SRSError::InvalidPoint(i));
+                }
+                Ok(point)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let g2_powers = g2_bytes
+            .par_chunks(4 * PTAU_FIELD_BYTES)
+            .enumerate()
+            .map(|(i, bytes)| {
+                let coordinate =
+                    |j: usize| read_fq(&bytes[j * PTAU_FIELD_BYTES..][..PTAU_FIELD_BYTES]);
+                let point = G2Affine::new_unchecked(
+                    Fq2::new(
+                        coordinate(0).ok_or(SRSError::InvalidPoint(i))?,
+                        coordinate(1).ok_or(SRSError::InvalidPoint(i))?,
+                    ),
+                    Fq2::new(
+                        coordinate(2).ok_or(SRSError::InvalidPoint(i))?,
+                        coordinate(3).ok_or(SRSError::InvalidPoint(i))?,
+                    ),
+                );
+                if point.is_zero()
+                    || !point.is_on_curve()
+                    || !point.is_in_correct_subgroup_assuming_on_curve()
+                {
+                    return Err(SRSError::InvalidPoint(i));
+                }
+                Ok(point)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Random coefficients for the batched pairing checks, derived from the powers themselves.
+        let seed: [u8; 32] = Keccak256::new()
+            .chain_update(&g1_bytes)
+            .chain_update(&g2_bytes)
+            .finalize()
+            .into();
+        let mut rng = ChaCha20Rng::from_seed(seed);
+
+        let srs = Self {
+            g1_powers,
+            g2_powers,
+        };
+        if !srs.is_consistent(&mut rng) {
+            return Err(SRSError::PairingCheckFailed);
+        }
+        Ok(srs)
+    }
+
+    /// Checks e(tau^{i+1} G1, G2) = e(tau^i G1, tau G2) for all i, batched with random
+    /// coefficients, and e(tau G1, G2) = e(G1, tau G2).
+    fn is_consistent<R: RngCore>(&self, rng: &mut R) -> bool {
+        let (g1, g2) = (&self.g1_powers, &self.g2_powers);
+        let coeffs: Vec<_> = (1..g1.len()).map(|_| ark_bn254::Fr::rand(rng)).collect();
+        let msm = |bases: &[G1Affine]| {
+            <ark_bn254::G1Projective as VariableBaseMSM>::msm(bases, &coeffs).unwrap()
+        };
+
+        let g1_check =
+            Bn254::multi_pairing([msm(&g1[1..]), -msm(&g1[..g1.len() - 1])], [g2[0], g2[1]]);
+        let g2_check =
+            Bn254::multi_pairing([g1[1].into_group(), -g1[0].into_group()], [g2[0], g2[1]]);
+        g1_check.is_zero() && g2_check.is_zero()
+    }
+}
+
+/// Pairings for which the structured reference string of the KZG-based schemes is taken
+/// from a public powers of tau ceremony.
+pub trait CeremonySRS: Pairing {
+    fn ceremony_srs(max_degree: usize) -> Result<Arc<SRS<Self>>, SRSError>;
+    /// Identifies the ceremony `ceremony_srs` loads from, so that SRSs from different
+    /// ceremonies are never mistaken for one another.
+    fn ceremony_id(max_degree: usize) -> Result<String, SRSError>;
+}
+
+impl CeremonySRS for Bn254 {
+    /// Loads the SRS from the file at `$JOLT_PTAU_PATH`.
+    fn ceremony_srs(max_degree: usize) -> Result<Arc<SRS<Self>>, SRSError> {
+        let path = std::env::var_os(PTAU_PATH_ENV).ok_or(SRSError::CeremonyUnset(max_degree))?;
+        SRS::load_ptau(Path::new(&path), max_degree)
+    }
+
+    /// The digest of the file at `$JOLT_PTAU_PATH`.
+    fn ceremony_id(max_degree: usize) -> Result<String, SRSError> {
+        let path = std::env::var_os(PTAU_PATH_ENV).ok_or(SRSError::CeremonyUnset(max_degree))?;
+        SRS::ptau_digest(Path::new(&path))
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, SRSError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, SRSError> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Reads a base field element stored little-endian in Montgomery form, as snarkjs does.
+fn read_fq(bytes: &[u8]) -> Option<Fq> {
+    let limbs: [u64; 4] =
+        std::array::from_fn(|i| u64::from_le_bytes(bytes[8 * i..8 * (i + 1)].try_into().unwrap()));
+    let value = BigInt::new(limbs);
+    (value < Fq::MODULUS).then(|| Fq::new_unchecked(value))
+}
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct KZGProverKey<P: Pairing> {
     srs: Arc<SRS<P>>,
     // offset to read into SRS
@@ -98,12 +385,16 @@ impl<P: Pairing> KZGProverKey<P> {
         }
     }
 
+    pub fn srs(&self) -> &SRS<P> {
+        &self.srs
+    }
+
     pub fn g1_powers(&self) -> &[P::G1Affine] {
         &self.srs.g1_powers[self.offset..self.offset + self.supported_size]
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct KZGVerifierKey<P: Pairing> {
     pub g1: P::G1Affine,
     pub g2: P::G2Affine,
@@ -229,4 +520,112 @@ mod test {
         }
         Ok(())
     }
+
+    fn fq_bytes(x: &Fq) -> Vec<u8> {
+        // Montgomery form, as written by snarkjs
+        x.0 .0.iter().flat_map(|limb| limb.to_le_bytes()).collect()
+    }
+
+    fn write_ptau(srs: &SRS<Bn254>, power: u32) -> Vec<u8> {
+        let mut header = (PTAU_FIELD_BYTES as u32).to_le_bytes().to_vec();
+        header.extend(Fq::MODULUS.to_bytes_le());
+        header.extend(power.to_le_bytes());
+        header.extend(power.to_le_bytes());
+        let g1: Vec<u8> = srs
+            .g1_powers
+            .iter()
+            .flat_map(|p| [fq_bytes(&p.x), fq_bytes(&p.y)].concat())
+            .collect();
+        let g2: Vec<u8> = srs
+            .g2_powers
+            .iter()
+            .flat_map(|p| {
+                [
+                    fq_bytes(&p.x.c0),
+                    fq_bytes(&p.x.c1),
+                    fq_bytes(&p.y.c0),
+                    fq_bytes(&p.y.c1),
+                ]
+                .concat()
+            })
+            .collect();
+
+        let mut bytes = b"ptau".to_vec();
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(3u32.to_le_bytes());
+        for (section_type, data) in [
+            (PTAU_HEADER_SECTION, header),
+            (PTAU_TAU_G1_SECTION, g1),
+            (PTAU_TAU_G2_SECTION, g2),
+        ] {
+            bytes.extend(section_type.to_le_bytes());
+            bytes.extend((data.len() as u64).to_le_bytes());
+            bytes.extend(data);
+        }
+        bytes
+    }
+
+    #[test]
+    fn ptau_load_and_trim() {
+        let srs = SRS::<Bn254>::setup(&mut ChaCha20Rng::from_seed([7u8; 32]), 15);
+        let ptau = write_ptau(&srs, 4);
+
+        let loaded = SRS::from_ptau(std::io::Cursor::new(&ptau), 7).unwrap();
+        assert_eq!(loaded.g1_powers, srs.g1_powers[..8]);
+        assert_eq!(loaded.g2_powers, srs.g2_powers[..2]);
+
+        assert!(matches!(
+            SRS::from_ptau(std::io::Cursor::new(&ptau), 40),
+            Err(SRSError::DegreeTooLarge(30, 40))
+        ));
+        assert!(matches!(
+            SRS::from_ptau(std::io::Cursor::new(&ptau[1..]), 7),
+            Err(SRSError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn ptau_rejects_inconsistent_powers() {
+        let mut srs = SRS::<Bn254>::setup(&mut ChaCha20Rng::from_seed([7u8; 32]), 15);
+        srs.g1_powers.swap(2, 3);
+        let ptau = write_ptau(&srs, 4);
+        assert!(matches!(
+            SRS::from_ptau(std::io::Cursor::new(&ptau), 7),
+            Err(SRSError::PairingCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn ceremony_srs_requires_ptau_file() {
+        // Even in tests, there is no fallback to an insecure SRS.
+        if std::env::var_os(PTAU_PATH_ENV).is_none() {
+            assert!(matches!(
+                Bn254::ceremony_srs(15),
+                Err(SRSError::CeremonyUnset(15))
+            ));
+            assert!(matches!(
+                Bn254::ceremony_id(15),
+                Err(SRSError::CeremonyUnset(15))
+            ));
+        }
+    }
+
+    #[test]
+    fn ptau_digest_identifies_ceremony() {
+        let dir = std::env::temp_dir().join(format!("jolt-ptau-digest-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let digests: Vec<String> = [7u8, 8u8]
+            .iter()
+            .map(|&seed| {
+                let srs = SRS::<Bn254>::setup(&mut ChaCha20Rng::from_seed([seed; 32]), 15);
+                let path = dir.join(format!("{}.ptau", seed));
+                std::fs::write(&path, write_ptau(&srs, 4)).unwrap();
+                SRS::ptau_digest(&path).unwrap()
+            })
+            .collect();
+        assert_eq!(digests[0].len(), 64);
+        assert_ne!(digests[0], digests[1]);
+        assert_eq!(SRS::ptau_digest(&dir.join("7.ptau")).unwrap(), digests[0]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -12,13 +12,14 @@ use crate::{
 };
 
 use super::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
+use super::kzg::SRSError;
 
 #[derive(Clone)]
 pub struct MockCommitScheme<F: JoltField> {
     _marker: PhantomData<F>,
 }
 
-#[derive(CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct MockCommitment<F: JoltField> {
     poly: DensePolynomial<F>,
 }
@@ -37,11 +38,18 @@ pub struct MockProof<F: JoltField> {
 impl<F: JoltField> CommitmentScheme for MockCommitScheme<F> {
     type Field = F;
     type Setup = ();
+    type VerifierSetup = ();
     type Commitment = MockCommitment<F>;
     type Proof = MockProof<F>;
     type BatchedProof = MockProof<F>;
 
-    fn setup(_shapes: &[CommitShape]) -> Self::Setup {}
+    fn setup(_shapes: &[CommitShape]) -> Result<Self::Setup, SRSError> {
+        Ok(())
+    }
+    fn trim_setup(_setup: &Self::Setup, _size: usize) -> Option<Self::Setup> {
+        Some(())
+    }
+    fn verifier_setup(_setup: &Self::Setup) -> Self::VerifierSetup {}
     fn commit(poly: &DensePolynomial<Self::Field>, _setup: &Self::Setup) -> Self::Commitment {
         MockCommitment {
             poly: poly.to_owned(),
@@ -92,7 +100,7 @@ impl<F: JoltField> CommitmentScheme for MockCommitScheme<F> {
 
     fn verify(
         proof: &Self::Proof,
-        _setup: &Self::Setup,
+        _setup: &Self::VerifierSetup,
         _transcript: &mut ProofTranscript,
         opening_point: &[Self::Field],
         opening: &Self::Field,
@@ -106,10 +114,11 @@ impl<F: JoltField> CommitmentScheme for MockCommitScheme<F> {
 
     fn batch_verify(
         batch_proof: &Self::BatchedProof,
-        _setup: &Self::Setup,
+        _setup: &Self::VerifierSetup,
         opening_point: &[Self::Field],
         openings: &[Self::Field],
         commitments: &[&Self::Commitment],
+        _batch_type: BatchType,
         _transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
         assert_eq!(batch_proof.opening_point, opening_point);
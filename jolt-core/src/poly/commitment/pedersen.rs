@@ -11,11 +11,29 @@ use crate::msm::VariableBaseMSM;
 #[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct PedersenGenerators<G: CurveGroup> {
     pub generators: Vec<G>,
+    /// Generator `h` of hiding commitments `<x, G> + r * h`, sampled from a separate seed so
+    /// that smaller setups remain prefixes of larger ones.
+    pub blinding_generator: G,
 }
 
 impl<G: CurveGroup> PedersenGenerators<G> {
     #[tracing::instrument(skip_all, name = "PedersenGenerators::new")]
     pub fn new(len: usize, label: &[u8]) -> Self {
+        let mut rng = Self::rng(label);
+        let mut generators: Vec<G> = Vec::new();
+        for _ in 0..len {
+            generators.push(G::rand(&mut rng));
+        }
+
+        let blinding_generator = G::rand(&mut Self::rng(&[label, b" blinding"].concat()));
+
+        Self {
+            generators,
+            blinding_generator,
+        }
+    }
+
+    fn rng(label: &[u8]) -> ChaCha20Rng {
         let mut shake = Shake256::default();
         shake.update(label);
         let mut buf = vec![];
@@ -25,14 +43,7 @@ impl<G: CurveGroup> PedersenGenerators<G> {
         let mut reader = shake.finalize_xof();
         let mut seed = [0u8; 32];
         reader.read_exact(&mut seed).unwrap();
-        let mut rng = ChaCha20Rng::from_seed(seed);
-
-        let mut generators: Vec<G> = Vec::new();
-        for _ in 0..len {
-            generators.push(G::rand(&mut rng));
-        }
-
-        Self { generators }
+        ChaCha20Rng::from_seed(seed)
     }
 
     pub fn clone_n(&self, n: usize) -> PedersenGenerators<G> {
@@ -45,8 +56,15 @@ impl<G: CurveGroup> PedersenGenerators<G> {
         let slice = &self.generators[..n];
         PedersenGenerators {
             generators: slice.into(),
+            blinding_generator: self.blinding_generator,
         }
     }
+
+    /// Hiding commitment `<values, generators> + blind * h` to `values`.
+    pub fn commit_hiding(&self, values: &[G::ScalarField], blind: &G::ScalarField) -> G {
+        let bases = G::normalize_batch(&self.generators[..values.len()]);
+        <G as VariableBaseMSM>::msm(&bases, values).unwrap() + self.blinding_generator * blind
+    }
 }
 
 pub trait PedersenCommitment<G: CurveGroup>: Sized {
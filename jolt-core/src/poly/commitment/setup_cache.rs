@@ -0,0 +1,291 @@
+//! On-disk cache of commitment scheme setups.
+//!
+//! Setups are stored as `<scheme>-<size>.v<version>.setup`, where `size` is the scheme's
+//! `CommitmentScheme::setup_size` for the requested shapes and `version` is
+//! `SETUP_FORMAT_VERSION`, or as `<scheme>-<source>-<size>.v<version>.setup` for schemes whose
+//! setups have a
+//! `CommitmentScheme::setup_source`, such as the digest of the ceremony file the KZG-based
+//! schemes load their SRS from (see `kzg::PTAU_PATH_ENV`). A request is served by the cached
+//! setup of the same source and size if there is one, and otherwise by trimming the smallest
+//! larger one of the same source, so that a setup generated for a large program is reused by
+//! every smaller program.
+//!
+//! Cached setups are deserialized with validation, so a corrupted file is recomputed rather
+//! than loaded with points that are not valid group elements.
+
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use super::commitment_scheme::{CommitShape, CommitmentScheme};
+use super::kzg::SRSError;
+
+/// Environment variable holding the directory in which setups are cached. Setups are not
+/// cached unless it is set.
+pub const SETUP_CACHE_DIR_ENV: &str = "JOLT_SETUP_CACHE_DIR";
+
+/// Version of the serialized layout of setups, which is part of the names of cached setups so
+/// that setups cached in an older layout are never loaded. It has to be bumped whenever the
+/// serialization of a scheme's `Setup` changes; version 1 added the blinding generator of
+/// `PedersenGenerators`.
+const SETUP_FORMAT_VERSION: u32 = 1;
+
+/// `PCS::setup(shapes)`, served from the cache in `$JOLT_SETUP_CACHE_DIR` if it is set.
+pub fn setup<PCS: CommitmentScheme>(shapes: &[CommitShape]) -> Result<PCS::Setup, SRSError> {
+    match std::env::var_os(SETUP_CACHE_DIR_ENV) {
+        Some(cache_dir) => cached_setup::<PCS>(Path::new(&cache_dir), shapes),
+        None => PCS::setup(shapes),
+    }
+}
+
+/// `PCS::setup(shapes)`, served from the cache in `cache_dir`. Setups that have to be
+/// computed are added to the cache; failing to do so is not an error.
+#[tracing::instrument(skip_all, name = "setup_cache::cached_setup")]
+pub fn cached_setup<PCS: CommitmentScheme>(
+    cache_dir: &Path,
+    shapes: &[CommitShape],
+) -> Result<PCS::Setup, SRSError> {
+    let size = PCS::setup_size(shapes);
+    cached_setup_from::<PCS>(cache_dir, size, PCS::setup_source(size)?, || {
+        PCS::setup(shapes)
+    })
+}
+
+/// A setup of the given size and source from the cache in `cache_dir`, computed with `compute`
+/// if it is not cached and can't be trimmed from a larger one.
+fn cached_setup_from<PCS: CommitmentScheme>(
+    cache_dir: &Path,
+    size: usize,
+    source: Option<String>,
+    compute: impl FnOnce() -> Result<PCS::Setup, SRSError>,
+) -> Result<PCS::Setup, SRSError> {
+    let name = match source {
+        Some(source) => format!("{}-{}", scheme_name::<PCS>(), source),
+        None => scheme_name::<PCS>(),
+    };
+
+    if let Some(setup) = load::<PCS>(&cache_dir.join(file_name(&name, size))) {
+        return Ok(setup);
+    }
+    for (_, path) in larger_setups(cache_dir, &name, size) {
+        if let Some(setup) = load::<PCS>(&path).and_then(|setup| PCS::trim_setup(&setup, size)) {
+            return Ok(setup);
+        }
+    }
+
+    let setup = compute()?;
+    if let Err(err) = store::<PCS>(cache_dir, &file_name(&name, size), &setup) {
+        tracing::warn!("failed to cache {} setup: {}", name, err);
+    }
+    Ok(setup)
+}
+
+/// The scheme's protocol name, restricted to characters that are safe in file names.
+fn scheme_name<PCS: CommitmentScheme>() -> String {
+    String::from_utf8_lossy(PCS::protocol_name())
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn file_name(name: &str, size: usize) -> String {
+    format!("{}-{}{}", name, size, file_extension())
+}
+
+fn file_extension() -> String {
+    format!(".v{}.setup", SETUP_FORMAT_VERSION)
+}
+
+/// Cached setups of the scheme larger than `size`, smallest first.
+fn larger_setups(cache_dir: &Path, name: &str, size: usize) -> Vec<(usize, PathBuf)> {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return vec![];
+    };
+    let prefix = format!("{}-", name);
+    let mut setups: Vec<(usize, PathBuf)> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let cached_size = path
+                .file_name()?
+                .to_str()?
+                .strip_prefix(&prefix)?
+                .strip_suffix(&file_extension())?
+                .parse::<usize>()
+                .ok()?;
+            (cached_size > size).then_some((cached_size, path))
+        })
+        .collect();
+    setups.sort();
+    setups
+}
+
+fn load<PCS: CommitmentScheme>(path: &Path) -> Option<PCS::Setup> {
+    let file = File::open(path).ok()?;
+    PCS::Setup::deserialize_uncompressed(BufReader::new(file)).ok()
+}
+
+fn store<PCS: CommitmentScheme>(
+    cache_dir: &Path,
+    file_name: &str,
+    setup: &PCS::Setup,
+) -> std::io::Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    // Write to a temporary file first, so that concurrent readers never see a partial setup.
+    let tmp_path = cache_dir.join(format!("{}.{}.tmp", file_name, std::process::id()));
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    setup
+        .serialize_uncompressed(&mut writer)
+        .map_err(|err| std::io::Error::other(err.to_string()))?;
+    writer.into_inner()?.sync_all()?;
+    fs::rename(tmp_path, cache_dir.join(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::commitment::commitment_scheme::BatchType;
+    use crate::poly::commitment::hyperkzg::HyperKZG;
+    use crate::poly::commitment::hyrax::HyraxScheme;
+    use ark_bn254::{Bn254, G1Projective};
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "jolt-setup-cache-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn shapes(input_length: usize) -> Vec<CommitShape> {
+        vec![CommitShape::new(input_length, BatchType::Big)]
+    }
+
+    type PCS = HyperKZG<Bn254>;
+
+    /// `cached_setup` for the insecure seeded SRS, which `PCS::setup` doesn't fall back to.
+    fn seeded_cached_setup(
+        dir: &Path,
+        input_length: usize,
+    ) -> Result<<PCS as CommitmentScheme>::Setup, SRSError> {
+        let shapes = shapes(input_length);
+        let size = PCS::setup_size(&shapes);
+        cached_setup_from::<PCS>(dir, size, Some("seeded".to_string()), || {
+            Ok(PCS::seeded_setup(&shapes))
+        })
+    }
+
+    #[test]
+    fn hyperkzg_setup_is_cached_and_trimmed() {
+        let dir = cache_dir("hyperkzg");
+
+        let large = seeded_cached_setup(&dir, 1 << 6).unwrap();
+        assert!(dir.join(file_name("hyperkzg-seeded", 64)).exists());
+        let reloaded = seeded_cached_setup(&dir, 1 << 6).unwrap();
+        assert_eq!(reloaded.0.kzg_pk.g1_powers(), large.0.kzg_pk.g1_powers());
+
+        // A smaller setup is trimmed from the cached one rather than computed.
+        let small = seeded_cached_setup(&dir, 1 << 4).unwrap();
+        assert!(!dir.join(file_name("hyperkzg-seeded", 16)).exists());
+        let fresh = PCS::seeded_setup(&shapes(1 << 4));
+        assert_eq!(small.0.kzg_pk.g1_powers(), fresh.0.kzg_pk.g1_powers());
+        assert_eq!(small.1.kzg_vk.beta_g2, fresh.1.kzg_vk.beta_g2);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn setups_from_other_ceremonies_are_not_used() {
+        let dir = cache_dir("hyperkzg-ceremony");
+
+        seeded_cached_setup(&dir, 1 << 6).unwrap();
+        fs::rename(
+            dir.join(file_name("hyperkzg-seeded", 64)),
+            dir.join(file_name("hyperkzg-0123abcd", 64)),
+        )
+        .unwrap();
+
+        // The setup of the other ceremony is not trimmed: a new one is computed and cached.
+        seeded_cached_setup(&dir, 1 << 4).unwrap();
+        assert!(dir.join(file_name("hyperkzg-seeded", 16)).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn corrupted_setup_is_recomputed() {
+        let dir = cache_dir("hyperkzg-corrupted");
+
+        seeded_cached_setup(&dir, 1 << 4).unwrap();
+        // Change the x-coordinate of the first power of tau in G1, which follows the length
+        // of the powers, so that it is no longer on the curve.
+        let path = dir.join(file_name("hyperkzg-seeded", 16));
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[9] ^= 1;
+        fs::write(&path, bytes).unwrap();
+        assert!(load::<PCS>(&path).is_none());
+
+        let setup = seeded_cached_setup(&dir, 1 << 4).unwrap();
+        let fresh = PCS::seeded_setup(&shapes(1 << 4));
+        assert_eq!(setup.0.kzg_pk.g1_powers(), fresh.0.kzg_pk.g1_powers());
+        assert!(load::<PCS>(&path).is_some());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn hyrax_setup_is_trimmed() {
+        type PCS = HyraxScheme<G1Projective>;
+        let dir = cache_dir("hyrax");
+
+        let large_shapes = shapes(1 << 16);
+        cached_setup::<PCS>(&dir, &large_shapes).unwrap();
+        let small_shapes = shapes(1 << 8);
+        let small = cached_setup::<PCS>(&dir, &small_shapes).unwrap();
+        let expected = PCS::setup(&small_shapes).unwrap();
+        assert_eq!(small.generators, expected.generators);
+        assert_eq!(small.blinding_generator, expected.blinding_generator);
+        assert_eq!(larger_setups(&dir, &scheme_name::<PCS>(), 0).len(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn setups_in_older_formats_are_ignored() {
+        type PCS = HyraxScheme<G1Projective>;
+        let dir = cache_dir("hyrax-format");
+
+        // A setup cached before the blinding generator was added, in a larger size that would
+        // otherwise be trimmed.
+        let setup = PCS::setup(&shapes(1 << 16)).unwrap();
+        let size = PCS::setup_size(&shapes(1 << 16));
+        let mut bytes = vec![];
+        setup.generators.serialize_uncompressed(&mut bytes).unwrap();
+        fs::create_dir_all(&dir).unwrap();
+        let legacy_name = format!("{}-{}.setup", scheme_name::<PCS>(), size);
+        fs::write(dir.join(legacy_name), bytes).unwrap();
+        assert!(larger_setups(&dir, &scheme_name::<PCS>(), 0).is_empty());
+
+        let small_shapes = shapes(1 << 8);
+        let small = cached_setup::<PCS>(&dir, &small_shapes).unwrap();
+        let expected = PCS::setup(&small_shapes).unwrap();
+        assert_eq!(small.blinding_generator, expected.blinding_generator);
+        let small_size = PCS::setup_size(&small_shapes);
+        assert!(dir
+            .join(file_name(&scheme_name::<PCS>(), small_size))
+            .exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -17,7 +17,6 @@ use ark_ff::{batch_inversion, Field};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::{One, Zero};
 use itertools::izip;
-use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
 use rand_core::{CryptoRng, RngCore};
 use std::sync::Arc;
 use tracing::trace_span;
@@ -26,7 +25,7 @@ use rayon::prelude::*;
 
 use super::{
     commitment_scheme::{BatchType, CommitShape, CommitmentScheme},
-    kzg::{KZGProverKey, KZGVerifierKey, UnivariateKZG, SRS},
+    kzg::{CeremonySRS, KZGProverKey, KZGVerifierKey, SRSError, UnivariateKZG, SRS},
 };
 
 pub struct ZeromorphSRS<P: Pairing>(Arc<SRS<P>>);
@@ -51,20 +50,19 @@ impl<P: Pairing> ZeromorphSRS<P> {
     }
 }
 
-//TODO: adapt interface to have prover and verifier key
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ZeromorphProverKey<P: Pairing> {
     pub commit_pp: KZGProverKey<P>,
     pub open_pp: KZGProverKey<P>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ZeromorphVerifierKey<P: Pairing> {
     pub kzg_vk: KZGVerifierKey<P>,
     pub tau_N_max_sub_2_N: P::G2Affine,
 }
 
-#[derive(Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ZeromorphCommitment<P: Pairing>(P::G1Affine);
 
 impl<P: Pairing> AppendToTranscript for ZeromorphCommitment<P> {
@@ -468,24 +466,41 @@ where
     }
 }
 
-impl<P: Pairing> CommitmentScheme for Zeromorph<P>
+impl<P: CeremonySRS> CommitmentScheme for Zeromorph<P>
 where
     <P as Pairing>::ScalarField: field::JoltField,
 {
     type Field = P::ScalarField;
     type Setup = (ZeromorphProverKey<P>, ZeromorphVerifierKey<P>);
+    type VerifierSetup = ZeromorphVerifierKey<P>;
     type Commitment = ZeromorphCommitment<P>;
     type Proof = ZeromorphProof<P>;
     type BatchedProof = ZeromorphProof<P>;
 
-    fn setup(shapes: &[CommitShape]) -> Self::Setup {
-        let max_len = shapes.iter().map(|shape| shape.input_length).max().unwrap();
+    fn setup(shapes: &[CommitShape]) -> Result<Self::Setup, SRSError> {
+        let max_len = Self::setup_size(shapes);
 
-        ZeromorphSRS(Arc::new(SRS::setup(
-            &mut ChaCha20Rng::from_seed(*b"ZEROMORPH_POLY_COMMITMENT_SCHEME"),
-            max_len,
-        )))
-        .trim(max_len)
+        Ok(ZeromorphSRS(P::ceremony_srs(max_len)?).trim(max_len))
+    }
+
+    #[cfg(any(test, feature = "test-srs"))]
+    fn seeded_setup(shapes: &[CommitShape]) -> Self::Setup {
+        let max_len = Self::setup_size(shapes);
+
+        ZeromorphSRS(Arc::new(SRS::seeded_for_tests(max_len))).trim(max_len)
+    }
+
+    fn setup_source(size: usize) -> Result<Option<String>, SRSError> {
+        P::ceremony_id(size).map(Some)
+    }
+
+    fn trim_setup(setup: &Self::Setup, size: usize) -> Option<Self::Setup> {
+        let srs = setup.0.commit_pp.srs().truncate(size);
+        Some(ZeromorphSRS(Arc::new(srs)).trim(size))
+    }
+
+    fn verifier_setup(setup: &Self::Setup) -> Self::VerifierSetup {
+        setup.1
     }
 
     fn commit(poly: &DensePolynomial<Self::Field>, setup: &Self::Setup) -> Self::Commitment {
@@ -554,32 +569,26 @@ where
 
     fn verify(
         proof: &Self::Proof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         transcript: &mut ProofTranscript,
         opening_point: &[Self::Field], // point at which the polynomial is evaluated
         opening: &Self::Field,         // evaluation \widetilde{Z}(r)
         commitment: &Self::Commitment,
     ) -> Result<(), ProofVerifyError> {
-        Zeromorph::<P>::verify(
-            &setup.1,
-            commitment,
-            opening_point,
-            opening,
-            proof,
-            transcript,
-        )
+        Zeromorph::<P>::verify(setup, commitment, opening_point, opening, proof, transcript)
     }
 
     fn batch_verify(
         batch_proof: &Self::BatchedProof,
-        setup: &Self::Setup,
+        setup: &Self::VerifierSetup,
         opening_point: &[Self::Field],
         openings: &[Self::Field],
         commitments: &[&Self::Commitment],
+        _batch_type: BatchType,
         transcript: &mut ProofTranscript,
     ) -> Result<(), ProofVerifyError> {
         Zeromorph::<P>::batch_verify(
-            &setup.1,
+            setup,
             commitments,
             opening_point,
             openings,
@@ -58,7 +58,7 @@ where
     /// Verifies an opening proof, given the associated polynomial `commitment` and `opening_point`.
     fn verify_openings(
         &self,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         opening_proof: &Self::Proof,
         commitment: &Polynomials::Commitment,
         opening_point: &[F],
@@ -2,6 +2,7 @@ use crate::{
     assert_static_aux_index, field::JoltField, impl_r1cs_input_lc_conversions, input_range,
     jolt::vm::rv32i_vm::C,
 };
+use common::rv_trace::MemoryLayout;
 
 use super::{
     builder::{CombinedUniformBuilder, OffsetEqConstraint, R1CSBuilder, R1CSConstraintBuilder},
@@ -10,22 +11,39 @@ use super::{
 
 pub fn construct_jolt_constraints<F: JoltField>(
     padded_trace_length: usize,
-    memory_start: u64,
+    memory_layout: &MemoryLayout,
 ) -> CombinedUniformBuilder<F, JoltIn> {
     let mut uniform_builder = R1CSBuilder::<F, JoltIn>::new();
-    let constraints = UniformJoltConstraints::new(memory_start);
+    let constraints = UniformJoltConstraints::new(memory_layout);
     constraints.build_constraints(&mut uniform_builder);
-
-    let non_uniform_constraint = OffsetEqConstraint::new(
-        (JoltIn::PcIn, true),
-        (Variable::Auxiliary(PC_BRANCH_AUX_INDEX), false),
-        (4 * JoltIn::PcIn + PC_START_ADDRESS, true),
-    );
+    let pc_start: i64 = memory_layout.ram_start.try_into().unwrap();
+
+    let non_uniform_constraints = vec![
+        // If the next instruction's ELF address is not zero (i.e. it's
+        // not padding), then check the PC update.
+        OffsetEqConstraint::new(
+            (JoltIn::Bytecode_ELFAddress, true),
+            (Variable::Auxiliary(PC_BRANCH_AUX_INDEX), false),
+            (4 * JoltIn::Bytecode_ELFAddress + pc_start, true),
+        ),
+        // If the current instruction is virtual, check that the next instruction
+        // in the trace is the next instruction in bytecode. Virtual sequences
+        // do not involve jumps or branches, so this should always hold,
+        // EXCEPT if we encounter a virtual instruction followed by a padding
+        // instruction. But that should never happen because the execution
+        // trace should always end with some return handling, which shouldn't involve
+        // any virtual sequences.
+        OffsetEqConstraint::new(
+            (JoltIn::OpFlags_IsVirtual, false),
+            (JoltIn::Bytecode_A, true),
+            (JoltIn::Bytecode_A + 1, false),
+        ),
+    ];
 
     CombinedUniformBuilder::construct(
         uniform_builder,
         padded_trace_length,
-        vec![non_uniform_constraint],
+        non_uniform_constraints,
     )
 }
 
@@ -99,8 +117,9 @@ pub enum JoltIn {
     OpFlags_LookupOutToRd,
     OpFlags_SignImm,
     OpFlags_IsConcat,
-    OpFlags_IsVirtualSequence,
     OpFlags_IsVirtual,
+    OpFlags_IsAssert,
+    OpFlags_DoNotUpdatePC,
 
     // Instruction Flags
     // Should match JoltInstructionSet
@@ -136,19 +155,24 @@ pub enum JoltIn {
 impl_r1cs_input_lc_conversions!(JoltIn);
 impl ConstraintInput for JoltIn {}
 
-pub const PC_START_ADDRESS: i64 = 0x80000000;
 const PC_NOOP_SHIFT: i64 = 4;
 const LOG_M: usize = 16;
 const OPERAND_SIZE: usize = LOG_M / 2;
 pub const PC_BRANCH_AUX_INDEX: usize = 15;
 
 pub struct UniformJoltConstraints {
+    /// Address of the first byte of memory, i.e. the start of the I/O regions.
     memory_start: u64,
+    /// Address of the first instruction, i.e. the start of RAM.
+    pc_start: u64,
 }
 
 impl UniformJoltConstraints {
-    pub fn new(memory_start: u64) -> Self {
-        Self { memory_start }
+    pub fn new(memory_layout: &MemoryLayout) -> Self {
+        Self {
+            memory_start: memory_layout.ram_start - memory_layout.ram_witness_offset,
+            pc_start: memory_layout.ram_start,
+        }
     }
 }
 
@@ -164,7 +188,8 @@ impl<F: JoltField> R1CSConstraintBuilder<F> for UniformJoltConstraints {
 
         cs.constrain_pack_be(flags.to_vec(), JoltIn::Bytecode_Bitflags, 1);
 
-        let real_pc = 4i64 * JoltIn::PcIn + (PC_START_ADDRESS - PC_NOOP_SHIFT);
+        let pc_start: i64 = self.pc_start.try_into().unwrap();
+        let real_pc = 4i64 * JoltIn::Bytecode_ELFAddress + (pc_start - PC_NOOP_SHIFT);
         let x = cs.allocate_if_else(JoltIn::OpFlags_IsRs1Rs2, real_pc, JoltIn::RS1_Read);
         let y = cs.allocate_if_else(
             JoltIn::OpFlags_IsImm,
@@ -252,6 +277,9 @@ impl<F: JoltField> R1CSConstraintBuilder<F> for UniformJoltConstraints {
             );
         }
 
+        // Virtual assert instructions constrain their lookup output to be 1
+        cs.constrain_eq_conditional(JoltIn::OpFlags_IsAssert, JoltIn::LookupOutput, 1);
+
         // if (rd != 0 && update_rd_with_lookup_output == 1) constrain(rd_val == LookupOutput)
         // if (rd != 0 && is_jump_instr == 1) constrain(rd_val == 4 * PC)
         let rd_nonzero_and_lookup_to_rd =
@@ -262,21 +290,23 @@ impl<F: JoltField> R1CSConstraintBuilder<F> for UniformJoltConstraints {
             JoltIn::LookupOutput,
         );
         let rd_nonzero_and_jmp = cs.allocate_prod(JoltIn::Bytecode_RD, JoltIn::OpFlags_IsJmp);
-        let lhs = JoltIn::PcIn + (PC_START_ADDRESS - PC_NOOP_SHIFT);
+        let lhs = 4 * JoltIn::Bytecode_ELFAddress + pc_start;
         let rhs = JoltIn::RD_Write;
         cs.constrain_eq_conditional(rd_nonzero_and_jmp, lhs, rhs);
 
         let branch_and_lookup_output =
             cs.allocate_prod(JoltIn::OpFlags_IsBranch, JoltIn::LookupOutput);
+        // Instructions in a virtual sequence (other than the last one) share the
+        // ELF address of the next instruction, so they don't increment the PC.
         let next_pc_jump = cs.allocate_if_else(
             JoltIn::OpFlags_IsJmp,
             JoltIn::LookupOutput + 4,
-            4 * JoltIn::PcIn + PC_START_ADDRESS + 4,
+            4 * JoltIn::Bytecode_ELFAddress + pc_start + 4 - 4 * JoltIn::OpFlags_DoNotUpdatePC,
         );
 
         let next_pc_jump_branch = cs.allocate_if_else(
             branch_and_lookup_output,
-            4 * JoltIn::PcIn + PC_START_ADDRESS + imm_signed,
+            4 * JoltIn::Bytecode_ELFAddress + pc_start + imm_signed,
             next_pc_jump,
         );
         assert_static_aux_index!(next_pc_jump_branch, PC_BRANCH_AUX_INDEX);
@@ -305,7 +335,7 @@ mod tests {
     fn single_instruction_jolt() {
         let mut uniform_builder = R1CSBuilder::<Fr, JoltIn>::new();
 
-        let jolt_constraints = UniformJoltConstraints::new(0);
+        let jolt_constraints = UniformJoltConstraints::new(&MemoryLayout::new(0, 0, 0));
         jolt_constraints.build_constraints(&mut uniform_builder);
 
         let num_steps = 1;
@@ -67,7 +67,7 @@ pub struct UniformR1CS<F: JoltField> {
     pub num_rows: usize,
 }
 
-/// NonUniformR1CSConstraint only supports a single additional equality constraint. 'a' holds the equality (something minus something),
+/// NonUniformR1CSConstraint represents a single additional equality constraint. 'a' holds the equality (something minus something),
 /// 'b' holds the condition. 'a' * 'b' == 0. Each SparseEqualityItem stores a uniform_column (pointing to a variable) and an offset
 /// suggesting which other step to point to.
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
@@ -150,6 +150,11 @@ impl<F: JoltField> UniformSpartanKey<F> {
         }
     }
 
+    /// Number of constraint rows per step: the uniform constraints followed by the non-uniform constraints.
+    pub(crate) fn num_constraint_rows(&self) -> usize {
+        self.uniform_r1cs.num_rows + self.offset_eq_r1cs.constraints.len()
+    }
+
     fn full_z_len(&self) -> usize {
         2 * self.num_steps * self.uniform_r1cs.num_vars.next_power_of_two()
     }
@@ -173,7 +178,7 @@ impl<F: JoltField> UniformSpartanKey<F> {
     pub fn evaluate_r1cs_mle_rlc(&self, r_constr: &[F], r_step: &[F], r_rlc: F) -> Vec<F> {
         assert_eq!(
             r_constr.len(),
-            (self.uniform_r1cs.num_rows + 1).next_power_of_two().log_2()
+            self.num_constraint_rows().next_power_of_two().log_2()
         );
         assert_eq!(r_step.len(), self.num_steps.log_2());
 
@@ -307,7 +312,7 @@ impl<F: JoltField> UniformSpartanKey<F> {
         let total_rows_bits = self.num_rows_total().log_2();
         let total_cols_bits = self.num_cols_total().log_2();
         let steps_bits = self.num_steps.log_2();
-        let constraint_rows_bits = (self.uniform_r1cs.num_rows + 1).next_power_of_two().log_2();
+        let constraint_rows_bits = self.num_constraint_rows().next_power_of_two().log_2();
         let uniform_cols_bits = self.uniform_r1cs.num_vars.next_power_of_two().log_2();
         assert_eq!(r.len(), total_rows_bits + total_cols_bits);
         assert_eq!(total_rows_bits - steps_bits, constraint_rows_bits);
@@ -497,7 +502,7 @@ mod test {
         let b = DensePolynomial::new(b);
         let c = DensePolynomial::new(c);
 
-        let r_row_constr_len = (key.uniform_r1cs.num_rows + 1).next_power_of_two().log_2();
+        let r_row_constr_len = key.num_constraint_rows().next_power_of_two().log_2();
         let r_col_step_len = key.num_steps.log_2();
 
         let r_row_constr = vec![Fr::from(100), Fr::from(200)];
@@ -62,11 +62,11 @@ pub enum SpartanError {
 /// the commitment to a vector viewed as a polynomial commitment
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct UniformSpartanProof<F: JoltField, C: CommitmentScheme<Field = F>> {
-    outer_sumcheck_proof: SumcheckInstanceProof<F>,
-    outer_sumcheck_claims: (F, F, F),
-    inner_sumcheck_proof: SumcheckInstanceProof<F>,
-    claimed_witness_evals: Vec<F>,
-    opening_proof: C::BatchedProof,
+    pub(crate) outer_sumcheck_proof: SumcheckInstanceProof<F>,
+    pub(crate) outer_sumcheck_claims: (F, F, F),
+    pub(crate) inner_sumcheck_proof: SumcheckInstanceProof<F>,
+    pub(crate) claimed_witness_evals: Vec<F>,
+    pub(crate) opening_proof: C::BatchedProof,
 }
 
 impl<F: JoltField, C: CommitmentScheme<Field = F>> UniformSpartanProof<F, C> {
@@ -220,7 +220,7 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> UniformSpartanProof<F, C> {
         &self,
         key: &UniformSpartanKey<F>,
         witness_segment_commitments: Vec<&C::Commitment>,
-        generators: &C::Setup,
+        generators: &C::VerifierSetup,
         transcript: &mut ProofTranscript,
     ) -> Result<(), SpartanError> {
         let num_rounds_x = key.num_rows_total().log_2();
@@ -292,6 +292,7 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> UniformSpartanProof<F, C> {
             r_y_point,
             &self.claimed_witness_evals,
             &witness_segment_commitments,
+            BatchType::Big,
             transcript,
         )
         .map_err(|_| SpartanError::InvalidPCSProof)?;
@@ -326,7 +327,7 @@ mod test {
             .iter()
             .map(|segment| segment.as_slice())
             .collect();
-        let gens = HyraxScheme::setup(&[CommitShape::new(16, BatchType::Small)]);
+        let gens = HyraxScheme::setup(&[CommitShape::new(16, BatchType::Small)]).unwrap();
         let witness_commitment =
             HyraxScheme::batch_commit(&witness_segments_ref, &gens, BatchType::Small);
 
@@ -350,7 +351,7 @@ mod test {
             .verify_precommitted(
                 &key,
                 witness_commitment_ref,
-                &gens,
+                &HyraxScheme::verifier_setup(&gens),
                 &mut verifier_transcript,
             )
             .expect("Spartan verifier failed");
@@ -0,0 +1,319 @@
+//! ABI encoding of proofs as calldata for the generated `SubprotocolVerifier` contract.
+
+use ark_bn254::{Bn254, Fq, Fr, G1Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use sha3::{Digest, Keccak256};
+
+use crate::poly::commitment::hyperkzg::{HyperKZG, HyperKZGCommitment, HyperKZGProof};
+use crate::r1cs::spartan::UniformSpartanProof;
+use crate::subprotocols::grand_product::BatchedGrandProductProof;
+use crate::subprotocols::sumcheck::SumcheckInstanceProof;
+use crate::utils::transcript::ProofTranscript;
+
+const TRANSCRIPT: &str = "(bytes32,uint256)";
+const G1_POINT: &str = "(uint256,uint256)";
+
+/// A value in the ABI's type system.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    /// A 32-byte static value, such as `uint256` or `bytes32`.
+    Word([u8; 32]),
+    /// A tuple or fixed-size array.
+    Tuple(Vec<Token>),
+    /// A dynamically-sized array.
+    Array(Vec<Token>),
+}
+
+impl Token {
+    pub fn uint(x: u64) -> Self {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&x.to_be_bytes());
+        Token::Word(word)
+    }
+
+    pub fn scalar(x: &Fr) -> Self {
+        Self::big_endian(x.into_bigint().to_bytes_be())
+    }
+
+    pub fn scalars(xs: &[Fr]) -> Self {
+        Token::Array(xs.iter().map(Self::scalar).collect())
+    }
+
+    /// A `G1Point`, with the point at infinity encoded as (0, 0).
+    pub fn point(p: &G1Affine) -> Self {
+        let (x, y) = p
+            .xy()
+            .map_or((Fq::from(0u64), Fq::from(0u64)), |(x, y)| (*x, *y));
+        Token::Tuple(vec![
+            Self::big_endian(x.into_bigint().to_bytes_be()),
+            Self::big_endian(y.into_bigint().to_bytes_be()),
+        ])
+    }
+
+    pub fn points(ps: &[G1Affine]) -> Self {
+        Token::Array(ps.iter().map(Self::point).collect())
+    }
+
+    /// The `Transcript` that resumes `transcript`.
+    pub fn transcript(transcript: &ProofTranscript) -> Self {
+        let (state, n_rounds) = transcript.state();
+        Token::Tuple(vec![Token::Word(state), Self::uint(n_rounds as u64)])
+    }
+
+    /// A sumcheck proof as its compressed round polynomials.
+    pub fn sumcheck(proof: &SumcheckInstanceProof<Fr>) -> Self {
+        Token::Array(
+            proof
+                .compressed_polys
+                .iter()
+                .map(|poly| Self::scalars(&poly.coeffs_except_linear_term))
+                .collect(),
+        )
+    }
+
+    pub fn hyperkzg_proof(proof: &HyperKZGProof<Bn254>) -> Self {
+        Token::Tuple(vec![
+            Self::points(&proof.com),
+            Self::points(&proof.w),
+            Token::Array(proof.v.iter().map(|v| Self::scalars(v)).collect()),
+        ])
+    }
+
+    /// A `GrandProductLayerProof[]`. Only proofs without a Quarks proof can be encoded.
+    pub fn grand_product_proof(proof: &BatchedGrandProductProof<HyperKZG<Bn254>>) -> Self {
+        assert!(proof.quark_proof.is_none());
+        Token::Array(
+            proof
+                .layers
+                .iter()
+                .map(|layer| {
+                    Token::Tuple(vec![
+                        Self::sumcheck(&layer.proof),
+                        Self::scalars(&layer.left_claims),
+                        Self::scalars(&layer.right_claims),
+                    ])
+                })
+                .collect(),
+        )
+    }
+
+    pub fn spartan_proof(proof: &UniformSpartanProof<Fr, HyperKZG<Bn254>>) -> Self {
+        let (claim_a, claim_b, claim_c) = &proof.outer_sumcheck_claims;
+        Token::Tuple(vec![
+            Self::sumcheck(&proof.outer_sumcheck_proof),
+            Token::Tuple(vec![
+                Self::scalar(claim_a),
+                Self::scalar(claim_b),
+                Self::scalar(claim_c),
+            ]),
+            Self::sumcheck(&proof.inner_sumcheck_proof),
+            Self::scalars(&proof.claimed_witness_evals),
+            Self::hyperkzg_proof(&proof.opening_proof),
+        ])
+    }
+
+    fn big_endian(bytes: Vec<u8>) -> Self {
+        let mut word = [0u8; 32];
+        word[32 - bytes.len()..].copy_from_slice(&bytes);
+        Token::Word(word)
+    }
+
+    fn is_dynamic(&self) -> bool {
+        match self {
+            Token::Word(_) => false,
+            Token::Tuple(items) => items.iter().any(Token::is_dynamic),
+            Token::Array(_) => true,
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            Token::Word(word) => word.to_vec(),
+            Token::Tuple(items) => encode_sequence(items),
+            Token::Array(items) => {
+                let mut out = word(items.len());
+                out.extend(encode_sequence(items));
+                out
+            }
+        }
+    }
+}
+
+/// Encodes `items` as a tuple: static items in place, followed by the dynamic items, which are
+/// referenced by their offsets.
+fn encode_sequence(items: &[Token]) -> Vec<u8> {
+    let encodings: Vec<Vec<u8>> = items.iter().map(Token::encode).collect();
+    let head_len: usize = items
+        .iter()
+        .zip(encodings.iter())
+        .map(|(item, encoding)| {
+            if item.is_dynamic() {
+                32
+            } else {
+                encoding.len()
+            }
+        })
+        .sum();
+
+    let mut head = Vec::with_capacity(head_len);
+    let mut tail = Vec::new();
+    for (item, encoding) in items.iter().zip(encodings) {
+        if item.is_dynamic() {
+            head.extend(word(head_len + tail.len()));
+            tail.extend(encoding);
+        } else {
+            head.extend(encoding);
+        }
+    }
+    head.extend(tail);
+    head
+}
+
+fn word(x: usize) -> Vec<u8> {
+    let mut word = vec![0u8; 32];
+    word[24..].copy_from_slice(&(x as u64).to_be_bytes());
+    word
+}
+
+/// Calldata calling the function with the canonical signature `signature` on `args`.
+pub fn encode_call(signature: &str, args: &[Token]) -> Vec<u8> {
+    let selector = Keccak256::digest(signature.as_bytes());
+    let mut calldata = selector[..4].to_vec();
+    calldata.extend(encode_sequence(args));
+    calldata
+}
+
+/// Calldata for `verifyHyperKZG`.
+pub fn verify_hyperkzg_call(
+    transcript: &ProofTranscript,
+    commitment: &HyperKZGCommitment<Bn254>,
+    point: &[Fr],
+    eval: &Fr,
+    proof: &HyperKZGProof<Bn254>,
+) -> Vec<u8> {
+    let signature = format!(
+        "verifyHyperKZG({},{},uint256[],uint256,({1}[],{1}[],uint256[][]))",
+        TRANSCRIPT, G1_POINT
+    );
+    encode_call(
+        &signature,
+        &[
+            Token::transcript(transcript),
+            Token::point(&commitment.0),
+            Token::scalars(point),
+            Token::scalar(eval),
+            Token::hyperkzg_proof(proof),
+        ],
+    )
+}
+
+/// Calldata for `verifyGrandProduct`.
+pub fn verify_grand_product_call(
+    transcript: &ProofTranscript,
+    proof: &BatchedGrandProductProof<HyperKZG<Bn254>>,
+    claims: &[Fr],
+) -> Vec<u8> {
+    let signature = format!(
+        "verifyGrandProduct({},(uint256[][],uint256[],uint256[])[],uint256[])",
+        TRANSCRIPT
+    );
+    encode_call(
+        &signature,
+        &[
+            Token::transcript(transcript),
+            Token::grand_product_proof(proof),
+            Token::scalars(claims),
+        ],
+    )
+}
+
+/// Calldata for `verifyR1CS`.
+pub fn verify_r1cs_call(
+    transcript: &ProofTranscript,
+    proof: &UniformSpartanProof<Fr, HyperKZG<Bn254>>,
+    witness_commitments: &[&HyperKZGCommitment<Bn254>],
+) -> Vec<u8> {
+    let signature = format!(
+        "verifyR1CS({},(uint256[][],uint256[3],uint256[][],uint256[],({1}[],{1}[],uint256[][])),{1}[])",
+        TRANSCRIPT, G1_POINT
+    );
+    let commitments: Vec<G1Affine> = witness_commitments.iter().map(|c| c.0).collect();
+    encode_call(
+        &signature,
+        &[
+            Token::transcript(transcript),
+            Token::spartan_proof(proof),
+            Token::points(&commitments),
+        ],
+    )
+}
+
+/// The word at `index` of `data`.
+pub fn decode_word(data: &[u8], index: usize) -> Option<[u8; 32]> {
+    data.get(32 * index..32 * (index + 1))?.try_into().ok()
+}
+
+/// A returned `Transcript` starting at word `index`, as its state and round count.
+pub fn decode_transcript(data: &[u8], index: usize) -> Option<([u8; 32], u32)> {
+    let state = decode_word(data, index)?;
+    let n_rounds = decode_word(data, index + 1)?;
+    Some((
+        state,
+        u32::from_be_bytes(n_rounds[28..].try_into().unwrap()),
+    ))
+}
+
+/// A returned `uint256[]` whose offset is the word at `index`.
+pub fn decode_scalars(data: &[u8], index: usize) -> Option<Vec<Fr>> {
+    let offset = usize::try_from(u64::from_be_bytes(
+        decode_word(data, index)?[24..].try_into().unwrap(),
+    ))
+    .ok()?;
+    let len = u64::from_be_bytes(data.get(offset + 24..offset + 32)?.try_into().unwrap());
+    (0..len as usize)
+        .map(|i| {
+            let start = offset + 32 * (i + 1);
+            Some(Fr::from_be_bytes_mod_order(data.get(start..start + 32)?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_nested_arrays() {
+        // The `g(uint256[][],string[])` example of the Solidity ABI specification, without the
+        // strings.
+        let arg = Token::Array(vec![
+            Token::Array(vec![Token::uint(1), Token::uint(2)]),
+            Token::Array(vec![Token::uint(3)]),
+        ]);
+        let calldata = encode_call("f(uint256[][])", &[arg]);
+        let words: Vec<u64> = calldata[4..]
+            .chunks(32)
+            .map(|word| u64::from_be_bytes(word[24..].try_into().unwrap()))
+            .collect();
+        assert_eq!(words, vec![0x20, 2, 0x40, 0xa0, 2, 1, 2, 1, 3]);
+
+        let selector = Keccak256::digest(b"f(uint256[][])");
+        assert_eq!(calldata[..4], selector[..4]);
+    }
+
+    #[test]
+    fn encodes_static_tuples_in_place() {
+        let point = Token::Tuple(vec![Token::uint(1), Token::uint(2)]);
+        let args = [point, Token::Array(vec![Token::uint(3)])];
+        let encoded = encode_sequence(&args);
+        let words: Vec<u64> = encoded
+            .chunks(32)
+            .map(|word| u64::from_be_bytes(word[24..].try_into().unwrap()))
+            .collect();
+        assert_eq!(words, vec![1, 2, 0x60, 1, 3]);
+
+        let decoded = decode_scalars(&encoded, 2).unwrap();
+        assert_eq!(decoded, vec![Fr::from(3u64)]);
+    }
+}
@@ -0,0 +1,308 @@
+//! Runs the generated verifier in a local EVM, using `solc` to compile it and go-ethereum's
+//! `evm` tool to execute it. Their paths can be set with `JOLT_SOLC_PATH` and `JOLT_EVM_PATH`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ark_bn254::{Bn254, Fr};
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{abi, generate_subprotocol_verifier, CONTRACT_NAME};
+use crate::jolt::vm::{Jolt, JoltCommitments, JoltProof, JoltVerifierKey};
+use crate::poly::commitment::hyperkzg::HyperKZG;
+use crate::r1cs::inputs::R1CSProof;
+use crate::utils::errors::ProofVerifyError;
+
+/// Environment variable overriding the `solc` binary.
+pub const SOLC_PATH_ENV: &str = "JOLT_SOLC_PATH";
+/// Environment variable overriding the go-ethereum `evm` binary.
+pub const EVM_PATH_ENV: &str = "JOLT_EVM_PATH";
+
+#[derive(Error, Debug)]
+pub enum EvmError {
+    #[error("Failed to run {0}: {1}")]
+    Tool(String, std::io::Error),
+    #[error("Compilation failed: {0}")]
+    Compile(String),
+    #[error("Execution reverted: {0}")]
+    Reverted(String),
+    #[error("Unexpected output: {0}")]
+    Output(String),
+    #[error("Off-chain verification failed: {0}")]
+    OffChain(#[from] ProofVerifyError),
+}
+
+fn solc() -> String {
+    std::env::var(SOLC_PATH_ENV).unwrap_or_else(|_| "solc".to_string())
+}
+
+fn evm() -> String {
+    std::env::var(EVM_PATH_ENV).unwrap_or_else(|_| "evm".to_string())
+}
+
+/// Compiles the source of a generated verifier to the runtime bytecode of `SubprotocolVerifier`.
+pub fn compile(source: &str) -> Result<Vec<u8>, EvmError> {
+    let mut child = Command::new(solc())
+        .args([
+            "--combined-json",
+            "bin-runtime",
+            "--optimize",
+            "--via-ir",
+            "-",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| EvmError::Tool(solc(), err))?;
+    {
+        use std::io::Write;
+        let mut stdin = child.stdin.take().unwrap();
+        stdin
+            .write_all(source.as_bytes())
+            .map_err(|err| EvmError::Tool(solc(), err))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|err| EvmError::Tool(solc(), err))?;
+    if !output.status.success() {
+        return Err(EvmError::Compile(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+
+    let json: Value =
+        serde_json::from_slice(&output.stdout).map_err(|err| EvmError::Output(err.to_string()))?;
+    let suffix = format!(":{}", CONTRACT_NAME);
+    json["contracts"]
+        .as_object()
+        .and_then(|contracts| {
+            contracts
+                .iter()
+                .find(|(name, _)| name.ends_with(&suffix))
+                .and_then(|(_, contract)| contract["bin-runtime"].as_str())
+        })
+        .and_then(from_hex)
+        .ok_or_else(|| EvmError::Output(format!("no bytecode for {}", CONTRACT_NAME)))
+}
+
+/// Calls `code` with `calldata`, returning the data it returns.
+pub fn call(code: &[u8], calldata: &[u8]) -> Result<Vec<u8>, EvmError> {
+    let code_file = ScratchFile::new("code", &to_hex(code))?;
+    let input_file = ScratchFile::new("input", &to_hex(calldata))?;
+    let output = Command::new(evm())
+        .arg("--codefile")
+        .arg(&code_file.0)
+        .arg("--inputfile")
+        .arg(&input_file.0)
+        .arg("run")
+        .output()
+        .map_err(|err| EvmError::Tool(evm(), err))?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() || stderr.contains("error") {
+        return Err(EvmError::Reverted(stderr.trim().to_string()));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix("0x").and_then(from_hex))
+        .ok_or_else(|| EvmError::Output(stdout.into_owned()))
+}
+
+/// Verifies a Jolt proof with the instruction lookup, bytecode and memory checking arguments
+/// checked off-chain, and the R1CS proof checked in the EVM by the generated verifier. The
+/// transcript state and witness commitments passed to the contract are the ones this function
+/// derived itself while verifying the rest of the proof.
+pub fn verify_jolt_proof<const C: usize, const M: usize, J>(
+    mut verifier_key: JoltVerifierKey<Fr, HyperKZG<Bn254>>,
+    proof: JoltProof<C, M, Fr, HyperKZG<Bn254>, J::InstructionSet, J::Subtables>,
+    commitments: JoltCommitments<HyperKZG<Bn254>>,
+) -> Result<(), EvmError>
+where
+    J: Jolt<Fr, HyperKZG<Bn254>, C, M>,
+{
+    J::check_complete_execution(&proof, &commitments)?;
+    let (r1cs_proof, commitments, transcript) =
+        J::verify_until_r1cs(&mut verifier_key, proof, commitments, None)?;
+
+    let code = compile(&generate_subprotocol_verifier(
+        &verifier_key,
+        &r1cs_proof.key,
+    ))?;
+    let witness_commitments = R1CSProof::format_commitments(&commitments, C);
+    let calldata = abi::verify_r1cs_call(&transcript, &r1cs_proof.proof, &witness_commitments);
+    call(&code, &calldata).map(|_| ())
+}
+
+/// A file in the temporary directory, removed on drop.
+struct ScratchFile(PathBuf);
+
+impl ScratchFile {
+    fn new(name: &str, contents: &str) -> Result<Self, EvmError> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "jolt-evm-{}-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+            name
+        ));
+        fs::write(&path, contents).map_err(|err| EvmError::Tool(path_name(&path), err))?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for ScratchFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn path_name(path: &Path) -> String {
+    path.display().to_string()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::commitment::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
+    use crate::poly::commitment::hyperkzg::{HyperKZGProverKey, HyperKZGVerifierKey};
+    use crate::poly::dense_mlpoly::DensePolynomial;
+    use crate::r1cs::spartan::UniformSpartanProof;
+    use crate::r1cs::test::{simp_test_builder_key, SimpTestIn};
+    use crate::subprotocols::grand_product::{BatchedDenseGrandProduct, BatchedGrandProduct};
+    use crate::utils::transcript::ProofTranscript;
+    use ark_std::{test_rng, One, UniformRand};
+
+    fn setup() -> (HyperKZGProverKey<Bn254>, HyperKZGVerifierKey<Bn254>) {
+        HyperKZG::<Bn254>::seeded_setup(&[CommitShape::new(1 << 4, BatchType::Small)])
+    }
+
+    /// Compiles a verifier for `vk` and the R1CS of `simp_test_builder_key`. The R1CS verifier
+    /// is also tested against proofs of the examples in `rv32i_vm`.
+    fn deploy(vk: &HyperKZGVerifierKey<Bn254>) -> Vec<u8> {
+        let (_, spartan_key) = simp_test_builder_key();
+        compile(&crate::solidity::render(vk, &spartan_key)).unwrap()
+    }
+
+    #[test]
+    #[ignore = "requires solc and go-ethereum's evm"]
+    fn hyperkzg_evm() {
+        let (pk, vk) = setup();
+        let code = deploy(&vk);
+
+        let mut rng = test_rng();
+        let poly = DensePolynomial::new((0..1 << 4).map(|_| Fr::rand(&mut rng)).collect());
+        let point: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+        let eval = poly.evaluate(&point);
+        let commitment = HyperKZG::commit(&pk, &poly).unwrap();
+        let mut transcript = ProofTranscript::new(b"TestEval");
+        let proof = HyperKZG::open(&pk, &poly, &point, &eval, &mut transcript).unwrap();
+
+        let mut transcript = ProofTranscript::new(b"TestEval");
+        let calldata = abi::verify_hyperkzg_call(&transcript, &commitment, &point, &eval, &proof);
+        HyperKZG::verify(&vk, &commitment, &point, &eval, &proof, &mut transcript).unwrap();
+        let output = call(&code, &calldata).unwrap();
+        assert_eq!(abi::decode_transcript(&output, 0), Some(transcript.state()));
+
+        let transcript = ProofTranscript::new(b"TestEval");
+        let wrong_eval = eval + Fr::one();
+        let calldata =
+            abi::verify_hyperkzg_call(&transcript, &commitment, &point, &wrong_eval, &proof);
+        assert!(matches!(call(&code, &calldata), Err(EvmError::Reverted(_))));
+    }
+
+    #[test]
+    #[ignore = "requires solc and go-ethereum's evm"]
+    fn grand_product_evm() {
+        let (_, vk) = setup();
+        let code = deploy(&vk);
+
+        let mut rng = test_rng();
+        let leaves: Vec<Vec<Fr>> = (0..2)
+            .map(|_| (0..1 << 4).map(|_| Fr::rand(&mut rng)).collect())
+            .collect();
+        type GrandProduct = BatchedDenseGrandProduct<Fr>;
+        let mut circuit =
+            <GrandProduct as BatchedGrandProduct<Fr, HyperKZG<Bn254>>>::construct(leaves);
+        let claims = <GrandProduct as BatchedGrandProduct<Fr, HyperKZG<Bn254>>>::claims(&circuit);
+        let mut transcript = ProofTranscript::new(b"test_transcript");
+        let (proof, _) =
+            <GrandProduct as BatchedGrandProduct<Fr, HyperKZG<Bn254>>>::prove_grand_product(
+                &mut circuit,
+                &mut transcript,
+                None,
+            );
+
+        let mut transcript = ProofTranscript::new(b"test_transcript");
+        let calldata = abi::verify_grand_product_call(&transcript, &proof, &claims);
+        let (leaf_claims, r) =
+            GrandProduct::verify_grand_product(&proof, &claims, &mut transcript, None);
+        let output = call(&code, &calldata).unwrap();
+        assert_eq!(abi::decode_transcript(&output, 0), Some(transcript.state()));
+        assert_eq!(abi::decode_scalars(&output, 2), Some(leaf_claims));
+        assert_eq!(abi::decode_scalars(&output, 3), Some(r));
+    }
+
+    #[test]
+    #[ignore = "requires solc and go-ethereum's evm"]
+    fn spartan_evm() {
+        let setup = setup();
+        let code = deploy(&setup.1);
+
+        let (builder, key) = simp_test_builder_key();
+        let witness_segments: Vec<Vec<Fr>> = vec![
+            vec![Fr::one(), Fr::from(5), Fr::from(9), Fr::from(13)], /* Q */
+            vec![Fr::one(), Fr::from(5), Fr::from(9), Fr::from(13)], /* R */
+            vec![Fr::one(), Fr::from(5), Fr::from(9), Fr::from(13)], /* S */
+        ];
+        let witness_segments_ref: Vec<&[Fr]> = witness_segments
+            .iter()
+            .map(|segment| segment.as_slice())
+            .collect();
+        let commitments = HyperKZG::batch_commit(&witness_segments_ref, &setup, BatchType::Small);
+        let commitments_ref: Vec<&_> = commitments.iter().collect();
+
+        let mut transcript = ProofTranscript::new(b"stuff");
+        let mut proof =
+            UniformSpartanProof::<Fr, HyperKZG<Bn254>>::prove_precommitted::<SimpTestIn>(
+                &setup,
+                builder,
+                &key,
+                witness_segments,
+                &mut transcript,
+            )
+            .unwrap();
+
+        let mut transcript = ProofTranscript::new(b"stuff");
+        let calldata = abi::verify_r1cs_call(&transcript, &proof, &commitments_ref);
+        proof
+            .verify_precommitted(&key, commitments_ref.clone(), &setup.1, &mut transcript)
+            .unwrap();
+        let output = call(&code, &calldata).unwrap();
+        assert_eq!(abi::decode_transcript(&output, 0), Some(transcript.state()));
+
+        proof.claimed_witness_evals[0] += Fr::one();
+        let transcript = ProofTranscript::new(b"stuff");
+        let calldata = abi::verify_r1cs_call(&transcript, &proof, &commitments_ref);
+        assert!(matches!(call(&code, &calldata), Err(EvmError::Reverted(_))));
+    }
+}
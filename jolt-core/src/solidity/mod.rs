@@ -0,0 +1,227 @@
+//! Generation of Solidity verifiers for the subprotocols of proofs committed with
+//! `HyperKZG<Bn254>`.
+//!
+//! `ProofTranscript` hashes 32-byte words with Keccak256, so it can be replayed on the EVM.
+//! The generated `SubprotocolVerifier` contract ports the transcript, sumcheck, batched grand
+//! product and HyperKZG verifiers, and verifies the Spartan proof of a Jolt proof's R1CS, with
+//! the HyperKZG verifier key and the R1CS matrices compiled in as constants. Each of its
+//! functions resumes a transcript from a given state and returns the state it ends in, so that
+//! a proof can be verified partly off-chain and partly on-chain.
+//!
+//! The contract is not a verifier of Jolt proofs. The instruction lookup, bytecode and memory
+//! checking arguments are not ported, and the transcript state and commitments it resumes from
+//! are taken from the caller rather than derived from the proof and the program's I/O. It only
+//! proves anything to a caller that derived them itself by verifying the rest of the proof, as
+//! `evm::verify_jolt_proof` does, so it must not be deployed as an on-chain verifier.
+
+use ark_bn254::{Bn254, Fq, Fr, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use ark_std::Zero;
+use std::fmt::Write;
+
+use crate::jolt::vm::JoltVerifierKey;
+use crate::poly::commitment::hyperkzg::{HyperKZG, HyperKZGVerifierKey};
+use crate::r1cs::key::{SparseConstraints, SparseEqualityItem, UniformSpartanKey};
+use crate::utils::math::Math;
+
+pub mod abi;
+#[cfg(feature = "host")]
+pub mod evm;
+
+const LICENSE_AND_PRAGMA: &str = "// SPDX-License-Identifier: MIT\npragma solidity ^0.8.20;\n";
+const LIBRARIES: &str = include_str!("templates/Libraries.sol");
+const CONTRACT: &str = include_str!("templates/SubprotocolVerifier.sol");
+
+/// Name of the generated contract.
+pub const CONTRACT_NAME: &str = "SubprotocolVerifier";
+
+/// Generates the Solidity source of a `SubprotocolVerifier` contract for `verifier_key`,
+/// verifying R1CS proofs against `spartan_key`.
+///
+/// The Spartan key depends on the padded trace length, so a contract only verifies proofs of
+/// traces padded to the same length. The key can be computed with
+/// `UniformSpartanProof::setup_precommitted`, or taken from `R1CSProof::key`.
+pub fn generate_subprotocol_verifier(
+    verifier_key: &JoltVerifierKey<Fr, HyperKZG<Bn254>>,
+    spartan_key: &UniformSpartanKey<Fr>,
+) -> String {
+    render(&verifier_key.generators, spartan_key)
+}
+
+fn render(vk: &HyperKZGVerifierKey<Bn254>, spartan_key: &UniformSpartanKey<Fr>) -> String {
+    let kzg_vk = &vk.kzg_vk;
+    let [g2_x_c1, g2_x_c0, g2_y_c1, g2_y_c0] = g2_words(&kzg_vk.g2);
+    let [beta_x_c1, beta_x_c0, beta_y_c1, beta_y_c0] = g2_words(&kzg_vk.beta_g2);
+    let (g1_x, g1_y) = kzg_vk.g1.xy().expect("G1 generator is not the identity");
+
+    let num_rounds_x = spartan_key.num_rows_total().log_2();
+    let num_rounds_y = spartan_key.num_cols_total().log_2();
+    let var_bits = spartan_key
+        .uniform_r1cs
+        .num_vars
+        .next_power_of_two()
+        .log_2();
+    let constraint_bits = spartan_key
+        .num_constraint_rows()
+        .next_power_of_two()
+        .log_2();
+    let step_bits = spartan_key.num_steps.log_2();
+    assert_eq!(num_rounds_x, constraint_bits + step_bits);
+    assert_eq!(num_rounds_y, var_bits + 1 + step_bits);
+
+    let substitutions = [
+        ("G1_X", fq(g1_x)),
+        ("G1_Y", fq(g1_y)),
+        ("G2_X_C1", g2_x_c1),
+        ("G2_X_C0", g2_x_c0),
+        ("G2_Y_C1", g2_y_c1),
+        ("G2_Y_C0", g2_y_c0),
+        ("BETA_G2_X_C1", beta_x_c1),
+        ("BETA_G2_X_C0", beta_x_c0),
+        ("BETA_G2_Y_C1", beta_y_c1),
+        ("BETA_G2_Y_C0", beta_y_c0),
+        ("NUM_ROUNDS_X", num_rounds_x.to_string()),
+        ("NUM_ROUNDS_Y", num_rounds_y.to_string()),
+        ("NUM_VARS", spartan_key.uniform_r1cs.num_vars.to_string()),
+        ("VAR_BITS", var_bits.to_string()),
+        ("CONSTRAINT_BITS", constraint_bits.to_string()),
+        ("STEP_BITS", step_bits.to_string()),
+        ("UNIFORM_A", uniform_matrix(&spartan_key.uniform_r1cs.a)),
+        ("UNIFORM_B", uniform_matrix(&spartan_key.uniform_r1cs.b)),
+        ("UNIFORM_C", uniform_matrix(&spartan_key.uniform_r1cs.c)),
+        ("NON_UNIFORM", non_uniform_constraints(spartan_key)),
+    ];
+    let mut contract = CONTRACT.to_string();
+    for (name, value) in substitutions {
+        contract = contract.replace(&format!("{{{{{}}}}}", name), &value);
+    }
+    debug_assert!(!contract.contains("{{"));
+
+    format!("{}\n{}\n{}", LICENSE_AND_PRAGMA, LIBRARIES, contract)
+}
+
+fn fq(x: &Fq) -> String {
+    x.into_bigint().to_string()
+}
+
+fn fr(x: &Fr) -> String {
+    x.into_bigint().to_string()
+}
+
+/// The coordinates of `p` in the order of the pairing precompile: [x.c1, x.c0, y.c1, y.c0].
+fn g2_words(p: &G2Affine) -> [String; 4] {
+    let (x, y) = p
+        .xy()
+        .expect("G2 element of the verifier key is the identity");
+    [fq(&x.c1), fq(&x.c0), fq(&y.c1), fq(&y.c0)]
+}
+
+/// `mulmod(a, coeff)`, skipping the multiplication by one.
+fn times_coeff(a: String, coeff: &Fr) -> String {
+    if *coeff == Fr::from(1u64) {
+        a
+    } else {
+        format!("mulmod({}, {}, R_MOD)", a, fr(coeff))
+    }
+}
+
+/// Statements accumulating a uniform matrix's variable and constant terms into `r[0]` and
+/// `r[1]`.
+fn uniform_matrix(constraints: &SparseConstraints<Fr>) -> String {
+    let mut body = String::new();
+    for (row, col, coeff) in constraints.vars.iter() {
+        let term = times_coeff(
+            format!("mulmod(eqConstraint[{}], eqVar[{}], R_MOD)", row, col),
+            coeff,
+        );
+        writeln!(body, "        r[0] = addmod(r[0], {}, R_MOD);", term).unwrap();
+    }
+    for (row, coeff) in constraints.consts.iter() {
+        let term = times_coeff(format!("eqConstraint[{}]", row), coeff);
+        writeln!(body, "        r[1] = addmod(r[1], {}, R_MOD);", term).unwrap();
+    }
+    body
+}
+
+/// Statements accumulating the non-uniform constraints' contributions to A and B into `a` and
+/// `b`.
+fn non_uniform_constraints(spartan_key: &UniformSpartanKey<Fr>) -> String {
+    let first_row = spartan_key.uniform_r1cs.num_rows;
+    let mut body = String::new();
+    for (i, constraint) in spartan_key.offset_eq_r1cs.constraints.iter().enumerate() {
+        writeln!(body, "        row = ctx.eqConstraint[{}];", first_row + i).unwrap();
+        for (matrix, item) in [("a", &constraint.eq), ("b", &constraint.condition)] {
+            body.push_str(&equality_item(item));
+            writeln!(
+                body,
+                "        {0} = addmod({0}, mulmod(row, item, R_MOD), R_MOD);",
+                matrix
+            )
+            .unwrap();
+        }
+    }
+    body
+}
+
+/// Statements evaluating `item` into `item`.
+fn equality_item(item: &SparseEqualityItem<Fr>) -> String {
+    let mut body = String::from("        item = 0;\n");
+    for (col, offset, coeff) in item.offset_vars.iter() {
+        let step = if *offset {
+            "ctx.eqStepPlusOne"
+        } else {
+            "ctx.eqStep"
+        };
+        let term = times_coeff(
+            format!("mulmod(ctx.eqVar[{}], {}, R_MOD)", col, step),
+            coeff,
+        );
+        writeln!(body, "        item = addmod(item, {}, R_MOD);", term).unwrap();
+    }
+    if !item.constant.is_zero() {
+        let term = times_coeff("ctx.eqConstantColumn".to_string(), &item.constant);
+        writeln!(body, "        item = addmod(item, {}, R_MOD);", term).unwrap();
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::commitment::commitment_scheme::{BatchType, CommitShape, CommitmentScheme};
+    use crate::r1cs::test::simp_test_builder_key;
+
+    #[test]
+    fn verifier_embeds_keys() {
+        let setup = HyperKZG::<Bn254>::seeded_setup(&[CommitShape::new(1 << 4, BatchType::Small)]);
+        let vk = HyperKZG::<Bn254>::verifier_setup(&setup);
+        let (_, spartan_key) = simp_test_builder_key();
+        let source = render(&vk, &spartan_key);
+
+        let (g1_x, g1_y) = vk.kzg_vk.g1.xy().unwrap();
+        assert!(source.contains(&format!("G1_X = {};", fq(g1_x))));
+        assert!(source.contains(&format!("G1_Y = {};", fq(g1_y))));
+        let beta = g2_words(&vk.kzg_vk.beta_g2);
+        assert!(source.contains(&format!("BETA_G2_X_C1 = {};", beta[0])));
+        assert!(source.contains(&format!("BETA_G2_Y_C0 = {};", beta[3])));
+        assert!(source.contains(&format!(
+            "NUM_VARS = {};",
+            spartan_key.uniform_r1cs.num_vars
+        )));
+        assert!(!source.contains("{{"));
+
+        // One statement per non-zero entry of the uniform matrices.
+        let entries = [
+            &spartan_key.uniform_r1cs.a,
+            &spartan_key.uniform_r1cs.b,
+            &spartan_key.uniform_r1cs.c,
+        ]
+        .iter()
+        .map(|m| m.vars.len() + m.consts.len())
+        .sum::<usize>();
+        let statements =
+            source.matches("        r[0] = ").count() + source.matches("        r[1] = ").count();
+        assert_eq!(statements, entries);
+    }
+}
@@ -0,0 +1,486 @@
+uint256 constant R_MOD = 21888242871839275222246405745257275088548364400416034343698204186575808495617;
+uint256 constant Q_MOD = 21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+error InvalidScalar();
+error InvalidProofShape();
+error InvalidSumcheckProof();
+error InvalidGrandProductProof();
+error InvalidR1CSProof();
+error InvalidOpeningProof();
+error PrecompileFailed();
+
+/// The state of a `ProofTranscript`.
+struct Transcript {
+    bytes32 state;
+    uint256 nRounds;
+}
+
+/// An affine BN254 G1 point, with the point at infinity encoded as (0, 0).
+struct G1Point {
+    uint256 x;
+    uint256 y;
+}
+
+struct KZGVerifierKey {
+    G1Point g1;
+    // G2 points in precompile order: [x.c1, x.c0, y.c1, y.c0].
+    uint256[4] g2;
+    uint256[4] betaG2;
+}
+
+struct HyperKZGProof {
+    G1Point[] com;
+    G1Point[] w;
+    uint256[][] v;
+}
+
+struct GrandProductLayerProof {
+    uint256[][] sumcheck;
+    uint256[] leftClaims;
+    uint256[] rightClaims;
+}
+
+library Fr {
+    function sub(uint256 a, uint256 b) internal pure returns (uint256) {
+        return addmod(a, R_MOD - (b % R_MOD), R_MOD);
+    }
+}
+
+library G1 {
+    function add(G1Point memory a, G1Point memory b) internal view returns (G1Point memory c) {
+        uint256[4] memory input = [a.x, a.y, b.x, b.y];
+        bool ok;
+        assembly {
+            ok := staticcall(gas(), 0x06, input, 0x80, c, 0x40)
+        }
+        if (!ok) revert PrecompileFailed();
+    }
+
+    function mul(G1Point memory a, uint256 s) internal view returns (G1Point memory c) {
+        uint256[3] memory input = [a.x, a.y, s];
+        bool ok;
+        assembly {
+            ok := staticcall(gas(), 0x07, input, 0x60, c, 0x40)
+        }
+        if (!ok) revert PrecompileFailed();
+    }
+
+    function neg(G1Point memory a) internal pure returns (G1Point memory) {
+        return G1Point(a.x, (Q_MOD - a.y) % Q_MOD);
+    }
+
+    function isZero(G1Point memory a) internal pure returns (bool) {
+        return a.x == 0 && a.y == 0;
+    }
+
+    /// The arkworks compressed serialization of `a`, read as a little-endian integer: the
+    /// x-coordinate with the infinity and sign-of-y flags in its two top bits.
+    function compressed(G1Point memory a) internal pure returns (uint256) {
+        if (isZero(a)) {
+            return 1 << 254;
+        }
+        return a.y > (Q_MOD - 1) / 2 ? a.x | (1 << 255) : a.x;
+    }
+
+    /// Checks that e(a1, b1) * e(a2, b2) = 1.
+    function pairingCheck(G1Point memory a1, uint256[4] memory b1, G1Point memory a2, uint256[4] memory b2)
+        internal
+        view
+        returns (bool)
+    {
+        uint256[12] memory input =
+            [a1.x, a1.y, b1[0], b1[1], b1[2], b1[3], a2.x, a2.y, b2[0], b2[1], b2[2], b2[3]];
+        uint256[1] memory out;
+        bool ok;
+        assembly {
+            ok := staticcall(gas(), 0x08, input, 0x180, out, 0x20)
+        }
+        if (!ok) revert PrecompileFailed();
+        return out[0] == 1;
+    }
+}
+
+/// A port of `ProofTranscript`: Keccak256 over a running state, the round count and the
+/// message, with scalars and points in their little-endian arkworks serialization.
+library TranscriptLib {
+    bytes32 internal constant BEGIN_VECTOR = 0x00000000000000000000000000626567696e5f617070656e645f766563746f72;
+    bytes32 internal constant END_VECTOR = 0x000000000000000000000000000000656e645f617070656e645f766563746f72;
+    bytes32 internal constant UNIPOLY_BEGIN = 0x00000000000000000000000000000000000000556e69506f6c795f626567696e;
+    bytes32 internal constant UNIPOLY_END = 0x000000000000000000000000000000000000000000556e69506f6c795f656e64;
+
+    /// Appends a message, left-padded to 32 bytes.
+    function appendMessage(Transcript memory t, bytes32 message) internal pure {
+        absorb(t, abi.encodePacked(message));
+    }
+
+    function appendBytes(Transcript memory t, bytes memory data) internal pure {
+        absorb(t, data);
+    }
+
+    function appendU64(Transcript memory t, uint64 x) internal pure {
+        absorb(t, abi.encodePacked(reverse64(x)));
+    }
+
+    function appendScalar(Transcript memory t, uint256 x) internal pure {
+        if (x >= R_MOD) revert InvalidScalar();
+        absorb(t, abi.encodePacked(reverse256(x)));
+    }
+
+    function appendScalars(Transcript memory t, uint256[] memory xs) internal pure {
+        appendMessage(t, BEGIN_VECTOR);
+        for (uint256 i = 0; i < xs.length; i++) {
+            appendScalar(t, xs[i]);
+        }
+        appendMessage(t, END_VECTOR);
+    }
+
+    function appendPoint(Transcript memory t, G1Point memory p) internal pure {
+        absorb(t, abi.encodePacked(reverse256(G1.compressed(p))));
+    }
+
+    function appendPoints(Transcript memory t, G1Point[] memory ps) internal pure {
+        appendMessage(t, BEGIN_VECTOR);
+        for (uint256 i = 0; i < ps.length; i++) {
+            appendPoint(t, ps[i]);
+        }
+        appendMessage(t, END_VECTOR);
+    }
+
+    function challengeScalar(Transcript memory t) internal pure returns (uint256) {
+        bytes32 rand = keccak256(abi.encodePacked(t.state, bytes28(0), reverse32(uint32(t.nRounds))));
+        t.state = rand;
+        t.nRounds += 1;
+        return reverse256(uint256(rand)) % R_MOD;
+    }
+
+    function challengeVector(Transcript memory t, uint256 len) internal pure returns (uint256[] memory r) {
+        r = new uint256[](len);
+        for (uint256 i = 0; i < len; i++) {
+            r[i] = challengeScalar(t);
+        }
+    }
+
+    /// Returns (1, q, q^2, ..., q^(len - 1)) for a fresh challenge q.
+    function challengePowers(Transcript memory t, uint256 len) internal pure returns (uint256[] memory powers) {
+        uint256 q = challengeScalar(t);
+        powers = new uint256[](len);
+        if (len > 0) {
+            powers[0] = 1;
+        }
+        for (uint256 i = 1; i < len; i++) {
+            powers[i] = mulmod(powers[i - 1], q, R_MOD);
+        }
+    }
+
+    function absorb(Transcript memory t, bytes memory data) private pure {
+        t.state = keccak256(abi.encodePacked(t.state, bytes28(0), reverse32(uint32(t.nRounds)), data));
+        t.nRounds += 1;
+    }
+
+    function reverse32(uint32 v) private pure returns (uint32) {
+        v = ((v >> 8) & 0x00FF00FF) | ((v & 0x00FF00FF) << 8);
+        return (v >> 16) | (v << 16);
+    }
+
+    function reverse64(uint64 v) private pure returns (uint64) {
+        v = ((v >> 8) & 0x00FF00FF00FF00FF) | ((v & 0x00FF00FF00FF00FF) << 8);
+        v = ((v >> 16) & 0x0000FFFF0000FFFF) | ((v & 0x0000FFFF0000FFFF) << 16);
+        return (v >> 32) | (v << 32);
+    }
+
+    function reverse256(uint256 v) private pure returns (uint256) {
+        v = ((v >> 8) & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF)
+            | ((v & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF) << 8);
+        v = ((v >> 16) & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF)
+            | ((v & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF) << 16);
+        v = ((v >> 32) & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF)
+            | ((v & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF) << 32);
+        v = ((v >> 64) & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF)
+            | ((v & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF) << 64);
+        return (v >> 128) | (v << 128);
+    }
+}
+
+library Eq {
+    /// eq(a[aStart..aStart + len], b[bStart..bStart + len]).
+    function evaluate(uint256[] memory a, uint256 aStart, uint256[] memory b, uint256 bStart, uint256 len)
+        internal
+        pure
+        returns (uint256 result)
+    {
+        result = 1;
+        for (uint256 i = 0; i < len; i++) {
+            uint256 x = a[aStart + i];
+            uint256 y = b[bStart + i];
+            uint256 term = addmod(mulmod(x, y, R_MOD), mulmod(Fr.sub(1, x), Fr.sub(1, y), R_MOD), R_MOD);
+            result = mulmod(result, term, R_MOD);
+        }
+    }
+
+    /// The evaluations of eq(r[start..start + len], i) over all i, as in `EqPolynomial::evals`.
+    function evals(uint256[] memory r, uint256 start, uint256 len) internal pure returns (uint256[] memory result) {
+        result = new uint256[](1 << len);
+        result[0] = 1;
+        uint256 size = 1;
+        for (uint256 j = 0; j < len; j++) {
+            size *= 2;
+            for (uint256 i = size - 1; i > 0; i -= 2) {
+                uint256 scalar = result[i / 2];
+                result[i] = mulmod(scalar, r[start + j], R_MOD);
+                result[i - 1] = Fr.sub(scalar, result[i]);
+                if (i == 1) break;
+            }
+        }
+    }
+
+    /// eq(x, y + 1) over `len` bits, as in `eq_plus_one`.
+    function plusOne(uint256[] memory x, uint256 xStart, uint256[] memory y, uint256 yStart, uint256 len)
+        internal
+        pure
+        returns (uint256 result)
+    {
+        if (len == 0) return 0;
+        // higher[k] is the product of eq over bits k + 1..len, counting from the least significant.
+        uint256[] memory higher = new uint256[](len);
+        higher[len - 1] = 1;
+        for (uint256 k = len - 1; k > 0; k--) {
+            uint256 xi = x[xStart + len - 1 - k];
+            uint256 yi = y[yStart + len - 1 - k];
+            uint256 eq = addmod(mulmod(xi, yi, R_MOD), mulmod(Fr.sub(1, xi), Fr.sub(1, yi), R_MOD), R_MOD);
+            higher[k - 1] = mulmod(higher[k], eq, R_MOD);
+        }
+        uint256 lower = 1;
+        for (uint256 k = 0; k < len; k++) {
+            uint256 xk = x[xStart + len - 1 - k];
+            uint256 yk = y[yStart + len - 1 - k];
+            uint256 kth = mulmod(Fr.sub(1, xk), yk, R_MOD);
+            result = addmod(result, mulmod(mulmod(lower, kth, R_MOD), higher[k], R_MOD), R_MOD);
+            lower = mulmod(lower, mulmod(xk, Fr.sub(1, yk), R_MOD), R_MOD);
+        }
+    }
+}
+
+library Sumcheck {
+    /// Verifies a sumcheck proof given as the compressed round polynomials (all coefficients
+    /// but the linear one), returning the final claim and the round challenges.
+    function verify(uint256 claim, uint256 numRounds, uint256 degree, uint256[][] memory polys, Transcript memory t)
+        internal
+        pure
+        returns (uint256 e, uint256[] memory r)
+    {
+        if (polys.length != numRounds) revert InvalidSumcheckProof();
+        e = claim;
+        r = new uint256[](numRounds);
+        for (uint256 i = 0; i < numRounds; i++) {
+            uint256[] memory coeffs = polys[i];
+            if (coeffs.length != degree || degree == 0) revert InvalidSumcheckProof();
+
+            // The linear term makes poly(0) + poly(1) equal the running claim.
+            uint256 linear = Fr.sub(e, addmod(coeffs[0], coeffs[0], R_MOD));
+            for (uint256 j = 1; j < degree; j++) {
+                linear = Fr.sub(linear, coeffs[j]);
+            }
+
+            TranscriptLib.appendMessage(t, TranscriptLib.UNIPOLY_BEGIN);
+            for (uint256 j = 0; j < degree; j++) {
+                TranscriptLib.appendScalar(t, coeffs[j]);
+            }
+            TranscriptLib.appendMessage(t, TranscriptLib.UNIPOLY_END);
+            uint256 ri = TranscriptLib.challengeScalar(t);
+            r[i] = ri;
+
+            uint256 eval = 0;
+            for (uint256 j = degree - 1; j > 0; j--) {
+                eval = addmod(mulmod(eval, ri, R_MOD), coeffs[j], R_MOD);
+            }
+            eval = addmod(mulmod(eval, ri, R_MOD), linear, R_MOD);
+            e = addmod(mulmod(eval, ri, R_MOD), coeffs[0], R_MOD);
+        }
+    }
+}
+
+/// Verification of batched grand products built from multiplication gates, as in
+/// `BatchedGrandProduct::verify_grand_product`.
+library GrandProduct {
+    /// Returns the claims about the leaves and the point at which they were made.
+    function verify(GrandProductLayerProof[] memory layers, uint256[] memory claims, Transcript memory t)
+        internal
+        pure
+        returns (uint256[] memory, uint256[] memory)
+    {
+        uint256[] memory rGrandProduct = new uint256[](0);
+        for (uint256 i = 0; i < layers.length; i++) {
+            (claims, rGrandProduct) = verifyLayer(layers[i], i, claims, rGrandProduct, t);
+        }
+        return (claims, rGrandProduct);
+    }
+
+    function verifyLayer(
+        GrandProductLayerProof memory layer,
+        uint256 numRounds,
+        uint256[] memory claims,
+        uint256[] memory rGrandProduct,
+        Transcript memory t
+    ) private pure returns (uint256[] memory, uint256[] memory) {
+        uint256[] memory coeffs = TranscriptLib.challengeVector(t, claims.length);
+        uint256 claim = 0;
+        for (uint256 i = 0; i < claims.length; i++) {
+            claim = addmod(claim, mulmod(claims[i], coeffs[i], R_MOD), R_MOD);
+        }
+        (uint256 sumcheckClaim, uint256[] memory rSumcheck) = Sumcheck.verify(claim, numRounds, 3, layer.sumcheck, t);
+        if (layer.leftClaims.length != claims.length || layer.rightClaims.length != claims.length) {
+            revert InvalidProofShape();
+        }
+        for (uint256 i = 0; i < claims.length; i++) {
+            TranscriptLib.appendScalar(t, layer.leftClaims[i]);
+            TranscriptLib.appendScalar(t, layer.rightClaims[i]);
+        }
+
+        // The sumcheck binds from the top, so its challenges are reversed.
+        uint256[] memory rNext = new uint256[](numRounds + 1);
+        for (uint256 i = 0; i < numRounds; i++) {
+            rNext[i] = rSumcheck[numRounds - 1 - i];
+        }
+        uint256 eqEval = Eq.evaluate(rGrandProduct, 0, rNext, 0, numRounds);
+
+        uint256 expected = 0;
+        for (uint256 i = 0; i < claims.length; i++) {
+            uint256 product = mulmod(layer.leftClaims[i], layer.rightClaims[i], R_MOD);
+            expected = addmod(expected, mulmod(mulmod(coeffs[i], product, R_MOD), eqEval, R_MOD), R_MOD);
+        }
+        if (expected != sumcheckClaim) revert InvalidGrandProductProof();
+
+        uint256 rLayer = TranscriptLib.challengeScalar(t);
+        rNext[numRounds] = rLayer;
+        uint256[] memory next = new uint256[](claims.length);
+        for (uint256 i = 0; i < claims.length; i++) {
+            uint256 left = layer.leftClaims[i];
+            next[i] = addmod(left, mulmod(rLayer, Fr.sub(layer.rightClaims[i], left), R_MOD), R_MOD);
+        }
+        return (next, rNext);
+    }
+}
+
+/// A port of `HyperKZG::verify` and `HyperKZG::batch_verify`.
+library HyperKZG {
+    function verify(
+        KZGVerifierKey memory vk,
+        G1Point memory c,
+        uint256[] memory x,
+        uint256 y,
+        HyperKZGProof memory proof,
+        Transcript memory t
+    ) internal view returns (bool) {
+        TranscriptLib.appendPoints(t, proof.com);
+        uint256 r = TranscriptLib.challengeScalar(t);
+        if (r == 0 || G1.isZero(c)) return false;
+
+        uint256 ell = x.length;
+        if (proof.v.length != 3) return false;
+        for (uint256 i = 0; i < 3; i++) {
+            if (proof.v[i].length != ell) return false;
+        }
+        if (!checkConsistency(x, y, r, proof.v)) return false;
+        return verifyBatch(vk, c, r, proof, t);
+    }
+
+    function batchVerify(
+        KZGVerifierKey memory vk,
+        G1Point[] memory commitments,
+        uint256[] memory point,
+        uint256[] memory evals,
+        HyperKZGProof memory proof,
+        Transcript memory t
+    ) internal view returns (bool) {
+        if (commitments.length != evals.length) revert InvalidProofShape();
+        uint256 rho = TranscriptLib.challengeScalar(t);
+        uint256 scalar = 1;
+        uint256 eval = 0;
+        G1Point memory c = G1Point(0, 0);
+        for (uint256 i = 0; i < evals.length; i++) {
+            eval = addmod(eval, mulmod(scalar, evals[i], R_MOD), R_MOD);
+            c = G1.add(c, G1.mul(commitments[i], scalar));
+            scalar = mulmod(scalar, rho, R_MOD);
+        }
+        return verify(vk, c, point, eval, proof, t);
+    }
+
+    /// Checks that each folded polynomial's evaluations at r and -r determine the next one's
+    /// evaluation at r^2, ending in the claimed evaluation y.
+    function checkConsistency(uint256[] memory x, uint256 y, uint256 r, uint256[][] memory v)
+        private
+        pure
+        returns (bool)
+    {
+        uint256 ell = x.length;
+        for (uint256 i = 0; i < ell; i++) {
+            uint256 yNext = i + 1 < ell ? v[2][i + 1] : y;
+            uint256 xi = x[ell - i - 1];
+            uint256 lhs = mulmod(mulmod(2, r, R_MOD), yNext, R_MOD);
+            uint256 rhs = addmod(
+                mulmod(mulmod(r, Fr.sub(1, xi), R_MOD), addmod(v[0][i], v[1][i], R_MOD), R_MOD),
+                mulmod(xi, Fr.sub(v[0][i], v[1][i]), R_MOD),
+                R_MOD
+            );
+            if (lhs != rhs) return false;
+        }
+        return true;
+    }
+
+    /// Checks the batched KZG openings of the folded polynomials at r, -r and r^2.
+    function verifyBatch(KZGVerifierKey memory vk, G1Point memory c, uint256 r, HyperKZGProof memory proof, Transcript memory t)
+        private
+        view
+        returns (bool)
+    {
+        uint256[][] memory v = proof.v;
+        TranscriptLib.appendMessage(t, TranscriptLib.BEGIN_VECTOR);
+        for (uint256 i = 0; i < 3; i++) {
+            for (uint256 j = 0; j < v[i].length; j++) {
+                TranscriptLib.appendScalar(t, v[i][j]);
+            }
+        }
+        TranscriptLib.appendMessage(t, TranscriptLib.END_VECTOR);
+        uint256[] memory q = TranscriptLib.challengePowers(t, proof.com.length + 1);
+
+        TranscriptLib.appendPoints(t, proof.w);
+        if (proof.w.length != 3) return false;
+        uint256[3] memory d;
+        d[0] = 1;
+        d[1] = TranscriptLib.challengeScalar(t);
+        d[2] = mulmod(d[1], d[1], R_MOD);
+
+        G1Point memory left = batchedLeft(vk, c, r, proof, q, d);
+        G1Point memory right = G1.add(proof.w[0], G1.add(G1.mul(proof.w[1], d[1]), G1.mul(proof.w[2], d[2])));
+        return G1.pairingCheck(left, vk.g2, G1.neg(right), vk.betaG2);
+    }
+
+    /// L = sum_j q^j (1 + d + d^2) C_j + sum_i d^i u_i W_i - (sum_i d^i B(u_i)) G, where
+    /// u = (r, -r, r^2) and B(u_i) = sum_j q^j v[i][j].
+    function batchedLeft(
+        KZGVerifierKey memory vk,
+        G1Point memory c,
+        uint256 r,
+        HyperKZGProof memory proof,
+        uint256[] memory q,
+        uint256[3] memory d
+    ) private view returns (G1Point memory left) {
+        uint256 multiplier = addmod(addmod(d[0], d[1], R_MOD), d[2], R_MOD);
+        left = G1.mul(c, mulmod(q[0], multiplier, R_MOD));
+        for (uint256 j = 1; j < q.length; j++) {
+            left = G1.add(left, G1.mul(proof.com[j - 1], mulmod(q[j], multiplier, R_MOD)));
+        }
+
+        uint256[3] memory u = [r, R_MOD - r, mulmod(r, r, R_MOD)];
+        uint256 batchedEval = 0;
+        for (uint256 i = 0; i < 3; i++) {
+            left = G1.add(left, G1.mul(proof.w[i], mulmod(u[i], d[i], R_MOD)));
+            uint256 bu = 0;
+            for (uint256 j = 0; j < q.length && j < proof.v[i].length; j++) {
+                bu = addmod(bu, mulmod(proof.v[i][j], q[j], R_MOD), R_MOD);
+            }
+            batchedEval = addmod(batchedEval, mulmod(d[i], bu, R_MOD), R_MOD);
+        }
+        left = G1.add(left, G1.mul(vk.g1, Fr.sub(0, batchedEval)));
+    }
+}
@@ -0,0 +1,220 @@
+struct SpartanProof {
+    uint256[][] outerSumcheck;
+    uint256[3] outerClaims;
+    uint256[][] innerSumcheck;
+    uint256[] witnessEvals;
+    HyperKZGProof opening;
+}
+
+/// Verifies parts of Jolt proofs committed with HyperKZG over BN254, for the verifier key and
+/// R1CS shape it was generated from. Each function resumes a transcript and returns it.
+/// The transcript and commitments are trusted: this is not a standalone verifier of Jolt
+/// proofs, and only checks a proof for a caller that verified the rest of it.
+contract SubprotocolVerifier {
+    uint256 internal constant G1_X = {{G1_X}};
+    uint256 internal constant G1_Y = {{G1_Y}};
+    uint256 internal constant G2_X_C1 = {{G2_X_C1}};
+    uint256 internal constant G2_X_C0 = {{G2_X_C0}};
+    uint256 internal constant G2_Y_C1 = {{G2_Y_C1}};
+    uint256 internal constant G2_Y_C0 = {{G2_Y_C0}};
+    uint256 internal constant BETA_G2_X_C1 = {{BETA_G2_X_C1}};
+    uint256 internal constant BETA_G2_X_C0 = {{BETA_G2_X_C0}};
+    uint256 internal constant BETA_G2_Y_C1 = {{BETA_G2_Y_C1}};
+    uint256 internal constant BETA_G2_Y_C0 = {{BETA_G2_Y_C0}};
+
+    /// Rounds of the outer and inner Spartan sumchecks.
+    uint256 internal constant NUM_ROUNDS_X = {{NUM_ROUNDS_X}};
+    uint256 internal constant NUM_ROUNDS_Y = {{NUM_ROUNDS_Y}};
+    /// Number of witness variables per step, and the bits indexing them.
+    uint256 internal constant NUM_VARS = {{NUM_VARS}};
+    uint256 internal constant VAR_BITS = {{VAR_BITS}};
+    /// Bits indexing the constraints of a step and the steps.
+    uint256 internal constant CONSTRAINT_BITS = {{CONSTRAINT_BITS}};
+    uint256 internal constant STEP_BITS = {{STEP_BITS}};
+
+    struct MatrixContext {
+        uint256[] eqConstraint;
+        uint256[] eqVar;
+        uint256 eqStep;
+        uint256 eqStepPlusOne;
+        uint256 eqConstantColumn;
+    }
+
+    function verifyHyperKZG(
+        Transcript memory transcript,
+        G1Point memory commitment,
+        uint256[] memory point,
+        uint256 eval,
+        HyperKZGProof memory proof
+    ) external view returns (Transcript memory) {
+        if (!HyperKZG.verify(verifierKey(), commitment, point, eval, proof, transcript)) {
+            revert InvalidOpeningProof();
+        }
+        return transcript;
+    }
+
+    /// Returns the claims about the leaves of the grand products and the point they are made at.
+    function verifyGrandProduct(
+        Transcript memory transcript,
+        GrandProductLayerProof[] memory layers,
+        uint256[] memory claims
+    ) external pure returns (Transcript memory, uint256[] memory, uint256[] memory) {
+        (uint256[] memory leafClaims, uint256[] memory r) = GrandProduct.verify(layers, claims, transcript);
+        return (transcript, leafClaims, r);
+    }
+
+    /// Verifies a Spartan proof for the R1CS shape, given the commitments to the witness
+    /// segments in the order of `R1CSProof::format_commitments`.
+    function verifyR1CS(
+        Transcript memory transcript,
+        SpartanProof memory proof,
+        G1Point[] memory witnessCommitments
+    ) external view returns (Transcript memory) {
+        if (proof.witnessEvals.length != NUM_VARS) revert InvalidProofShape();
+
+        uint256[] memory rx = verifyOuterSumcheck(proof, transcript);
+        TranscriptLib.appendMessage(transcript, TranscriptLib.BEGIN_VECTOR);
+        for (uint256 i = 0; i < 3; i++) {
+            TranscriptLib.appendScalar(transcript, proof.outerClaims[i]);
+        }
+        TranscriptLib.appendMessage(transcript, TranscriptLib.END_VECTOR);
+        uint256 rlc = TranscriptLib.challengeScalar(transcript);
+        uint256[] memory ry = verifyInnerSumcheck(proof, rx, rlc, transcript);
+
+        uint256[] memory point = new uint256[](NUM_ROUNDS_Y - VAR_BITS - 1);
+        for (uint256 i = 0; i < point.length; i++) {
+            point[i] = ry[VAR_BITS + 1 + i];
+        }
+        if (!HyperKZG.batchVerify(verifierKey(), witnessCommitments, point, proof.witnessEvals, proof.opening, transcript)) {
+            revert InvalidOpeningProof();
+        }
+        return transcript;
+    }
+
+    function verifierKey() internal pure returns (KZGVerifierKey memory) {
+        return KZGVerifierKey(
+            G1Point(G1_X, G1_Y),
+            [G2_X_C1, G2_X_C0, G2_Y_C1, G2_Y_C0],
+            [BETA_G2_X_C1, BETA_G2_X_C0, BETA_G2_Y_C1, BETA_G2_Y_C0]
+        );
+    }
+
+    function verifyOuterSumcheck(SpartanProof memory proof, Transcript memory transcript)
+        internal
+        pure
+        returns (uint256[] memory rx)
+    {
+        uint256[] memory tau = TranscriptLib.challengeVector(transcript, NUM_ROUNDS_X);
+        (uint256 claim, uint256[] memory r) = Sumcheck.verify(0, NUM_ROUNDS_X, 3, proof.outerSumcheck, transcript);
+
+        // The outer sumcheck binds from the top, so its challenges are reversed.
+        rx = new uint256[](NUM_ROUNDS_X);
+        for (uint256 i = 0; i < NUM_ROUNDS_X; i++) {
+            rx[i] = r[NUM_ROUNDS_X - 1 - i];
+        }
+        uint256[3] memory claims = proof.outerClaims;
+        uint256 expected = mulmod(
+            Eq.evaluate(tau, 0, rx, 0, NUM_ROUNDS_X),
+            Fr.sub(mulmod(claims[0], claims[1], R_MOD), claims[2]),
+            R_MOD
+        );
+        if (claim != expected) revert InvalidR1CSProof();
+    }
+
+    function verifyInnerSumcheck(
+        SpartanProof memory proof,
+        uint256[] memory rx,
+        uint256 rlc,
+        Transcript memory transcript
+    ) internal pure returns (uint256[] memory ry) {
+        uint256[3] memory claims = proof.outerClaims;
+        uint256 rlcSquared = mulmod(rlc, rlc, R_MOD);
+        uint256 joint = addmod(
+            addmod(claims[0], mulmod(rlc, claims[1], R_MOD), R_MOD), mulmod(rlcSquared, claims[2], R_MOD), R_MOD
+        );
+        uint256 claim;
+        (claim, ry) = Sumcheck.verify(joint, NUM_ROUNDS_Y, 2, proof.innerSumcheck, transcript);
+
+        (uint256 a, uint256 b, uint256 c) = evaluateMatrices(rx, ry);
+        uint256 matrices = addmod(addmod(a, mulmod(rlc, b, R_MOD), R_MOD), mulmod(rlcSquared, c, R_MOD), R_MOD);
+        if (claim != mulmod(matrices, evaluateZ(proof.witnessEvals, ry), R_MOD)) revert InvalidR1CSProof();
+    }
+
+    /// The witness vector's multilinear extension at ry, as in `UniformSpartanKey::evaluate_z_mle`.
+    function evaluateZ(uint256[] memory witnessEvals, uint256[] memory ry) internal pure returns (uint256) {
+        uint256[] memory eqVar = Eq.evals(ry, 1, VAR_BITS);
+        uint256 variables = 0;
+        for (uint256 i = 0; i < NUM_VARS; i++) {
+            variables = addmod(variables, mulmod(eqVar[i], witnessEvals[i], R_MOD), R_MOD);
+        }
+        uint256 constant_ = 1;
+        for (uint256 i = 1; i < NUM_ROUNDS_Y; i++) {
+            constant_ = mulmod(constant_, Fr.sub(1, ry[i]), R_MOD);
+        }
+        return addmod(mulmod(Fr.sub(1, ry[0]), variables, R_MOD), mulmod(ry[0], constant_, R_MOD), R_MOD);
+    }
+
+    /// A, B and C at (rx, ry), as in `UniformSpartanKey::evaluate_r1cs_matrix_mles`.
+    function evaluateMatrices(uint256[] memory rx, uint256[] memory ry)
+        internal
+        pure
+        returns (uint256 a, uint256 b, uint256 c)
+    {
+        MatrixContext memory ctx;
+        ctx.eqConstraint = Eq.evals(rx, 0, CONSTRAINT_BITS);
+        ctx.eqVar = Eq.evals(ry, 0, VAR_BITS + 1);
+        ctx.eqStep = Eq.evaluate(rx, CONSTRAINT_BITS, ry, VAR_BITS + 1, STEP_BITS);
+        ctx.eqStepPlusOne = Eq.plusOne(rx, CONSTRAINT_BITS, ry, VAR_BITS + 1, STEP_BITS);
+        ctx.eqConstantColumn = ry[0];
+        for (uint256 i = 1; i < NUM_ROUNDS_Y; i++) {
+            ctx.eqConstantColumn = mulmod(ctx.eqConstantColumn, Fr.sub(1, ry[i]), R_MOD);
+        }
+
+        a = uniform(ctx, uniformA(ctx.eqConstraint, ctx.eqVar));
+        b = uniform(ctx, uniformB(ctx.eqConstraint, ctx.eqVar));
+        c = uniform(ctx, uniformC(ctx.eqConstraint, ctx.eqVar));
+        (uint256 nonUniformA, uint256 nonUniformB) = nonUniform(ctx);
+        a = addmod(a, nonUniformA, R_MOD);
+        b = addmod(b, nonUniformB, R_MOD);
+    }
+
+    function uniform(MatrixContext memory ctx, uint256[2] memory varsAndConsts) internal pure returns (uint256) {
+        return addmod(
+            mulmod(varsAndConsts[0], ctx.eqStep, R_MOD), mulmod(varsAndConsts[1], ctx.eqConstantColumn, R_MOD), R_MOD
+        );
+    }
+
+    // The matrices' entries follow, as sums over their non-zero coefficients of
+    // coefficient * eq(rx_constraint, row) * eq(ry_var, column).
+
+    function uniformA(uint256[] memory eqConstraint, uint256[] memory eqVar)
+        internal
+        pure
+        returns (uint256[2] memory r)
+    {
+{{UNIFORM_A}}
+    }
+
+    function uniformB(uint256[] memory eqConstraint, uint256[] memory eqVar)
+        internal
+        pure
+        returns (uint256[2] memory r)
+    {
+{{UNIFORM_B}}
+    }
+
+    function uniformC(uint256[] memory eqConstraint, uint256[] memory eqVar)
+        internal
+        pure
+        returns (uint256[2] memory r)
+    {
+{{UNIFORM_C}}
+    }
+
+    /// The non-uniform constraints, which relate each step's variables to the next step's.
+    function nonUniform(MatrixContext memory ctx) internal pure returns (uint256 a, uint256 b) {
+        uint256 row;
+        uint256 item;
+{{NON_UNIFORM}}
+    }
+}
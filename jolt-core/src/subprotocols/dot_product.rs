@@ -0,0 +1,149 @@
+use ark_ec::CurveGroup;
+use ark_serialize::*;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::field::JoltField;
+use crate::poly::commitment::pedersen::PedersenGenerators;
+use crate::utils::compute_dotproduct;
+use crate::utils::errors::ProofVerifyError;
+use crate::utils::transcript::ProofTranscript;
+
+/// Proof that a hiding Pedersen commitment `C = <x, G> + blind * h` opens to a vector `x`
+/// whose inner product with a public vector `a` is `y`, revealing nothing about `x` beyond
+/// `y`. This is the Schnorr-style proof of Section 13.2 of Thaler's Proofs, Arguments, and
+/// Zero-Knowledge, with `y` public rather than committed.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Debug)]
+pub struct DotProductProof<G: CurveGroup> {
+    /// Commitment to the random vector `d` masking `x`.
+    mask_commitment: G,
+    /// `<a, d>`
+    mask_dot_product: G::ScalarField,
+    /// `c * x + d` for the verifier's challenge `c`.
+    z: Vec<G::ScalarField>,
+    /// `c * blind + blind_d`
+    z_blind: G::ScalarField,
+}
+
+impl<F: JoltField, G: CurveGroup<ScalarField = F>> DotProductProof<G> {
+    fn protocol_name() -> &'static [u8] {
+        b"Jolt DotProductProof"
+    }
+
+    /// Proves that `commitment = generators.commit_hiding(x, blind)` and returns the proof
+    /// along with `y = <x, a>`.
+    #[tracing::instrument(skip_all, name = "DotProductProof::prove")]
+    pub fn prove<R: RngCore + CryptoRng>(
+        generators: &PedersenGenerators<G>,
+        commitment: &G,
+        x: &[F],
+        blind: &F,
+        a: &[F],
+        transcript: &mut ProofTranscript,
+        rng: &mut R,
+    ) -> (Self, F) {
+        let y = compute_dotproduct(x, a);
+        transcript.append_protocol_name(Self::protocol_name());
+        transcript.append_point(commitment);
+        transcript.append_scalar(&y);
+
+        let d: Vec<F> = (0..x.len()).map(|_| F::random(rng)).collect();
+        let blind_d = F::random(rng);
+        let mask_commitment = generators.commit_hiding(&d, &blind_d);
+        let mask_dot_product = compute_dotproduct(&d, a);
+        transcript.append_point(&mask_commitment);
+        transcript.append_scalar(&mask_dot_product);
+
+        let c: F = transcript.challenge_scalar();
+        let z = x
+            .iter()
+            .zip(d.iter())
+            .map(|(x_i, d_i)| c * x_i + d_i)
+            .collect();
+        let z_blind = c * blind + blind_d;
+
+        let proof = Self {
+            mask_commitment,
+            mask_dot_product,
+            z,
+            z_blind,
+        };
+        (proof, y)
+    }
+
+    pub fn verify(
+        &self,
+        generators: &PedersenGenerators<G>,
+        commitment: &G,
+        a: &[F],
+        y: &F,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(), ProofVerifyError> {
+        if self.z.len() != a.len() {
+            return Err(ProofVerifyError::InvalidInputLength(a.len(), self.z.len()));
+        }
+        if a.len() > generators.generators.len() {
+            return Err(ProofVerifyError::KeyLengthError(
+                generators.generators.len(),
+                a.len(),
+            ));
+        }
+
+        transcript.append_protocol_name(Self::protocol_name());
+        transcript.append_point(commitment);
+        transcript.append_scalar(y);
+        transcript.append_point(&self.mask_commitment);
+        transcript.append_scalar(&self.mask_dot_product);
+        let c: F = transcript.challenge_scalar();
+
+        // c * C + C_d must open to z, and the inner product must be carried along with it.
+        let opens_to_z = *commitment * c + self.mask_commitment
+            == generators.commit_hiding(&self.z, &self.z_blind);
+        let inner_product_holds = compute_dotproduct(&self.z, a) == c * y + self.mask_dot_product;
+        if opens_to_z && inner_product_holds {
+            Ok(())
+        } else {
+            Err(ProofVerifyError::InternalError)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Fr, G1Projective};
+    use ark_std::rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn dot_product_proof() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let generators = PedersenGenerators::<G1Projective>::new(8, b"test dot product");
+        let x: Vec<Fr> = (0..8).map(|_| Fr::random(&mut rng)).collect();
+        let a: Vec<Fr> = (0..8).map(|_| Fr::random(&mut rng)).collect();
+        let blind = Fr::random(&mut rng);
+        let commitment = generators.commit_hiding(&x, &blind);
+
+        let mut transcript = ProofTranscript::new(b"test");
+        let (proof, y) = DotProductProof::prove(
+            &generators,
+            &commitment,
+            &x,
+            &blind,
+            &a,
+            &mut transcript,
+            &mut rng,
+        );
+        assert_eq!(y, compute_dotproduct(&x, &a));
+
+        let mut transcript = ProofTranscript::new(b"test");
+        assert!(proof
+            .verify(&generators, &commitment, &a, &y, &mut transcript)
+            .is_ok());
+
+        let mut transcript = ProofTranscript::new(b"test");
+        let wrong_y = y + Fr::from(1u64);
+        assert!(proof
+            .verify(&generators, &commitment, &a, &wrong_y, &mut transcript)
+            .is_err());
+    }
+}
@@ -184,7 +184,7 @@ pub trait BatchedGrandProduct<F: JoltField, C: CommitmentScheme<Field = F>>: Siz
         proof: &BatchedGrandProductProof<C>,
         claims: &Vec<F>,
         transcript: &mut ProofTranscript,
-        _setup: Option<&C::Setup>,
+        _setup: Option<&C::VerifierSetup>,
     ) -> (Vec<F>, Vec<F>) {
         // Pass the inputs to the layer verification function, by default we have no quarks and so we do not
         // use the quark proof fields.
@@ -148,7 +148,7 @@ impl<F: JoltField, C: CommitmentScheme<Field = F>> BatchedGrandProduct<F, C>
         proof: &BatchedGrandProductProof<C>,
         claims: &Vec<F>,
         transcript: &mut ProofTranscript,
-        setup: Option<&C::Setup>,
+        setup: Option<&C::VerifierSetup>,
     ) -> (Vec<F>, Vec<F>) {
         // Here we must also support the case where the number of layers is very small
         let (v_points, rand) = match proof.quark_proof.as_ref() {
@@ -329,7 +329,7 @@ impl<C: CommitmentScheme> QuarkGrandProductProof<C> {
         claims: &[C::Field],
         transcript: &mut ProofTranscript,
         n_rounds: usize,
-        setup: &C::Setup,
+        setup: &C::VerifierSetup,
     ) -> Result<(Vec<C::Field>, Vec<C::Field>), QuarkError> {
         // First we append the claimed values for the commitment and the product
         transcript.append_scalars(claims);
@@ -368,6 +368,7 @@ impl<C: CommitmentScheme> QuarkGrandProductProof<C> {
             &r,
             &self.claimed_eval_g_r.0,
             &borrowed_g,
+            BatchType::Big,
             transcript,
         )
         .map_err(|_| QuarkError::InvalidOpeningProof)?;
@@ -568,13 +569,21 @@ fn line_reduce_opening_verify<C: CommitmentScheme>(
     r: &[C::Field],
     commitments: &[&C::Commitment],
     transcript: &mut ProofTranscript,
-    setup: &C::Setup,
+    setup: &C::VerifierSetup,
 ) -> Result<(), QuarkError> {
     // First compute the line reduction and points
     let (r_star, claimed) = &line_reduce_verify(&(data.0.clone(), data.1.clone()), r, transcript);
 
     // Finally check the opening at r_star
-    let res = C::batch_verify(&data.2, setup, r_star, claimed, commitments, transcript);
+    let res = C::batch_verify(
+        &data.2,
+        setup,
+        r_star,
+        claimed,
+        commitments,
+        BatchType::Big,
+        transcript,
+    );
     match res {
         Ok(_) => Ok(()),
         Err(_) => Err(QuarkError::InvalidOpeningProof),
@@ -636,7 +645,7 @@ mod quark_grand_product_tests {
 
         // Note resetting the transcript is important
         transcript = ProofTranscript::new(b"test_transcript");
-        let result = proof.verify(&known_products, &mut transcript, 8, &setup);
+        let result = proof.verify(&known_products, &mut transcript, 8, &setup.1);
 
         assert!(result.is_ok(), "Proof did not verify");
     }
@@ -673,7 +682,7 @@ mod quark_grand_product_tests {
             &proof,
             &known_products,
             &mut transcript,
-            Some(&setup),
+            Some(&setup.1),
         );
     }
 }
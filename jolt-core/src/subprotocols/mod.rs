@@ -1,5 +1,6 @@
 #![allow(clippy::too_many_arguments)]
 
+pub mod dot_product;
 pub mod grand_product;
 pub mod grand_product_quarks;
 pub mod sumcheck;
@@ -2,14 +2,18 @@
 #![allow(clippy::type_complexity)]
 
 use crate::field::JoltField;
+use crate::poly::commitment::pedersen::PedersenGenerators;
 use crate::poly::dense_mlpoly::DensePolynomial;
 use crate::poly::unipoly::{CompressedUniPoly, UniPoly};
 use crate::r1cs::special_polys::{IndexablePoly, SparsePolynomial, SparseTripleIterator};
+use crate::subprotocols::dot_product::DotProductProof;
 use crate::utils::errors::ProofVerifyError;
 use crate::utils::mul_0_optimized;
 use crate::utils::thread::drop_in_background_thread;
 use crate::utils::transcript::{AppendToTranscript, ProofTranscript};
+use ark_ec::CurveGroup;
 use ark_serialize::*;
+use rand_core::{CryptoRng, RngCore};
 use rayon::prelude::*;
 
 /// Batched cubic sumcheck used in grand products
@@ -93,68 +97,8 @@ impl<F: JoltField> SumcheckInstanceProof<F> {
         let mut compressed_polys: Vec<CompressedUniPoly<F>> = Vec::new();
 
         for _round in 0..num_rounds {
-            // Vector storing evaluations of combined polynomials g(x) = P_0(x) * ... P_{num_polys} (x)
-            // for points {0, ..., |g(x)|}
-            let mut eval_points = vec![F::zero(); combined_degree + 1];
-
-            let mle_half = polys[0].len() / 2;
-
-            let accum: Vec<Vec<F>> = (0..mle_half)
-                .into_par_iter()
-                .map(|poly_term_i| {
-                    let mut accum = vec![F::zero(); combined_degree + 1];
-                    // Evaluate P({0, ..., |g(r)|})
-
-                    // TODO(#28): Optimize
-                    // Tricks can be used here for low order bits {0,1} but general premise is a running sum for each
-                    // of the m terms in the Dense multilinear polynomials. Formula is:
-                    // half = | D_{n-1} | / 2
-                    // D_n(index, r) = D_{n-1}[half + index] + r * (D_{n-1}[half + index] - D_{n-1}[index])
-
-                    // eval 0: bound_func is A(low)
-                    let params_zero: Vec<F> = polys.iter().map(|poly| poly[poly_term_i]).collect();
-                    accum[0] += comb_func(&params_zero);
-
-                    // TODO(#28): Can be computed from prev_round_claim - eval_point_0
-                    let params_one: Vec<F> = polys
-                        .iter()
-                        .map(|poly| poly[mle_half + poly_term_i])
-                        .collect();
-                    accum[1] += comb_func(&params_one);
-
-                    // D_n(index, r) = D_{n-1}[half + index] + r * (D_{n-1}[half + index] - D_{n-1}[index])
-                    // D_n(index, 0) = D_{n-1}[LOW]
-                    // D_n(index, 1) = D_{n-1}[HIGH]
-                    // D_n(index, 2) = D_{n-1}[HIGH] + (D_{n-1}[HIGH] - D_{n-1}[LOW])
-                    // D_n(index, 3) = D_{n-1}[HIGH] + (D_{n-1}[HIGH] - D_{n-1}[LOW]) + (D_{n-1}[HIGH] - D_{n-1}[LOW])
-                    // ...
-                    let mut existing_term = params_one;
-                    for eval_i in 2..(combined_degree + 1) {
-                        let mut poly_evals = vec![F::zero(); polys.len()];
-                        for poly_i in 0..polys.len() {
-                            let poly = &polys[poly_i];
-                            poly_evals[poly_i] = existing_term[poly_i]
-                                + poly[mle_half + poly_term_i]
-                                - poly[poly_term_i];
-                        }
-
-                        accum[eval_i] += comb_func(&poly_evals);
-                        existing_term = poly_evals;
-                    }
-                    accum
-                })
-                .collect();
-
-            eval_points
-                .par_iter_mut()
-                .enumerate()
-                .for_each(|(poly_i, eval_point)| {
-                    *eval_point = accum
-                        .par_iter()
-                        .take(mle_half)
-                        .map(|mle| mle[poly_i])
-                        .sum::<F>();
-                });
+            let eval_points =
+                Self::compute_eval_points_arbitrary(polys, &comb_func, combined_degree);
 
             let round_uni_poly = UniPoly::from_evals(&eval_points);
             let round_compressed_poly = round_uni_poly.compress();
@@ -176,6 +120,82 @@ impl<F: JoltField> SumcheckInstanceProof<F> {
         (SumcheckInstanceProof::new(compressed_polys), r, final_evals)
     }
 
+    /// Evaluations at 0, 1, ..., `combined_degree` of the round polynomial of a sumcheck over
+    /// `comb_func` applied to `polys`, binding their top variable.
+    #[inline]
+    pub fn compute_eval_points_arbitrary<Func>(
+        polys: &[DensePolynomial<F>],
+        comb_func: &Func,
+        combined_degree: usize,
+    ) -> Vec<F>
+    where
+        Func: Fn(&[F]) -> F + std::marker::Sync,
+    {
+        // Vector storing evaluations of combined polynomials g(x) = P_0(x) * ... P_{num_polys} (x)
+        // for points {0, ..., |g(x)|}
+        let mut eval_points = vec![F::zero(); combined_degree + 1];
+
+        let mle_half = polys[0].len() / 2;
+
+        let accum: Vec<Vec<F>> = (0..mle_half)
+            .into_par_iter()
+            .map(|poly_term_i| {
+                let mut accum = vec![F::zero(); combined_degree + 1];
+                // Evaluate P({0, ..., |g(r)|})
+
+                // TODO(#28): Optimize
+                // Tricks can be used here for low order bits {0,1} but general premise is a running sum for each
+                // of the m terms in the Dense multilinear polynomials. Formula is:
+                // half = | D_{n-1} | / 2
+                // D_n(index, r) = D_{n-1}[half + index] + r * (D_{n-1}[half + index] - D_{n-1}[index])
+
+                // eval 0: bound_func is A(low)
+                let params_zero: Vec<F> = polys.iter().map(|poly| poly[poly_term_i]).collect();
+                accum[0] += comb_func(&params_zero);
+
+                // TODO(#28): Can be computed from prev_round_claim - eval_point_0
+                let params_one: Vec<F> = polys
+                    .iter()
+                    .map(|poly| poly[mle_half + poly_term_i])
+                    .collect();
+                accum[1] += comb_func(&params_one);
+
+                // D_n(index, r) = D_{n-1}[half + index] + r * (D_{n-1}[half + index] - D_{n-1}[index])
+                // D_n(index, 0) = D_{n-1}[LOW]
+                // D_n(index, 1) = D_{n-1}[HIGH]
+                // D_n(index, 2) = D_{n-1}[HIGH] + (D_{n-1}[HIGH] - D_{n-1}[LOW])
+                // D_n(index, 3) = D_{n-1}[HIGH] + (D_{n-1}[HIGH] - D_{n-1}[LOW]) + (D_{n-1}[HIGH] - D_{n-1}[LOW])
+                // ...
+                let mut existing_term = params_one;
+                for eval_i in 2..(combined_degree + 1) {
+                    let mut poly_evals = vec![F::zero(); polys.len()];
+                    for poly_i in 0..polys.len() {
+                        let poly = &polys[poly_i];
+                        poly_evals[poly_i] = existing_term[poly_i] + poly[mle_half + poly_term_i]
+                            - poly[poly_term_i];
+                    }
+
+                    accum[eval_i] += comb_func(&poly_evals);
+                    existing_term = poly_evals;
+                }
+                accum
+            })
+            .collect();
+
+        eval_points
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(poly_i, eval_point)| {
+                *eval_point = accum
+                    .par_iter()
+                    .take(mle_half)
+                    .map(|mle| mle[poly_i])
+                    .sum::<F>();
+            });
+
+        eval_points
+    }
+
     #[inline]
     #[tracing::instrument(
         skip_all,
@@ -486,7 +506,7 @@ impl<F: JoltField> SumcheckInstanceProof<F> {
 
 #[derive(CanonicalSerialize, CanonicalDeserialize, Debug)]
 pub struct SumcheckInstanceProof<F: JoltField> {
-    compressed_polys: Vec<CompressedUniPoly<F>>,
+    pub(crate) compressed_polys: Vec<CompressedUniPoly<F>>,
 }
 
 impl<F: JoltField> SumcheckInstanceProof<F> {
@@ -548,3 +568,275 @@ impl<F: JoltField> SumcheckInstanceProof<F> {
         Ok((e, r))
     }
 }
+
+/// Random polynomial `g(x) = g_0(x_0) + ... + g_{n-1}(x_{n-1})` masking a sumcheck, where
+/// `x_i` is the variable bound in round `i`.
+struct SumcheckMask<F: JoltField> {
+    polys: Vec<UniPoly<F>>,
+}
+
+impl<F: JoltField> SumcheckMask<F> {
+    fn random<R: RngCore + CryptoRng>(num_rounds: usize, degree: usize, rng: &mut R) -> Self {
+        Self {
+            polys: (0..num_rounds)
+                .map(|_| UniPoly::random(degree + 1, rng))
+                .collect(),
+        }
+    }
+
+    /// The coefficients of the `g_i`, which the prover commits to.
+    fn coeffs(&self) -> Vec<F> {
+        self.polys
+            .iter()
+            .flat_map(|poly| poly.coeffs.iter().copied())
+            .collect()
+    }
+
+    /// The sum of `g` over the hypercube.
+    fn sum(&self) -> F {
+        let num_rounds = self.polys.len();
+        let total: F = self
+            .polys
+            .iter()
+            .map(|poly| poly.eval_at_zero() + poly.eval_at_one())
+            .sum();
+        F::from_u64(1 << (num_rounds - 1)).unwrap() * total
+    }
+
+    /// Evaluations at 0, 1, ..., `degree` of `g`'s round polynomial after binding the
+    /// variables of the previous rounds to `r`.
+    fn round_evals(&self, r: &[F], degree: usize) -> Vec<F> {
+        let round = r.len();
+        let free_vars = self.polys.len() - round - 1;
+        let bound: F = self.polys[..round]
+            .iter()
+            .zip(r.iter())
+            .map(|(poly, r_i)| poly.evaluate(r_i))
+            .sum();
+        // Each later g_i takes each of its values on {0, 1} at half of the 2^free_vars points.
+        let free: F = if free_vars == 0 {
+            F::zero()
+        } else {
+            let sum: F = self.polys[round + 1..]
+                .iter()
+                .map(|poly| poly.eval_at_zero() + poly.eval_at_one())
+                .sum();
+            F::from_u64(1 << (free_vars - 1)).unwrap() * sum
+        };
+        let num_points = F::from_u64(1 << free_vars).unwrap();
+
+        (0..=degree)
+            .map(|x| {
+                let g_x = self.polys[round].evaluate(&F::from_u64(x as u64).unwrap());
+                num_points * (bound + g_x) + free
+            })
+            .collect()
+    }
+}
+
+/// The vector whose inner product with the mask's coefficients is `g(r)`: the powers
+/// `1, r_i, ..., r_i^degree` of each `r_i`.
+fn mask_eval_weights<F: JoltField>(r: &[F], degree: usize) -> Vec<F> {
+    r.iter()
+        .flat_map(|r_i| {
+            std::iter::successors(Some(F::one()), move |power| Some(*power * r_i)).take(degree + 1)
+        })
+        .collect()
+}
+
+/// A sumcheck proof whose round polynomials are masked, so that they reveal nothing about
+/// the summed polynomial `f` beyond its sum and its evaluation at the final point (see Section
+/// 13.3 of Thaler's Proofs, Arguments, and Zero-Knowledge).
+///
+/// The prover commits to a random polynomial `g` (see `SumcheckMask`) with a hiding Pedersen
+/// commitment and sends its sum, the verifier picks `rho`, and the sumcheck runs over
+/// `f + rho * g`. At the end the prover reveals `g(r)` and proves it against the commitment.
+/// `f(r)` is still revealed to the caller, who has to check it with hiding openings for the
+/// protocol as a whole to be zero-knowledge.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Debug)]
+pub struct MaskedSumcheckProof<G: CurveGroup>
+where
+    G::ScalarField: JoltField,
+{
+    pub mask_commitment: G,
+    pub mask_sum: G::ScalarField,
+    pub sumcheck_proof: SumcheckInstanceProof<G::ScalarField>,
+    pub mask_eval: G::ScalarField,
+    pub mask_opening: DotProductProof<G>,
+}
+
+impl<F: JoltField, G: CurveGroup<ScalarField = F>> MaskedSumcheckProof<G> {
+    /// Masked counterpart of `SumcheckInstanceProof::prove_arbitrary`. `generators` must have
+    /// at least `num_rounds * (combined_degree + 1)` generators.
+    ///
+    /// Returns (MaskedSumcheckProof, r_eval_point, final_evals), as `prove_arbitrary`.
+    #[tracing::instrument(skip_all, name = "MaskedSumcheckProof::prove_arbitrary")]
+    pub fn prove_arbitrary<Func, R: RngCore + CryptoRng>(
+        num_rounds: usize,
+        polys: &mut Vec<DensePolynomial<F>>,
+        comb_func: Func,
+        combined_degree: usize,
+        generators: &PedersenGenerators<G>,
+        transcript: &mut ProofTranscript,
+        rng: &mut R,
+    ) -> (Self, Vec<F>, Vec<F>)
+    where
+        Func: Fn(&[F]) -> F + std::marker::Sync,
+    {
+        let mask = SumcheckMask::random(num_rounds, combined_degree, rng);
+        let mask_coeffs = mask.coeffs();
+        let mask_blind = F::random(rng);
+        let mask_commitment = generators.commit_hiding(&mask_coeffs, &mask_blind);
+        let mask_sum = mask.sum();
+        transcript.append_point(&mask_commitment);
+        transcript.append_scalar(&mask_sum);
+        let rho: F = transcript.challenge_scalar();
+
+        let mut r: Vec<F> = Vec::new();
+        let mut compressed_polys: Vec<CompressedUniPoly<F>> = Vec::new();
+
+        for _round in 0..num_rounds {
+            let mut eval_points = SumcheckInstanceProof::compute_eval_points_arbitrary(
+                polys,
+                &comb_func,
+                combined_degree,
+            );
+            let mask_evals = mask.round_evals(&r, combined_degree);
+            for (eval, mask_eval) in eval_points.iter_mut().zip(mask_evals) {
+                *eval += rho * mask_eval;
+            }
+
+            let round_compressed_poly = UniPoly::from_evals(&eval_points).compress();
+
+            // append the prover's message to the transcript
+            round_compressed_poly.append_to_transcript(transcript);
+            let r_j = transcript.challenge_scalar();
+            r.push(r_j);
+
+            polys
+                .par_iter_mut()
+                .for_each(|poly| poly.bound_poly_var_top(&r_j));
+            compressed_polys.push(round_compressed_poly);
+        }
+
+        let final_evals = polys.iter().map(|poly| poly[0]).collect();
+
+        let (mask_opening, mask_eval) = DotProductProof::prove(
+            generators,
+            &mask_commitment,
+            &mask_coeffs,
+            &mask_blind,
+            &mask_eval_weights(&r, combined_degree),
+            transcript,
+            rng,
+        );
+
+        let proof = Self {
+            mask_commitment,
+            mask_sum,
+            sumcheck_proof: SumcheckInstanceProof::new(compressed_polys),
+            mask_eval,
+            mask_opening,
+        };
+        (proof, r, final_evals)
+    }
+
+    /// Verifies this proof against the claimed sum of `f`. As with
+    /// `SumcheckInstanceProof::verify`, the caller has to check the returned claim about `f`.
+    ///
+    /// Returns (e, r)
+    /// - `e`: Claimed evaluation of `f` at random point
+    /// - `r`: Evaluation point
+    pub fn verify(
+        &self,
+        claim: F,
+        num_rounds: usize,
+        degree_bound: usize,
+        generators: &PedersenGenerators<G>,
+        transcript: &mut ProofTranscript,
+    ) -> Result<(F, Vec<F>), ProofVerifyError> {
+        transcript.append_point(&self.mask_commitment);
+        transcript.append_scalar(&self.mask_sum);
+        let rho: F = transcript.challenge_scalar();
+
+        let (e, r) = self.sumcheck_proof.verify(
+            claim + rho * self.mask_sum,
+            num_rounds,
+            degree_bound,
+            transcript,
+        )?;
+
+        self.mask_opening.verify(
+            generators,
+            &self.mask_commitment,
+            &mask_eval_weights(&r, degree_bound),
+            &self.mask_eval,
+            transcript,
+        )?;
+        Ok((e - rho * self.mask_eval, r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Fr, G1Projective};
+    use ark_std::rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn masked_sumcheck() {
+        const NUM_VARS: usize = 4;
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let a: Vec<Fr> = (0..1 << NUM_VARS).map(|_| Fr::random(&mut rng)).collect();
+        let b: Vec<Fr> = (0..1 << NUM_VARS).map(|_| Fr::random(&mut rng)).collect();
+        let claim: Fr = a.iter().zip(b.iter()).map(|(a, b)| *a * b).sum();
+        let comb_func = |evals: &[Fr]| evals[0] * evals[1];
+        let generators = PedersenGenerators::<G1Projective>::new(NUM_VARS * 3, b"test");
+
+        let mut polys = vec![
+            DensePolynomial::new(a.clone()),
+            DensePolynomial::new(b.clone()),
+        ];
+        let mut transcript = ProofTranscript::new(b"test");
+        let (mut proof, r_prover, final_evals) = MaskedSumcheckProof::prove_arbitrary(
+            NUM_VARS,
+            &mut polys,
+            comb_func,
+            2,
+            &generators,
+            &mut transcript,
+            &mut rng,
+        );
+
+        let mut transcript = ProofTranscript::new(b"test");
+        let (e, r) = proof
+            .verify(claim, NUM_VARS, 2, &generators, &mut transcript)
+            .unwrap();
+        assert_eq!(r, r_prover);
+        assert_eq!(e, comb_func(&final_evals));
+        assert_eq!(final_evals[0], DensePolynomial::new(a.clone()).evaluate(&r));
+
+        // The round polynomials differ from those of the unmasked sumcheck.
+        let mut polys = vec![DensePolynomial::new(a), DensePolynomial::new(b)];
+        let mut transcript = ProofTranscript::new(b"test");
+        let (unmasked, _, _) = SumcheckInstanceProof::prove_arbitrary(
+            &claim,
+            NUM_VARS,
+            &mut polys,
+            comb_func,
+            2,
+            &mut transcript,
+        );
+        assert_ne!(
+            unmasked.compressed_polys[0].coeffs_except_linear_term,
+            proof.sumcheck_proof.compressed_polys[0].coeffs_except_linear_term
+        );
+
+        proof.mask_eval += Fr::from(1u64);
+        let mut transcript = ProofTranscript::new(b"test");
+        assert!(proof
+            .verify(claim, NUM_VARS, 2, &generators, &mut transcript)
+            .is_err());
+    }
+}
@@ -16,4 +16,18 @@ pub enum ProofVerifyError {
     SpartanError(String),
     #[error("Length Error: SRS Length: {0}, Key Length: {0}")]
     KeyLengthError(usize, usize),
+    #[error("Program did not terminate")]
+    ProgramNotTerminated,
+    #[error("Bytecode commitments do not match the verifier key")]
+    BytecodeCommitmentMismatch,
+    #[error("Program memory commitment does not match the verifier key")]
+    ProgramMemoryCommitmentMismatch,
+    #[error("Memory layout does not match the verifier key")]
+    MemoryLayoutMismatch,
+    #[error("Initial memory commitment does not match the expected initial state")]
+    InitialMemoryMismatch,
+    #[error("Continuation has no segments")]
+    EmptyContinuation,
+    #[error("Segment {0} does not continue from the previous segment")]
+    SegmentChainMismatch(usize),
 }
@@ -38,6 +38,11 @@ impl ProofTranscript {
             .chain_update(self.n_rounds.to_le_bytes())
     }
 
+    /// The running state and round count, from which the Solidity transcript resumes.
+    pub(crate) fn state(&self) -> ([u8; 32], u32) {
+        (self.state, self.n_rounds)
+    }
+
     pub fn append_message(&mut self, msg: &'static [u8]) {
         // We require all messages to fit into one evm word and then left pad them
         assert!(msg.len() < 33);
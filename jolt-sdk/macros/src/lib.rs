@@ -4,7 +4,10 @@ extern crate proc_macro;
 
 use core::panic;
 
-use common::{attributes::parse_attributes, rv_trace::MemoryLayout};
+use common::{
+    attributes::{parse_attributes, AllocatorAttribute, CommitmentSchemeAttribute},
+    constants::{IO_DIGEST_SIZE, MAX_DIAGNOSTICS_SIZE},
+};
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
@@ -40,10 +43,18 @@ struct MacroBuilder {
     func: ItemFn,
     std: bool,
     func_args: Vec<(Ident, Box<Type>)>,
+    /// Arguments marked `#[private]`, which are passed to the guest as advice rather
+    /// than as public inputs.
+    private_args: Vec<Ident>,
+    /// Whether any argument is passed as advice, which determines the size of the advice
+    /// region.
+    has_advice_args: bool,
 }
 
 impl MacroBuilder {
-    fn new(attr: AttributeArgs, func: ItemFn) -> Self {
+    fn new(attr: AttributeArgs, mut func: ItemFn) -> Self {
+        let has_advice_args = parse_attributes(&attr).has_advice_args(&func.sig);
+        let private_args = Self::take_private_args(&mut func);
         let func_args = Self::get_func_args(&func);
         #[cfg(feature = "guest-std")]
         let std = true;
@@ -55,6 +66,8 @@ impl MacroBuilder {
             func,
             std,
             func_args,
+            private_args,
+            has_advice_args,
         }
     }
 
@@ -64,6 +77,7 @@ impl MacroBuilder {
         let analyze_fn = self.make_analyze_function();
         let preprocess_fn = self.make_preprocess_func();
         let prove_fn = self.make_prove_func();
+        let build_verifier_fn = self.make_build_verifier_fn();
 
         let main_fn = if let Some(func) = self.get_func_selector() {
             if *self.get_func_name() == func {
@@ -81,6 +95,7 @@ impl MacroBuilder {
             #analyze_fn
             #preprocess_fn
             #prove_fn
+            #build_verifier_fn
             #main_fn
         }
         .into()
@@ -90,6 +105,7 @@ impl MacroBuilder {
         let fn_name = self.get_func_name();
         let build_fn_name = Ident::new(&format!("build_{}", fn_name), fn_name.span());
         let prove_output_ty = self.get_prove_output_type();
+        let proof_ty = self.get_proof_type();
 
         let input_names = self.func_args.iter().map(|(name, _)| name);
         let input_types = self.func_args.iter().map(|(_, ty)| ty);
@@ -97,20 +113,20 @@ impl MacroBuilder {
         let preprocess_fn_name = Ident::new(&format!("preprocess_{}", fn_name), fn_name.span());
         let prove_fn_name = Ident::new(&format!("prove_{}", fn_name), fn_name.span());
         let imports = self.make_imports();
+        let vm = self.get_vm();
 
         quote! {
             #[cfg(all(not(target_arch = "wasm32"), not(feature = "guest")))]
             pub fn #build_fn_name() -> (
                 impl Fn(#(#input_types),*) -> #prove_output_ty,
-                impl Fn(jolt::RV32IHyraxProof) -> bool
+                impl Fn(#proof_ty) -> bool
             ) {
                 #imports
                 let (program, preprocessing) = #preprocess_fn_name();
                 let program = std::rc::Rc::new(program);
                 let preprocessing = std::rc::Rc::new(preprocessing);
 
-                let program_cp = program.clone();
-                let preprocessing_cp = preprocessing.clone();
+                let verifier_key = preprocessing.verifier_key();
 
                 let prove_closure = move |#inputs| {
                     let program = (*program).clone();
@@ -119,10 +135,9 @@ impl MacroBuilder {
                 };
 
 
-                let verify_closure = move |proof: jolt::RV32IHyraxProof| {
-                    let program = (*program_cp).clone();
-                    let preprocessing = (*preprocessing_cp).clone();
-                    RV32IJoltVM::verify(preprocessing, proof.proof, proof.commitments).is_ok()
+                let verify_closure = move |proof: #proof_ty| {
+                    let verifier_key = verifier_key.clone();
+                    #vm::verify(verifier_key, proof.proof, proof.commitments).is_ok()
                 };
 
                 (prove_closure, verify_closure)
@@ -130,6 +145,31 @@ impl MacroBuilder {
         }
     }
 
+    fn make_build_verifier_fn(&self) -> TokenStream2 {
+        let fn_name = self.get_func_name();
+        let build_verifier_fn_name =
+            Ident::new(&format!("build_verifier_{}", fn_name), fn_name.span());
+        let pcs_ty = self.get_pcs_type();
+        let proof_ty = self.get_proof_type();
+        let imports = self.make_imports();
+        let vm = self.get_vm();
+
+        quote! {
+            /// Builds a verifier from a previously saved verifier key (see
+            /// `JoltVerifierKey::from_file`), without rebuilding the guest.
+            #[cfg(all(not(target_arch = "wasm32"), not(feature = "guest")))]
+            pub fn #build_verifier_fn_name(
+                verifier_key: jolt::JoltVerifierKey<jolt::F, #pcs_ty>
+            ) -> impl Fn(#proof_ty) -> bool {
+                #imports
+                move |proof: #proof_ty| {
+                    let verifier_key = verifier_key.clone();
+                    #vm::verify(verifier_key, proof.proof, proof.commitments).is_ok()
+                }
+            }
+        }
+    }
+
     fn make_execute_function(&self) -> TokenStream2 {
         let fn_name = self.get_func_name();
         let inputs = &self.func.sig.inputs;
@@ -154,16 +194,13 @@ impl MacroBuilder {
         let fn_name_str = fn_name.to_string();
         let analyze_fn_name = Ident::new(&format!("analyze_{}", fn_name), fn_name.span());
         let inputs = &self.func.sig.inputs;
-        let set_program_args = self.func_args.iter().map(|(name, _)| {
-            quote! {
-                program.set_input(&#name);
-            }
-        });
+        let set_program_args = self.make_set_program_args();
+        let instruction_set = self.get_instruction_set();
 
         quote! {
              #[cfg(not(target_arch = "wasm32"))]
              #[cfg(not(feature = "guest"))]
-             pub fn #analyze_fn_name(#inputs) -> jolt::host::analyze::ProgramSummary {
+             pub fn #analyze_fn_name(#inputs) -> jolt::host::analyze::ProgramSummary<#instruction_set> {
                 #imports
 
                 let mut program = Program::new(#guest_name);
@@ -172,7 +209,9 @@ impl MacroBuilder {
                 #set_mem_size
                 #(#set_program_args;)*
 
-                program.trace_analyze::<jolt::F>()
+                program
+                    .trace_analyze::<#instruction_set, jolt::F>()
+                    .unwrap_or_else(|e| panic!("failed to trace guest program: {}", e))
              }
         }
     }
@@ -182,15 +221,17 @@ impl MacroBuilder {
         let guest_name = self.get_guest_name();
         let imports = self.make_imports();
         let set_std = self.make_set_std();
+        let pcs_ty = self.get_pcs_type();
 
         let fn_name = self.get_func_name();
         let fn_name_str = fn_name.to_string();
         let preprocess_fn_name = Ident::new(&format!("preprocess_{}", fn_name), fn_name.span());
+        let vm = self.get_vm();
         quote! {
             #[cfg(all(not(target_arch = "wasm32"), not(feature = "guest")))]
             pub fn #preprocess_fn_name() -> (
                 jolt::host::Program,
-                jolt::JoltPreprocessing<jolt::F, jolt::PCS>
+                jolt::JoltPreprocessing<jolt::F, #pcs_ty>
             ) {
                 #imports
 
@@ -201,14 +242,16 @@ impl MacroBuilder {
                 let (bytecode, memory_init) = program.decode();
 
                 // TODO(moodlezoup): Feed in size parameters via macro
-                let preprocessing: JoltPreprocessing<jolt::F, jolt::PCS> =
-                    RV32IJoltVM::preprocess(
+                let preprocessing: JoltPreprocessing<jolt::F, #pcs_ty> =
+                    #vm::preprocess(
                         bytecode,
                         memory_init,
+                        program.memory_layout(),
                         1 << 20,
                         1 << 20,
                         1 << 24
-                    );
+                    )
+                    .unwrap_or_else(|e| panic!("failed to preprocess guest program: {}", e));
 
                 (program, preprocessing)
             }
@@ -217,53 +260,61 @@ impl MacroBuilder {
 
     fn make_prove_func(&self) -> TokenStream2 {
         let prove_output_ty = self.get_prove_output_type();
+        let max_output_len = parse_attributes(&self.attr).max_output_size as usize;
 
         let handle_return = match &self.func.sig.output {
             ReturnType::Default => quote! {
                 let ret_val = ();
             },
+            // A guest that panics never writes its return value, so it is decoded from an
+            // unwritten, zeroed output region instead. Callers should check `panic` in the
+            // proof's program I/O before using it.
             ReturnType::Type(_, ty) => quote! {
-                let ret_val = jolt::postcard::from_bytes::<#ty>(&output_bytes).unwrap();
+                let ret_val = if io_device.panic {
+                    jolt::postcard::from_bytes::<#ty>(&[0; #max_output_len])
+                } else {
+                    jolt::postcard::from_bytes::<#ty>(io_device.output_bytes())
+                }
+                .unwrap_or_else(|e| panic!("failed to decode guest output: {}", e));
             },
         };
 
-        let set_program_args = self.func_args.iter().map(|(name, _)| {
-            quote! {
-                program.set_input(&#name);
-            }
-        });
+        let set_program_args = self.make_set_program_args();
 
         let fn_name = self.get_func_name();
         let inputs = &self.func.sig.inputs;
         let imports = self.make_imports();
 
         let prove_fn_name = syn::Ident::new(&format!("prove_{}", fn_name), fn_name.span());
+        let pcs_ty = self.get_pcs_type();
+        let proof_ty = self.get_proof_type();
+        let vm = self.get_vm();
+        let instruction_set = self.get_instruction_set();
         quote! {
             #[cfg(all(not(target_arch = "wasm32"), not(feature = "guest")))]
             pub fn #prove_fn_name(
                 mut program: jolt::host::Program,
-                preprocessing: jolt::JoltPreprocessing<jolt::F, jolt::PCS>,
+                preprocessing: jolt::JoltPreprocessing<jolt::F, #pcs_ty>,
                 #inputs
             ) -> #prove_output_ty {
                 #imports
 
                 #(#set_program_args;)*
 
-                let (io_device, trace, circuit_flags) =
-                    program.trace();
+                let (io_device, trace, circuit_flags) = program
+                    .trace::<#instruction_set, jolt::F>()
+                    .unwrap_or_else(|e| panic!("failed to trace guest program: {}", e));
 
-                let output_bytes = io_device.outputs.clone();
+                #handle_return
 
-                let (jolt_proof, jolt_commitments) = RV32IJoltVM::prove(
+                let (jolt_proof, jolt_commitments) = #vm::prove(
                     io_device,
                     trace,
                     circuit_flags,
                     preprocessing,
                 );
 
-                #handle_return
-
-                let proof = jolt::RV32IHyraxProof {
+                let proof: #proof_ty = jolt::JoltVMProof {
                     proof: jolt_proof,
                     commitments: jolt_commitments,
                 };
@@ -275,25 +326,65 @@ impl MacroBuilder {
 
     fn make_main_func(&self) -> TokenStream2 {
         let attributes = parse_attributes(&self.attr);
-        let memory_layout =
-            MemoryLayout::new(attributes.max_input_size, attributes.max_output_size);
+        let memory_layout = attributes.memory_layout(self.has_advice_args);
         let input_start = memory_layout.input_start;
         let output_start = memory_layout.output_start;
+        let advice_start = memory_layout.advice_start;
         let max_input_len = attributes.max_input_size as usize;
         let max_output_len = attributes.max_output_size as usize;
+        let max_advice_len = memory_layout.max_advice_size as usize;
+        let io_digest_size = IO_DIGEST_SIZE as usize;
 
         let get_input_slice = quote! {
             let input_ptr = #input_start as *const u8;
             let input_slice = unsafe {
                 core::slice::from_raw_parts(input_ptr, #max_input_len)
             };
+            let advice_ptr = #advice_start as *const u8;
+            let advice_slice = unsafe {
+                core::slice::from_raw_parts(advice_ptr, #max_advice_len)
+            };
+        };
+
+        let hash_io = attributes.hash_io;
+        if hash_io && attributes.max_output_size < IO_DIGEST_SIZE {
+            panic!(
+                "max_output_size must be at least {} bytes with hash_io",
+                IO_DIGEST_SIZE
+            );
+        }
+
+        // With hashed I/O, the public arguments are hashed as they are read
+        let declare_input_hasher = if hash_io {
+            quote! {
+                let mut input_hasher = <jolt::sha3::Keccak256 as jolt::sha3::Digest>::new();
+            }
+        } else {
+            quote! {}
         };
 
         let args = &self.func_args;
         let args_fetch = args.iter().map(|(name, ty)| {
-            quote! {
-                let (#name, input_slice) =
-                    jolt::postcard::take_from_bytes::<#ty>(input_slice).unwrap();
+            if self.private_args.contains(name) {
+                quote! {
+                    let (#name, advice_slice) =
+                        jolt::postcard::take_from_bytes::<#ty>(advice_slice).unwrap();
+                }
+            } else if hash_io {
+                quote! {
+                    let (#name, remaining_advice) =
+                        jolt::postcard::take_from_bytes::<#ty>(advice_slice).unwrap();
+                    jolt::sha3::Digest::update(
+                        &mut input_hasher,
+                        &advice_slice[..advice_slice.len() - remaining_advice.len()],
+                    );
+                    let advice_slice = remaining_advice;
+                }
+            } else {
+                quote! {
+                    let (#name, input_slice) =
+                        jolt::postcard::take_from_bytes::<#ty>(input_slice).unwrap();
+                }
             }
         });
 
@@ -304,7 +395,20 @@ impl MacroBuilder {
         let block = quote! {let to_return = (|| -> _ { #block })();};
 
         let handle_return = match &self.func.sig.output {
+            ReturnType::Default if hash_io => quote! {
+                let output_hash =
+                    <jolt::sha3::Keccak256 as jolt::sha3::Digest>::digest(b"").into();
+            },
             ReturnType::Default => quote! {},
+            ReturnType::Type(_, ty) if hash_io => quote! {
+                let output_ptr = #output_start as *mut u8;
+                let output_slice = unsafe {
+                    core::slice::from_raw_parts_mut(output_ptr, #max_output_len)
+                };
+                let (_, return_slice) = output_slice.split_at_mut(#io_digest_size);
+
+                let output_hash = jolt::to_slice_hashed::<#ty>(&to_return, return_slice).unwrap();
+            },
             ReturnType::Type(_, ty) => quote! {
                 let output_ptr = #output_start as *mut u8;
                 let output_slice = unsafe {
@@ -315,7 +419,46 @@ impl MacroBuilder {
             },
         };
 
-        let panic_fn = self.make_panic(memory_layout.panic);
+        // The digest goes at the start of the output region, ahead of the return value
+        let write_io_digest = if hash_io {
+            quote! {
+                let input_hash = jolt::sha3::Digest::finalize(input_hasher).into();
+                let io_digest = jolt::io_digest_from_hashes(input_hash, output_hash);
+                let digest_slice = unsafe {
+                    core::slice::from_raw_parts_mut(#output_start as *mut u8, #io_digest_size)
+                };
+                digest_slice.copy_from_slice(&io_digest);
+            }
+        } else {
+            quote! {}
+        };
+
+        let termination_address = memory_layout.termination;
+        let handle_termination = quote! {
+            unsafe {
+                core::ptr::write_volatile(#termination_address as *mut u8, 1);
+            }
+        };
+
+        let panic_fn = self.make_panic(
+            memory_layout.panic,
+            termination_address,
+            memory_layout.diagnostics,
+        );
+        let set_panic_hook = if self.std {
+            quote! {
+                std::panic::set_hook(std::boxed::Box::new(|info| {
+                    let _ = core::fmt::Write::write_fmt(
+                        &mut JoltDiagnosticsWriter { len: 0 },
+                        format_args!("{}", info),
+                    );
+                }));
+            }
+        } else {
+            quote! {}
+        };
+        let console_fn = self.make_console(memory_layout.console);
+        let hint_fns = self.make_hints(memory_layout.hint, memory_layout.hint_service);
         let declare_alloc = self.make_allocator();
 
         quote! {
@@ -337,26 +480,129 @@ impl MacroBuilder {
             #[cfg(feature = "guest")]
             #[no_mangle]
             pub extern "C" fn main() {
+                #set_panic_hook
                 let mut offset = 0;
                 #get_input_slice
+                #declare_input_hasher
                 #(#args_fetch;)*
+                unsafe {
+                    JOLT_HINT_CURSOR = advice_slice.as_ptr() as usize;
+                }
                 #check_input_len
                 #block
                 #handle_return
+                #write_io_digest
+                #handle_termination
             }
 
             #panic_fn
+
+            #console_fn
+
+            #hint_fns
+        }
+    }
+
+    fn make_console(&self, console_address: u64) -> TokenStream2 {
+        quote! {
+            #[cfg(feature = "guest")]
+            #[no_mangle]
+            pub extern "C" fn jolt_print(ptr: *const u8, len: usize) {
+                let bytes = unsafe { core::slice::from_raw_parts(ptr, len) };
+                for byte in bytes {
+                    unsafe {
+                        core::ptr::write_volatile(#console_address as *mut u8, *byte);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Hint requests are written to the hint address and terminated by a zero byte. Reading
+    /// the hint service address then has the host append the response to the advice region,
+    /// prefixed by its length as a little-endian `u32`.
+    fn make_hints(&self, hint_address: u64, hint_service_address: u64) -> TokenStream2 {
+        quote! {
+            /// Address of the next unread response in the advice region.
+            #[cfg(feature = "guest")]
+            static mut JOLT_HINT_CURSOR: usize = 0;
+
+            #[cfg(feature = "guest")]
+            #[no_mangle]
+            pub extern "C" fn jolt_hint_write(ptr: *const u8, len: usize) {
+                let bytes = unsafe { core::slice::from_raw_parts(ptr, len) };
+                for byte in bytes {
+                    unsafe {
+                        core::ptr::write_volatile(#hint_address as *mut u8, *byte);
+                    }
+                }
+            }
+
+            #[cfg(feature = "guest")]
+            #[no_mangle]
+            pub extern "C" fn jolt_hint_read(len: *mut usize) -> *const u8 {
+                unsafe {
+                    core::ptr::write_volatile(#hint_address as *mut u8, 0);
+                    core::ptr::read_volatile(#hint_service_address as *const u8);
+                    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+
+                    let response = JOLT_HINT_CURSOR as *const u8;
+                    let mut response_len = [0u8; 4];
+                    for (i, byte) in response_len.iter_mut().enumerate() {
+                        *byte = core::ptr::read_volatile(response.add(i));
+                    }
+                    let response_len = u32::from_le_bytes(response_len) as usize;
+                    *len = response_len;
+                    JOLT_HINT_CURSOR += 4 + response_len;
+                    response.add(4)
+                }
+            }
         }
     }
 
-    fn make_panic(&self, panic_address: u64) -> TokenStream2 {
+    /// The panic message and location are written to the diagnostics address, from the
+    /// panic handler for `no_std` guests and from a panic hook installed by `main` for `std`
+    /// guests, whose panic handler is provided by the toolchain.
+    fn make_panic(
+        &self,
+        panic_address: u64,
+        termination_address: u64,
+        diagnostics_address: u64,
+    ) -> TokenStream2 {
+        let max_diagnostics_len = MAX_DIAGNOSTICS_SIZE as usize;
+        let diagnostics_writer = quote! {
+            #[cfg(feature = "guest")]
+            struct JoltDiagnosticsWriter {
+                len: usize,
+            }
+
+            #[cfg(feature = "guest")]
+            impl core::fmt::Write for JoltDiagnosticsWriter {
+                fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                    for byte in s.bytes() {
+                        if self.len == #max_diagnostics_len {
+                            break;
+                        }
+                        unsafe {
+                            core::ptr::write_volatile(#diagnostics_address as *mut u8, byte);
+                        }
+                        self.len += 1;
+                    }
+                    Ok(())
+                }
+            }
+        };
+
         if self.std {
             quote! {
+                #diagnostics_writer
+
                 #[cfg(feature = "guest")]
                 #[no_mangle]
                 pub extern "C" fn jolt_panic() {
                     unsafe {
                         core::ptr::write_volatile(#panic_address as *mut u8, 1);
+                        core::ptr::write_volatile(#termination_address as *mut u8, 1);
                     }
 
                     loop {}
@@ -364,14 +610,21 @@ impl MacroBuilder {
             }
         } else {
             quote! {
+                #diagnostics_writer
+
                 #[cfg(feature = "guest")]
                 use core::panic::PanicInfo;
 
                 #[cfg(feature = "guest")]
                 #[panic_handler]
-                fn panic(_info: &PanicInfo) -> ! {
+                fn panic(info: &PanicInfo) -> ! {
+                    let _ = core::fmt::Write::write_fmt(
+                        &mut JoltDiagnosticsWriter { len: 0 },
+                        format_args!("{}", info),
+                    );
                     unsafe {
                         core::ptr::write_volatile(#panic_address as *mut u8, 1);
+                        core::ptr::write_volatile(#termination_address as *mut u8, 1);
                     }
 
                     loop {}
@@ -381,14 +634,22 @@ impl MacroBuilder {
     }
 
     fn make_allocator(&self) -> TokenStream2 {
+        let allocator = parse_attributes(&self.attr).allocator;
         if self.std {
-            quote! {}
-        } else {
-            quote! {
-                #[cfg(feature = "guest")]
-                #[global_allocator]
-                static ALLOCATOR: jolt::BumpAllocator = jolt::BumpAllocator;
+            if allocator != AllocatorAttribute::Bump {
+                panic!("the allocator attribute is only supported for no_std guests");
             }
+            return quote! {};
+        }
+
+        let allocator_ty = match allocator {
+            AllocatorAttribute::Bump => quote! { jolt::BumpAllocator },
+            AllocatorAttribute::FreeList => quote! { jolt::FreeListAllocator },
+        };
+        quote! {
+            #[cfg(feature = "guest")]
+            #[global_allocator]
+            static ALLOCATOR: #allocator_ty = #allocator_ty;
         }
     }
 
@@ -419,28 +680,6 @@ impl MacroBuilder {
             use wasm_bindgen::prelude::*;
             #[cfg(target_arch = "wasm32")]
             use std::vec::Vec;
-            #[cfg(target_arch = "wasm32")]
-            use rmp_serde::Deserializer;
-            #[cfg(target_arch = "wasm32")]
-            use serde::{Deserialize, Serialize};
-
-            #[cfg(all(target_arch = "wasm32", not(feature = "guest")))]
-            use jolt::host::ELFInstruction;
-
-            #[cfg(all(target_arch = "wasm32", not(feature = "guest")))]
-            #[derive(Serialize, Deserialize)]
-            struct DecodedData {
-                bytecode: Vec<ELFInstruction>,
-                memory_init: Vec<(u64, u8)>,
-            }
-
-            #[cfg(target_arch = "wasm32")]
-            fn deserialize_from_bin<'a, T: Deserialize<'a>>(
-                data: &'a [u8],
-            ) -> Result<T, rmp_serde::decode::Error> {
-                let mut de = Deserializer::new(data);
-                Deserialize::deserialize(&mut de)
-            }
         }
     }
 
@@ -448,6 +687,11 @@ impl MacroBuilder {
         let attributes = parse_attributes(&self.attr);
         let mut code: Vec<TokenStream2> = Vec::new();
 
+        let value = attributes.ram_start;
+        code.push(quote! {
+            program.set_ram_start(#value);
+        });
+
         let value = attributes.memory_size;
         code.push(quote! {
             program.set_memory_size(#value);
@@ -468,6 +712,21 @@ impl MacroBuilder {
             program.set_max_output_size(#value);
         });
 
+        let value = self.get_max_advice_size();
+        code.push(quote! {
+            program.set_max_advice_size(#value);
+        });
+
+        let value = attributes.max_cycles;
+        code.push(quote! {
+            program.set_max_cycles(#value);
+        });
+
+        let value = attributes.hash_io;
+        code.push(quote! {
+            program.set_hash_io(#value);
+        });
+
         quote! {
             #(#code;)*
         }
@@ -486,16 +745,116 @@ impl MacroBuilder {
     }
 
     fn get_prove_output_type(&self) -> TokenStream2 {
+        let proof_ty = self.get_proof_type();
         match &self.func.sig.output {
             ReturnType::Default => quote! {
-                ((), jolt::RV32IHyraxProof)
+                ((), #proof_ty)
             },
             ReturnType::Type(_, ty) => quote! {
-                (#ty, jolt::RV32IHyraxProof)
+                (#ty, #proof_ty)
             },
         }
     }
 
+    fn get_pcs_type(&self) -> TokenStream2 {
+        match parse_attributes(&self.attr).pcs {
+            CommitmentSchemeAttribute::Hyrax => quote! { jolt::HyraxScheme<jolt::G> },
+            CommitmentSchemeAttribute::HyperKZG => quote! { jolt::HyperKZG<jolt::Bn254> },
+            CommitmentSchemeAttribute::Zeromorph => quote! { jolt::Zeromorph<jolt::Bn254> },
+        }
+    }
+
+    fn get_proof_type(&self) -> TokenStream2 {
+        let pcs_ty = self.get_pcs_type();
+        let (vm_ty, c, m) = self.get_vm_parameters();
+        quote! { jolt::JoltVMProof<#vm_ty, #pcs_ty, #c, #m> }
+    }
+
+    /// The `Jolt` VM selected via the `vm`, `c` and `m` attributes, defaulting to
+    /// `RV32IJoltVM`, along with its `C` and `M` parameters.
+    fn get_vm_parameters(&self) -> (TokenStream2, TokenStream2, TokenStream2) {
+        let attributes = parse_attributes(&self.attr);
+        let vm_ty = match &attributes.vm {
+            Some(path) => {
+                let path: syn::Path = syn::parse_str(path).expect("invalid vm path");
+                quote! { #path }
+            }
+            None => quote! { jolt::RV32IJoltVM },
+        };
+        let c = match attributes.c {
+            Some(c) => {
+                let c = c as usize;
+                quote! { #c }
+            }
+            None => quote! { { jolt::C } },
+        };
+        let m = match attributes.m {
+            Some(m) => {
+                let m = m as usize;
+                quote! { #m }
+            }
+            None => quote! { { jolt::M } },
+        };
+        (vm_ty, c, m)
+    }
+
+    /// The selected VM, qualified with its `Jolt` implementation for the selected PCS.
+    fn get_vm(&self) -> TokenStream2 {
+        let pcs_ty = self.get_pcs_type();
+        let (vm_ty, c, m) = self.get_vm_parameters();
+        quote! { <#vm_ty as jolt::Jolt<jolt::F, #pcs_ty, #c, #m>> }
+    }
+
+    fn get_instruction_set(&self) -> TokenStream2 {
+        let vm = self.get_vm();
+        quote! { #vm::InstructionSet }
+    }
+
+    fn make_set_program_args(&self) -> Vec<TokenStream2> {
+        self.func_args
+            .iter()
+            .map(|(name, _)| {
+                if self.is_advice_arg(name) {
+                    quote! {
+                        program.set_advice(&#name);
+                    }
+                } else {
+                    quote! {
+                        program.set_input(&#name);
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn is_advice_arg(&self, name: &Ident) -> bool {
+        parse_attributes(&self.attr).is_advice_arg(self.private_args.contains(name))
+    }
+
+    fn get_max_advice_size(&self) -> u64 {
+        parse_attributes(&self.attr).advice_size(self.has_advice_args)
+    }
+
+    /// Strips the `#[private]` attribute from the function's arguments, returning the names
+    /// of the arguments it was on.
+    fn take_private_args(func: &mut ItemFn) -> Vec<Ident> {
+        let mut private_args = Vec::new();
+        for arg in func.sig.inputs.iter_mut() {
+            if let syn::FnArg::Typed(PatType { attrs, pat, .. }) = arg {
+                let len = attrs.len();
+                attrs.retain(|attr| !attr.path.is_ident("private"));
+                if attrs.len() != len {
+                    match pat.as_ref() {
+                        syn::Pat::Ident(pat_ident) => private_args.push(pat_ident.ident.clone()),
+                        _ => panic!("cannot parse arg"),
+                    }
+                }
+            }
+        }
+
+        private_args
+    }
+
     fn get_func_args(func: &ItemFn) -> Vec<(Ident, Box<Type>)> {
         let mut args = Vec::new();
         for arg in &func.sig.inputs {
@@ -532,25 +891,21 @@ impl MacroBuilder {
     fn make_wasm_function(&self) -> TokenStream2 {
         let fn_name = self.get_func_name();
         let verify_wasm_fn_name = Ident::new(&format!("verify_{}", fn_name), fn_name.span());
+        let pcs_ty = self.get_pcs_type();
+        let proof_ty = self.get_proof_type();
+        let vm = self.get_vm();
 
         quote! {
             #[wasm_bindgen]
             #[cfg(all(target_arch = "wasm32", not(feature = "guest")))]
-            pub fn #verify_wasm_fn_name(preprocessing_data: &[u8], proof_bytes: &[u8]) -> bool {
-                use jolt::{Jolt, RV32IHyraxProof, RV32IJoltVM};
-
-                let decoded_preprocessing_data: DecodedData = deserialize_from_bin(preprocessing_data).unwrap();
-                let proof = RV32IHyraxProof::deserialize_from_bytes(proof_bytes).unwrap();
-
-                let preprocessing = RV32IJoltVM::preprocess(
-                    decoded_preprocessing_data.bytecode,
-                    decoded_preprocessing_data.memory_init,
-                    1 << 20,
-                    1 << 20,
-                    1 << 24,
-                );
+            pub fn #verify_wasm_fn_name(verifier_key_data: &[u8], proof_bytes: &[u8]) -> bool {
+
+                let verifier_key =
+                    jolt::JoltVerifierKey::<jolt::F, #pcs_ty>::deserialize_from_bytes(verifier_key_data)
+                        .unwrap();
+                let proof = <#proof_ty>::deserialize_from_bytes(proof_bytes).unwrap();
 
-                let result = RV32IJoltVM::verify(preprocessing, proof.proof, proof.commitments);
+                let result = #vm::verify(verifier_key, proof.proof, proof.commitments);
                 result.is_ok()
             }
         }
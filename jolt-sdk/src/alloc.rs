@@ -1,4 +1,5 @@
 use core::alloc::{GlobalAlloc, Layout};
+use core::ptr::{addr_of_mut, null_mut};
 
 pub struct BumpAllocator;
 
@@ -10,8 +11,111 @@ unsafe impl GlobalAlloc for BumpAllocator {
     unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
 }
 
+/// An allocator that reuses freed memory. Each allocation is rounded up to a
+/// power-of-two size class, and freed blocks are kept on a per-class free list.
+/// Fresh blocks are carved from the heap between `_HEAP_PTR` and `_HEAP_END`.
+/// Once the heap is exhausted allocation fails, which panics the guest through
+/// `handle_alloc_error`. Tracing still completes, with the panic flag set in the
+/// program I/O, so the out-of-memory condition is part of what gets proven.
+pub struct FreeListAllocator;
+
+struct FreeBlock {
+    next: *mut FreeBlock,
+}
+
+/// The smallest size class must be able to hold a free list link.
+const MIN_SIZE_CLASS: u32 = 3;
+const NUM_SIZE_CLASSES: usize = usize::BITS as usize;
+/// Fresh blocks are aligned to their size, up to this bound, so that most
+/// freed blocks can be handed back out regardless of the requested alignment.
+const BLOCK_ALIGN: usize = 16;
+
+static mut FREE_LIST_HEAP: FreeListHeap = FreeListHeap::new();
+
+fn size_class(layout: &Layout) -> Option<usize> {
+    let size = layout
+        .size()
+        .max(layout.align())
+        .max(1 << MIN_SIZE_CLASS)
+        .checked_next_power_of_two()?;
+    Some(size.trailing_zeros() as usize)
+}
+
+unsafe impl GlobalAlloc for FreeListAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let heap = unsafe { &mut *addr_of_mut!(FREE_LIST_HEAP) };
+        if heap.end == 0 {
+            heap.next = unsafe { (&_HEAP_PTR) as *const u8 as usize };
+            heap.end = unsafe { (&_HEAP_END) as *const u8 as usize };
+        }
+        unsafe { heap.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { (*addr_of_mut!(FREE_LIST_HEAP)).dealloc(ptr, layout) }
+    }
+}
+
+/// The state of `FreeListAllocator`: its free lists, and the part of the heap
+/// between `next` and `end` that has never been handed out.
+struct FreeListHeap {
+    free_lists: [*mut FreeBlock; NUM_SIZE_CLASSES],
+    next: usize,
+    end: usize,
+}
+
+impl FreeListHeap {
+    const fn new() -> Self {
+        Self {
+            free_lists: [null_mut(); NUM_SIZE_CLASSES],
+            next: 0,
+            end: 0,
+        }
+    }
+
+    unsafe fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        let class = match size_class(&layout) {
+            Some(class) => class,
+            None => return null_mut(),
+        };
+
+        let head = self.free_lists[class];
+        if !head.is_null() && head as usize & (layout.align() - 1) == 0 {
+            self.free_lists[class] = unsafe { (*head).next };
+            return head as *mut u8;
+        }
+
+        let size = 1 << class;
+        self.bump(size, layout.align().max(size.min(BLOCK_ALIGN)))
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        // `alloc` succeeded for this layout, so it has a size class
+        let class = size_class(&layout).unwrap();
+        let block = ptr as *mut FreeBlock;
+        unsafe { (*block).next = self.free_lists[class] };
+        self.free_lists[class] = block;
+    }
+
+    /// Bumps `next`, returning null if the block would run past `end`.
+    fn bump(&mut self, size: usize, align: usize) -> *mut u8 {
+        let start = match self.next.checked_add(align - 1) {
+            Some(addr) => addr & !(align - 1),
+            None => return null_mut(),
+        };
+        match start.checked_add(size) {
+            Some(end) if end <= self.end => {
+                self.next = end;
+                start as *mut u8
+            }
+            _ => null_mut(),
+        }
+    }
+}
+
 extern "C" {
     static _HEAP_PTR: u8;
+    static _HEAP_END: u8;
 }
 
 static mut ALLOC_NEXT: usize = 0;
@@ -37,3 +141,84 @@ pub unsafe extern "C" fn sys_alloc(size: usize, align: usize) -> *mut u8 {
 fn align_up(addr: usize, align: usize) -> usize {
     (addr + align - 1) & !(align - 1)
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::{vec, vec::Vec};
+
+    /// A heap over a fresh host buffer of `size` bytes, starting `offset` bytes past a
+    /// 64-byte boundary.
+    fn heap(buffer: &mut Vec<u8>, size: usize, offset: usize) -> FreeListHeap {
+        *buffer = vec![0; size + offset + 64];
+        let start = (buffer.as_ptr() as usize).next_multiple_of(64) + offset;
+        let mut heap = FreeListHeap::new();
+        heap.next = start;
+        heap.end = start + size;
+        heap
+    }
+
+    fn layout(size: usize, align: usize) -> Layout {
+        Layout::from_size_align(size, align).unwrap()
+    }
+
+    #[test]
+    fn size_classes() {
+        assert_eq!(size_class(&layout(1, 1)), Some(3));
+        assert_eq!(size_class(&layout(8, 8)), Some(3));
+        assert_eq!(size_class(&layout(9, 1)), Some(4));
+        assert_eq!(size_class(&layout(100, 4)), Some(7));
+        // Alignment counts towards the size
+        assert_eq!(size_class(&layout(4, 64)), Some(6));
+    }
+
+    #[test]
+    fn reuses_freed_blocks() {
+        let mut buffer = Vec::new();
+        let mut heap = heap(&mut buffer, 1024, 0);
+        unsafe {
+            let a = heap.alloc(layout(12, 4));
+            let b = heap.alloc(layout(16, 4));
+            assert!(!a.is_null() && !b.is_null() && a != b);
+
+            heap.dealloc(a, layout(12, 4));
+            // Blocks are only reused within their size class
+            let c = heap.alloc(layout(32, 4));
+            assert_ne!(c, a);
+            assert_eq!(heap.alloc(layout(16, 8)), a);
+        }
+    }
+
+    #[test]
+    fn aligns_blocks() {
+        let mut buffer = Vec::new();
+        let mut heap = heap(&mut buffer, 1024, 8);
+        unsafe {
+            let a = heap.alloc(layout(1, 1));
+            assert_eq!(a as usize % 8, 0);
+            let b = heap.alloc(layout(64, 16));
+            assert_eq!(b as usize % 16, 0);
+            assert_ne!(b as usize % 64, 0);
+
+            // A freed block that is not aligned enough is not handed out again
+            heap.dealloc(b, layout(64, 16));
+            let c = heap.alloc(layout(64, 64));
+            assert_eq!(c as usize % 64, 0);
+            assert_ne!(c, b);
+        }
+    }
+
+    #[test]
+    fn returns_null_when_exhausted() {
+        let mut buffer = Vec::new();
+        let mut heap = heap(&mut buffer, 64, 0);
+        unsafe {
+            assert!(!heap.alloc(layout(32, 4)).is_null());
+            assert!(!heap.alloc(layout(32, 4)).is_null());
+            assert!(heap.alloc(layout(8, 4)).is_null());
+            assert!(heap.alloc(layout(128, 4)).is_null());
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Debug output for guests. Text printed here is captured by the host in
+//! `JoltDevice::console`, and is not part of the proven program I/O.
+
+#[cfg(not(feature = "host"))]
+extern "C" {
+    fn jolt_print(ptr: *const u8, len: usize);
+}
+
+/// Writes `s` to the console.
+pub fn print_str(s: &str) {
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        jolt_print(s.as_ptr(), s.len());
+    }
+
+    #[cfg(feature = "host")]
+    std::print!("{}", s);
+}
+
+/// `core::fmt::Write` adapter for the console, used by `print!` and `println!`.
+pub struct Console;
+
+impl core::fmt::Write for Console {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        print_str(s);
+        Ok(())
+    }
+}
+
+/// Prints to the guest console.
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => {{
+        let _ = core::fmt::Write::write_fmt(&mut $crate::Console, format_args!($($arg)*));
+    }};
+}
+
+/// Prints to the guest console, with a newline.
+#[macro_export]
+macro_rules! println {
+    () => {
+        $crate::print!("\n")
+    };
+    ($($arg:tt)*) => {{
+        $crate::print!($($arg)*);
+        $crate::print!("\n");
+    }};
+}
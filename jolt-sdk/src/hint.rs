@@ -0,0 +1,59 @@
+//! Untrusted hints from the host. A guest can ask a provider registered with
+//! `Program::set_hint_provider` to compute something on its behalf, e.g. a square root or a
+//! signature that the guest then verifies. Responses are read from the advice region, so
+//! the guest must check them before relying on them.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(not(feature = "host"))]
+extern "C" {
+    fn jolt_hint_write(ptr: *const u8, len: usize);
+    fn jolt_hint_read(len: *mut usize) -> *const u8;
+}
+
+/// Streams serialized bytes straight to the hint port.
+#[cfg(not(feature = "host"))]
+struct HintWriter;
+
+#[cfg(not(feature = "host"))]
+impl postcard::ser_flavors::Flavor for HintWriter {
+    type Output = ();
+
+    fn try_push(&mut self, data: u8) -> postcard::Result<()> {
+        self.try_extend(&[data])
+    }
+
+    fn try_extend(&mut self, data: &[u8]) -> postcard::Result<()> {
+        unsafe { jolt_hint_write(data.as_ptr(), data.len()) };
+        Ok(())
+    }
+
+    fn finalize(self) -> postcard::Result<()> {
+        Ok(())
+    }
+}
+
+/// Requests a hint from the host provider registered under `name`.
+///
+/// Panics if the response cannot be deserialized as `Resp`.
+pub fn hint<Req: Serialize, Resp: DeserializeOwned>(name: &str, request: &Req) -> Resp {
+    #[cfg(not(feature = "host"))]
+    {
+        unsafe { jolt_hint_write(name.as_ptr(), name.len()) };
+        unsafe { jolt_hint_write([0u8].as_ptr(), 1) };
+        postcard::serialize_with_flavor(request, HintWriter).unwrap();
+
+        let mut len = 0;
+        let response = unsafe {
+            let ptr = jolt_hint_read(&mut len);
+            core::slice::from_raw_parts(ptr, len)
+        };
+        postcard::from_bytes(response).unwrap()
+    }
+
+    #[cfg(feature = "host")]
+    {
+        let _ = (name, request);
+        panic!("hints are only available to guests")
+    }
+}
@@ -1,6 +1,12 @@
-pub use ark_bn254::{Fr as F, G1Projective as G};
+pub use ark_bn254::{Bn254, Fr as F, G1Projective as G};
 pub use ark_ec::CurveGroup;
-pub use jolt_core::{field::JoltField, poly::commitment::hyrax::HyraxScheme};
+pub use jolt_core::{
+    field::JoltField,
+    poly::commitment::{
+        commitment_scheme::CommitmentScheme, hyperkzg::HyperKZG, hyrax::HyraxScheme,
+        zeromorph::Zeromorph,
+    },
+};
 
 pub use common::{
     constants::MEMORY_OPS_PER_INSTRUCTION,
@@ -10,7 +16,11 @@ pub use jolt_core::host;
 pub use jolt_core::jolt::instruction;
 pub use jolt_core::jolt::vm::{
     bytecode::BytecodeRow,
-    rv32i_vm::{RV32IHyraxProof, RV32IJoltProof, RV32IJoltVM, PCS, RV32I},
-    Jolt, JoltCommitments, JoltPreprocessing, JoltProof,
+    rv32i_vm::{
+        RV32IHyperKZGProof, RV32IHyraxProof, RV32IJoltProof, RV32IJoltVM, RV32IProof,
+        RV32IZeromorphProof, C, M, PCS, RV32I,
+    },
+    Jolt, JoltCommitments, JoltPreprocessing, JoltProof, JoltSegment, JoltVMProof, JoltVerifierKey,
+    SegmentProof,
 };
 pub use tracer;
@@ -0,0 +1,72 @@
+//! Hashed program I/O. A function marked `#[jolt::provable(hash_io)]` doesn't reveal its
+//! arguments and return value to the verifier; instead, the guest hashes them and the proof
+//! attests to the resulting digest, computed by `io_digest`.
+
+use serde::Serialize;
+use sha3::{Digest, Keccak256};
+
+/// Computes the digest a guest with hashed I/O commits to, given the concatenated
+/// postcard-serialized public arguments and the serialized return value:
+/// `keccak256(keccak256(inputs) || keccak256(outputs))`.
+pub fn io_digest(inputs: &[u8], outputs: &[u8]) -> [u8; 32] {
+    io_digest_from_hashes(
+        Keccak256::digest(inputs).into(),
+        Keccak256::digest(outputs).into(),
+    )
+}
+
+/// Like `io_digest`, but from the Keccak-256 hashes of the inputs and outputs.
+pub fn io_digest_from_hashes(input_hash: [u8; 32], output_hash: [u8; 32]) -> [u8; 32] {
+    Keccak256::new()
+        .chain_update(input_hash)
+        .chain_update(output_hash)
+        .finalize()
+        .into()
+}
+
+/// Serializes `value` into `buf` like `postcard::to_slice`, returning the Keccak-256 hash of
+/// the serialized bytes. Used by guests with hashed I/O to hash their return value as it is
+/// written, since the output region can't be read back.
+pub fn to_slice_hashed<T: Serialize + ?Sized>(
+    value: &T,
+    buf: &mut [u8],
+) -> postcard::Result<[u8; 32]> {
+    postcard::serialize_with_flavor(
+        value,
+        HashingSlice {
+            buf,
+            len: 0,
+            hasher: Keccak256::new(),
+        },
+    )
+}
+
+/// A postcard flavor that writes to a slice and hashes what it writes.
+struct HashingSlice<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+    hasher: Keccak256,
+}
+
+impl postcard::ser_flavors::Flavor for HashingSlice<'_> {
+    type Output = [u8; 32];
+
+    fn try_push(&mut self, data: u8) -> postcard::Result<()> {
+        self.try_extend(&[data])
+    }
+
+    fn try_extend(&mut self, data: &[u8]) -> postcard::Result<()> {
+        let end = self.len + data.len();
+        if end > self.buf.len() {
+            return Err(postcard::Error::SerializeBufferFull);
+        }
+        self.buf[self.len..end].copy_from_slice(data);
+        self.hasher.update(data);
+        self.len = end;
+        Ok(())
+    }
+
+    fn finalize(self) -> postcard::Result<[u8; 32]> {
+        Ok(self.hasher.finalize().into())
+    }
+}
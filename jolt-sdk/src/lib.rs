@@ -4,6 +4,7 @@ extern crate jolt_sdk_macros;
 
 pub use jolt_sdk_macros::provable;
 pub use postcard;
+pub use sha3;
 
 #[cfg(feature = "host")]
 pub mod host_utils;
@@ -12,3 +13,12 @@ pub use host_utils::*;
 
 pub mod alloc;
 pub use alloc::*;
+
+pub mod console;
+pub use console::*;
+
+pub mod hint;
+pub use hint::*;
+
+pub mod io;
+pub use io::*;
@@ -1,4 +1,7 @@
-use common::attributes::{parse_attributes, Attributes};
+use common::{
+    attributes::{parse_attributes, Attributes, CommitmentSchemeAttribute},
+    rv_trace::MemoryLayout,
+};
 
 use std::{
     fs::{self, File},
@@ -6,55 +9,101 @@ use std::{
     path::Path,
 };
 
+use ark_bn254::{Bn254, Fr, G1Projective};
 use eyre::Result;
-use jolt_core::host::{ELFInstruction, Program};
-use rmp_serde::Serializer;
-use serde::{Deserialize, Serialize};
+use jolt_core::{
+    host::{ELFInstruction, Program},
+    jolt::vm::{
+        rv32i_vm::{RV32IJoltVM, C, M},
+        Jolt, JoltPreprocessing,
+    },
+    poly::commitment::{
+        commitment_scheme::CommitmentScheme, hyperkzg::HyperKZG, hyrax::HyraxScheme,
+        zeromorph::Zeromorph,
+    },
+};
 use syn::{Attribute, ItemFn, Meta, PathSegment};
 use toml_edit::{value, Array, DocumentMut, InlineTable, Item, Table, Value};
 
-#[derive(Serialize, Deserialize)]
-struct DecodedData {
-    bytecode: Vec<ELFInstruction>,
-    memory_init: Vec<(u64, u8)>,
-}
-
 struct FunctionAttributes {
     pub func_name: String,
     pub attributes: Attributes,
+    /// Whether any of the function's arguments is passed as advice.
+    pub has_advice_args: bool,
 }
 
-fn preprocess_and_save(func_name: &str, attributes: &Attributes, is_std: bool) -> Result<()> {
+/// The guest program of `function`, configured as the `#[jolt::provable]` macro configures it
+/// for proving, so that the verifier key matches the prover's.
+fn program(function: &FunctionAttributes, is_std: bool) -> Program {
+    let attributes = &function.attributes;
     let mut program = Program::new("guest");
 
-    program.set_func(func_name);
+    program.set_func(&function.func_name);
     program.set_std(is_std);
-    program.set_memory_size(attributes.memory_size);
-    program.set_stack_size(attributes.stack_size);
-    program.set_max_input_size(attributes.max_input_size);
-    program.set_max_output_size(attributes.max_output_size);
+    program.set_memory_config(attributes.memory_config(function.has_advice_args));
+    program.set_max_cycles(attributes.max_cycles);
+    program.set_hash_io(attributes.hash_io);
+    program
+}
+
+fn preprocess_and_save(function: &FunctionAttributes, is_std: bool) -> Result<()> {
+    if let Some(vm) = &function.attributes.vm {
+        eyre::bail!(
+            "{} is proven with the custom vm {}, but only RV32IJoltVM is supported",
+            function.func_name,
+            vm
+        );
+    }
+
+    let mut program = program(function, is_std);
 
     let (bytecode, memory_init) = program.decode();
-    let decoded_data = DecodedData {
-        bytecode,
-        memory_init,
+    let memory_layout = program.memory_layout();
+    let verifier_key = match function.attributes.pcs {
+        CommitmentSchemeAttribute::Hyrax => {
+            verifier_key_bytes::<HyraxScheme<G1Projective>>(bytecode, memory_init, memory_layout)?
+        }
+        CommitmentSchemeAttribute::HyperKZG => {
+            verifier_key_bytes::<HyperKZG<Bn254>>(bytecode, memory_init, memory_layout)?
+        }
+        CommitmentSchemeAttribute::Zeromorph => {
+            verifier_key_bytes::<Zeromorph<Bn254>>(bytecode, memory_init, memory_layout)?
+        }
     };
 
-    let mut buf = Vec::new();
-    decoded_data.serialize(&mut Serializer::new(&mut buf))?;
-
     let target_dir = Path::new("target/wasm32-unknown-unknown/release");
     fs::create_dir_all(target_dir)?;
 
-    let output_path = target_dir.join(format!("preprocessed_{}.bin", func_name));
+    let output_path = target_dir.join(format!("verifier_key_{}.bin", function.func_name));
     let mut file = File::create(output_path)?;
-    file.write_all(&buf)?;
+    file.write_all(&verifier_key)?;
     Ok(())
 }
 
+fn verifier_key_bytes<PCS: CommitmentScheme<Field = Fr>>(
+    bytecode: Vec<ELFInstruction>,
+    memory_init: Vec<(u64, u8)>,
+    memory_layout: MemoryLayout,
+) -> Result<Vec<u8>> {
+    let preprocessing: JoltPreprocessing<Fr, PCS> =
+        <RV32IJoltVM as Jolt<Fr, PCS, C, M>>::preprocess(
+            bytecode,
+            memory_init,
+            memory_layout,
+            1 << 20,
+            1 << 20,
+            1 << 24,
+        )?;
+    preprocessing.verifier_key().serialize_to_bytes()
+}
+
 fn extract_provable_functions() -> Vec<FunctionAttributes> {
     let content = fs::read_to_string("guest/src/lib.rs").expect("Unable to read file");
-    let syntax: syn::File = syn::parse_file(&content).expect("Unable to parse file");
+    parse_provable_functions(&content)
+}
+
+fn parse_provable_functions(content: &str) -> Vec<FunctionAttributes> {
+    let syntax: syn::File = syn::parse_file(content).expect("Unable to parse file");
 
     syntax
         .items
@@ -68,6 +117,7 @@ fn extract_provable_functions() -> Vec<FunctionAttributes> {
                             parse_attributes(&meta_list.nested.iter().cloned().collect());
                         return Some(FunctionAttributes {
                             func_name: sig.ident.to_string(),
+                            has_advice_args: attributes.has_advice_args(sig),
                             attributes,
                         });
                     }
@@ -176,12 +226,12 @@ fn create_index_html(func_names: Vec<String>) -> Result<()> {
                     const proofArrayBuffer = event.target.result;
                     const proofData = new Uint8Array(proofArrayBuffer);
 
-                    // Fetch preprocessing data and prepare wasm binary to json conversion
-                    const response = await fetch('target/wasm32-unknown-unknown/release/preprocessed_{0}.bin')
-                    const wasmBinary = await response.arrayBuffer();
-                    const wasmData = new Uint8Array(wasmBinary);
+                    // Fetch the verifier key generated by `jolt build-wasm`
+                    const response = await fetch('target/wasm32-unknown-unknown/release/verifier_key_{0}.bin')
+                    const verifierKeyBuffer = await response.arrayBuffer();
+                    const verifierKeyData = new Uint8Array(verifierKeyBuffer);
 
-                    const result = verify_{0}(wasmData, proofData);
+                    const result = verify_{0}(verifierKeyData, proofData);
                     alert(result ? "Proof is valid!" : "Proof is invalid.");
                 }};
 
@@ -212,7 +262,6 @@ pub fn modify_cargo_toml(name: &str) -> Result<()> {
         });
         dependencies.insert("serde_json", toml_edit::value("1.0"));
         dependencies.insert("serde-wasm-bindgen", toml_edit::value("=0.6.5"));
-        dependencies.insert("rmp-serde", toml_edit::value("1.3.0"));
     }
 
     {
@@ -272,8 +321,7 @@ pub fn build_wasm() {
     let function_names: Vec<String> = functions.iter().map(|f| f.func_name.clone()).collect();
     let is_std = is_std().expect("Failed to check if std feature is enabled");
     for function in functions {
-        preprocess_and_save(&function.func_name, &function.attributes, is_std)
-            .expect("Failed to preprocess functions");
+        preprocess_and_save(&function, is_std).expect("Failed to preprocess functions");
     }
 
     create_index_html(function_names).expect("Failed to create example index.html");
@@ -311,3 +359,69 @@ const HTML_TAIL: &str = r#"
     </body>
 </html>
 "#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::constants::DEFAULT_MAX_ADVICE_SIZE;
+
+    const GUEST: &str = r#"
+        #[jolt::provable(wasm, hash_io)]
+        fn hashed(n: u32) -> u32 {
+            n
+        }
+
+        #[jolt::provable(wasm, max_input_size = 256)]
+        fn private(n: u32, #[private] secret: u32) -> u32 {
+            n + secret
+        }
+
+        #[jolt::provable(wasm)]
+        fn public(n: u32) -> u32 {
+            n
+        }
+    "#;
+
+    #[test]
+    fn layout_matches_macro() {
+        let functions = parse_provable_functions(GUEST);
+        assert_eq!(functions.len(), 3);
+
+        for function in &functions {
+            // The layout the macro configures the prover's `Program` with.
+            let expected = function.attributes.memory_layout(function.has_advice_args);
+            assert_eq!(program(function, false).memory_layout(), expected);
+        }
+
+        let hashed = program(&functions[0], false).memory_layout();
+        assert!(hashed.hash_io);
+        assert_eq!(hashed.max_advice_size, DEFAULT_MAX_ADVICE_SIZE);
+
+        let private = program(&functions[1], false).memory_layout();
+        assert!(!private.hash_io);
+        assert_eq!(private.max_advice_size, DEFAULT_MAX_ADVICE_SIZE);
+        assert_eq!(private.max_input_size, 256);
+
+        let public = program(&functions[2], false).memory_layout();
+        assert!(!public.hash_io);
+        assert_eq!(public.max_advice_size, 0);
+    }
+
+    #[test]
+    fn custom_vm_is_rejected() {
+        let functions = parse_provable_functions(
+            r#"
+            #[jolt::provable(wasm, vm = "my_vm::MyJoltVM", c = 8)]
+            fn custom(n: u32) -> u32 {
+                n
+            }
+            "#,
+        );
+        assert_eq!(
+            functions[0].attributes.vm.as_deref(),
+            Some("my_vm::MyJoltVM")
+        );
+        assert_eq!(functions[0].attributes.c, Some(8));
+        assert!(preprocess_and_save(&functions[0], false).is_err());
+    }
+}
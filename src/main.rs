@@ -3,6 +3,7 @@ mod build_wasm;
 use std::{
     fs::{self, File},
     io::Write,
+    path::PathBuf,
 };
 
 use clap::{Parser, Subcommand};
@@ -11,7 +12,7 @@ use rand::prelude::SliceRandom;
 use sysinfo::System;
 
 use build_wasm::{build_wasm, modify_cargo_toml};
-use jolt_core::host::toolchain;
+use jolt_core::host::{self, toolchain};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -31,17 +32,33 @@ enum Command {
         wasm: bool,
     },
     /// Installs the required RISC-V toolchains for Rust
-    InstallToolchain,
+    InstallToolchain {
+        /// Installs from a local toolchain archive or unpacked toolchain instead of downloading
+        #[arg(long)]
+        from: Option<PathBuf>,
+        /// Expected SHA-256 checksum of the archive passed with --from
+        #[arg(long, requires = "from")]
+        sha256: Option<String>,
+    },
     /// Handles preprocessing and generates WASM compatible files
     BuildWasm,
+    /// Checks that a guest ELF only contains instructions Jolt can prove
+    Check {
+        /// Path to the guest ELF
+        elf: PathBuf,
+        /// Address at which the guest's RAM starts, if not the default
+        #[arg(long, value_parser = parse_address)]
+        ram_start: Option<u64>,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
     match cli.command {
         Command::New { name, wasm } => create_project(name, wasm),
-        Command::InstallToolchain => install_toolchain(),
+        Command::InstallToolchain { from, sha256 } => install_toolchain(from, sha256),
         Command::BuildWasm => build_wasm(),
+        Command::Check { elf, ram_start } => check(elf, ram_start),
     }
 }
 
@@ -54,13 +71,38 @@ fn create_project(name: String, wasm: bool) {
     }
 }
 
-fn install_toolchain() {
-    if let Err(err) = toolchain::install_toolchain() {
+fn install_toolchain(from: Option<PathBuf>, sha256: Option<String>) {
+    let result = match from {
+        Some(path) => toolchain::install_toolchain_from(&path, sha256.as_deref()),
+        None => toolchain::install_toolchain(),
+    };
+    if let Err(err) = result {
         panic!("toolchain install failed: {}", err);
     }
     display_welcome();
 }
 
+fn check(elf: PathBuf, ram_start: Option<u64>) {
+    let mut program = host::Program::new("guest");
+    program.elf = Some(elf);
+    if let Some(ram_start) = ram_start {
+        program.set_ram_start(ram_start);
+    }
+    if let Err(err) = program.check() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+    println!("All instructions are supported");
+}
+
+/// Parses an address given in decimal or, with a `0x` prefix, in hexadecimal.
+fn parse_address(s: &str) -> Result<u64, std::num::ParseIntError> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
 fn create_folder_structure(name: &str) -> Result<()> {
     fs::create_dir(name)?;
     fs::create_dir(format!("{}/src", name))?;
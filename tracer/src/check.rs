@@ -0,0 +1,254 @@
+use std::fmt;
+
+use common::rv_trace::MemoryConfig;
+use object::{elf::SHF_ALLOC, Architecture, Object, ObjectSection, SectionFlags, SectionKind};
+
+use crate::decode::decode_raw;
+use crate::emulator::elf_analyzer::ElfAnalyzer;
+use crate::error::TraceError;
+
+/// An instruction in a guest ELF that Jolt cannot prove.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedInstruction {
+    pub address: u64,
+    pub word: u32,
+    pub kind: UnsupportedKind,
+    /// The function containing the instruction, if the ELF has a symbol table.
+    pub symbol: Option<String>,
+}
+
+/// Why an instruction was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnsupportedKind {
+    /// A valid RISC-V instruction outside of RV32IM, e.g. a floating-point or CSR instruction.
+    Extension(&'static str),
+    /// A 16-bit instruction from the C extension.
+    Compressed,
+    /// A word that does not decode to any known instruction.
+    Unknown,
+}
+
+impl fmt::Display for UnsupportedKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnsupportedKind::Extension(name) => write!(f, "{}", name.to_lowercase()),
+            UnsupportedKind::Compressed => write!(f, "compressed instruction"),
+            UnsupportedKind::Unknown => write!(f, "unknown instruction"),
+        }
+    }
+}
+
+impl fmt::Display for UnsupportedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:#x}: {:#010x} ({})",
+            self.address, self.word, self.kind
+        )?;
+        if let Some(symbol) = &self.symbol {
+            write!(f, " in {}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes every instruction in the text sections of `elf` at or above `ram_start` and returns
+/// the ones Jolt cannot prove, in address order. This is much cheaper than tracing the program
+/// and also catches instructions on paths that a particular input would not exercise.
+pub fn check(elf: &[u8], ram_start: u64) -> Result<Vec<UnsupportedInstruction>, TraceError> {
+    let obj = object::File::parse(elf).map_err(|_| TraceError::InvalidElf)?;
+    let symbols = function_symbols(elf);
+
+    let mut unsupported = Vec::new();
+    for section in obj.sections() {
+        if section.address() < ram_start || section.kind() != SectionKind::Text {
+            continue;
+        }
+        let raw_data = section.data().map_err(|_| TraceError::InvalidElf)?;
+
+        // Compressed instructions are only 2 bytes long, so instructions are only guaranteed
+        // to be 2-byte aligned.
+        let mut offset = 0;
+        while offset + 2 <= raw_data.len() {
+            let address = offset as u64 + section.address();
+            let low = u16::from_le_bytes([raw_data[offset], raw_data[offset + 1]]) as u32;
+            let (word, kind) = if low & 0b11 != 0b11 {
+                offset += 2;
+                (low, UnsupportedKind::Compressed)
+            } else {
+                let Some(bytes) = raw_data.get(offset..offset + 4) else {
+                    break;
+                };
+                offset += 4;
+                let word = u32::from_le_bytes(bytes.try_into().unwrap());
+                match decode_raw(word) {
+                    Ok(inst) if inst.trace.is_some() => continue,
+                    Ok(inst) => (word, UnsupportedKind::Extension(inst.name)),
+                    Err(()) => (word, UnsupportedKind::Unknown),
+                }
+            };
+            unsupported.push(UnsupportedInstruction {
+                address,
+                word,
+                kind,
+                symbol: symbol_at(&symbols, address),
+            });
+        }
+    }
+
+    unsupported.sort_by_key(|inst| inst.address);
+    Ok(unsupported)
+}
+
+/// Checks that `elf` is a 32-bit RISC-V executable whose sections and entry point all lie
+/// within the RAM described by `memory_config`. The tracer only loads sections from RAM, so a
+/// program linked elsewhere would silently run without them.
+pub fn check_layout(elf: &[u8], memory_config: &MemoryConfig) -> Result<(), TraceError> {
+    let obj = object::File::parse(elf).map_err(|_| TraceError::InvalidElf)?;
+    if obj.architecture() != Architecture::Riscv32 {
+        return Err(TraceError::InvalidElf);
+    }
+
+    let ram = memory_config.ram_start..memory_config.ram_start + memory_config.memory_size;
+    for section in obj.sections() {
+        let allocated = match section.flags() {
+            SectionFlags::Elf { sh_flags } => sh_flags & SHF_ALLOC as u64 != 0,
+            _ => false,
+        };
+        if !allocated || section.size() == 0 {
+            continue;
+        }
+        let start = section.address();
+        let end = start
+            .checked_add(section.size())
+            .ok_or(TraceError::InvalidElf)?;
+        if start < ram.start || end > ram.end {
+            return Err(TraceError::SectionOutOfRange {
+                section: section.name().unwrap_or_default().to_string(),
+                start,
+                end,
+            });
+        }
+    }
+
+    if !ram.contains(&obj.entry()) {
+        return Err(TraceError::EntryOutOfRange(obj.entry()));
+    }
+    Ok(())
+}
+
+/// Reads the function symbols of `elf`, sorted by address.
+fn function_symbols(elf: &[u8]) -> Vec<(u64, String)> {
+    let analyzer = ElfAnalyzer::new(elf.to_vec());
+    let header = analyzer.read_header();
+    let section_headers = analyzer.read_section_headers(&header);
+
+    let mut symbols = Vec::new();
+    for symbol_table in section_headers.iter().filter(|h| h.sh_type == 2) {
+        let Some(string_table) = section_headers.get(symbol_table.sh_link as usize) else {
+            continue;
+        };
+        let entries = analyzer.read_symbol_entries(&header, &vec![symbol_table]);
+        let map = analyzer.create_symbol_map(&entries, string_table);
+        symbols.extend(
+            map.into_iter()
+                // Skip mapping symbols ($x, $d) and local labels, which aren't functions
+                .filter(|(name, _)| !name.starts_with('$') && !name.starts_with(".L"))
+                .map(|(name, address)| (address, format!("{:#}", rustc_demangle::demangle(&name)))),
+        );
+    }
+    symbols.sort();
+    symbols
+}
+
+/// Returns the name of the symbol that most closely precedes `address`.
+fn symbol_at(symbols: &[(u64, String)], address: u64) -> Option<String> {
+    let index = symbols.partition_point(|(start, _)| *start <= address);
+    index.checked_sub(1).map(|i| symbols[i].1.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{elf_with_text, words};
+
+    const TEXT_ADDRESS: u64 = 0x8000_0000;
+
+    #[test]
+    fn check_accepts_supported_instructions() {
+        // addi a0, a0, 1; add a0, a0, a1; jalr zero, 0(ra)
+        let text = words(&[0x0015_0513, 0x00b5_0533, 0x0000_8067]);
+        let elf = elf_with_text(TEXT_ADDRESS as u32, &text, TEXT_ADDRESS as u32);
+        assert_eq!(check(&elf, TEXT_ADDRESS).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn check_rejects_unsupported_instructions() {
+        // c.nop; addi a0, a0, 1; fadd.d ft0, ft0, ft0; an undefined opcode
+        let mut text = 0x0001u16.to_le_bytes().to_vec();
+        text.extend(words(&[0x0015_0513, 0x0200_0053, 0x0000_007f]));
+        let elf = elf_with_text(TEXT_ADDRESS as u32, &text, TEXT_ADDRESS as u32);
+
+        let unsupported = check(&elf, TEXT_ADDRESS).unwrap();
+        let found: Vec<_> = unsupported
+            .iter()
+            .map(|inst| (inst.address, inst.word, inst.kind.clone()))
+            .collect();
+        // The compressed instruction must not shift the 4-byte instructions after it
+        assert_eq!(
+            found,
+            vec![
+                (TEXT_ADDRESS, 0x0001, UnsupportedKind::Compressed),
+                (
+                    TEXT_ADDRESS + 6,
+                    0x0200_0053,
+                    UnsupportedKind::Extension("FADD.D")
+                ),
+                (TEXT_ADDRESS + 10, 0x0000_007f, UnsupportedKind::Unknown),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_layout_accepts_program_in_ram() {
+        let config = MemoryConfig::default();
+        let elf = elf_with_text(TEXT_ADDRESS as u32, &words(&[0x13]), TEXT_ADDRESS as u32);
+        assert!(check_layout(&elf, &config).is_ok());
+    }
+
+    #[test]
+    fn check_layout_rejects_section_outside_ram() {
+        let config = MemoryConfig::default();
+        let elf = elf_with_text(0x1000, &words(&[0x13]), TEXT_ADDRESS as u32);
+        assert!(matches!(
+            check_layout(&elf, &config),
+            Err(TraceError::SectionOutOfRange {
+                start: 0x1000,
+                end: 0x1004,
+                ..
+            })
+        ));
+
+        // A section straddling the end of RAM
+        let ram_end = config.ram_start + config.memory_size;
+        let elf = elf_with_text(
+            ram_end as u32 - 4,
+            &words(&[0x13, 0x13]),
+            TEXT_ADDRESS as u32,
+        );
+        assert!(matches!(
+            check_layout(&elf, &config),
+            Err(TraceError::SectionOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn check_layout_rejects_entry_outside_ram() {
+        let config = MemoryConfig::default();
+        let elf = elf_with_text(TEXT_ADDRESS as u32, &words(&[0x13]), 0x1000);
+        assert!(matches!(
+            check_layout(&elf, &config),
+            Err(TraceError::EntryOutOfRange(0x1000))
+        ));
+    }
+}
@@ -84,6 +84,9 @@ pub struct Cpu {
     decode_cache: DecodeCache,
     unsigned_data_mask: u64,
     pub tracer: Rc<Tracer>,
+    /// The first exception raised while executing a program, along with the
+    /// address of the instruction that raised it.
+    exception: Option<(Trap, u64)>,
 }
 
 #[derive(Clone)]
@@ -101,11 +104,13 @@ pub enum PrivilegeMode {
     Machine,
 }
 
+#[derive(Clone)]
 pub struct Trap {
     pub trap_type: TrapType,
     pub value: u64, // Trap type specific value
 }
 
+#[derive(Clone)]
 #[allow(dead_code)]
 pub enum TrapType {
     InstructionAddressMisaligned,
@@ -190,7 +195,7 @@ fn _get_trap_type_name(trap_type: &TrapType) -> &'static str {
     }
 }
 
-fn get_trap_cause(trap: &Trap, xlen: &Xlen) -> u64 {
+pub(crate) fn get_trap_cause(trap: &Trap, xlen: &Xlen) -> u64 {
     let interrupt_bit = match xlen {
         Xlen::Bit32 => 0x80000000_u64,
         Xlen::Bit64 => 0x8000000000000000_u64,
@@ -245,6 +250,7 @@ impl Cpu {
             decode_cache: DecodeCache::new(),
             unsigned_data_mask: 0xffffffffffffffff,
             tracer,
+            exception: None,
         };
         cpu.x[0xb] = 0x1020; // I don't know why but Linux boot seems to require this initialization
         cpu.write_csr_raw(CSR_MISA_ADDRESS, 0x800000008014312f);
@@ -289,12 +295,23 @@ impl Cpu {
         self.pc
     }
 
+    /// Takes the first exception raised since the last call, if any, along
+    /// with the address of the instruction that raised it.
+    pub fn take_exception(&mut self) -> Option<(Trap, u64)> {
+        self.exception.take()
+    }
+
     /// Runs program one cycle. Fetch, decode, and execution are completed in a cycle so far.
     pub fn tick(&mut self) {
         let instruction_address = self.pc;
         match self.tick_operate() {
             Ok(()) => {}
-            Err(e) => self.handle_exception(e, instruction_address),
+            Err(e) => {
+                if self.exception.is_none() {
+                    self.exception = Some((e.clone(), instruction_address));
+                }
+                self.handle_exception(e, instruction_address)
+            }
         }
         self.mmu.tick(&mut self.csr[CSR_MIP_ADDRESS as usize]);
         self.handle_interrupt(self.pc);
@@ -1847,7 +1864,7 @@ fn trace_r(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs1: Some(normalize_register(f.rs1)),
         rs2: Some(normalize_register(f.rs2)),
         rd: Some(normalize_register(f.rd)),
-        virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
     }
 }
 
@@ -1860,7 +1877,7 @@ fn trace_i(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs1: Some(normalize_register(f.rs1)),
         rs2: None,
         rd: Some(normalize_register(f.rd)),
-        virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
     }
 }
 
@@ -1873,7 +1890,7 @@ fn trace_s(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs1: Some(normalize_register(f.rs1)),
         rs2: Some(normalize_register(f.rs2)),
         rd: None,
-        virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
     }
 }
 
@@ -1886,7 +1903,7 @@ fn trace_b(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs1: Some(normalize_register(f.rs1)),
         rs2: Some(normalize_register(f.rs2)),
         rd: None,
-        virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
     }
 }
 
@@ -1899,7 +1916,7 @@ fn trace_u(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs1: None,
         rs2: None,
         rd: Some(normalize_register(f.rd)),
-        virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
     }
 }
 
@@ -1913,7 +1930,7 @@ fn trace_j(inst: &Instruction, xlen: &Xlen, word: u32, address: u64) -> ELFInstr
         rs1: None,
         rs2: None,
         rd: Some(normalize_register(f.rd)),
-        virtual_sequence_index: None,
+        virtual_sequence_remaining: None,
     }
 }
 
@@ -4056,6 +4073,32 @@ mod test_cpu {
         assert_eq!(8, cpu.read_register(8));
     }
 
+    #[test]
+    fn take_exception() {
+        let mut cpu = create_cpu();
+        cpu.get_mut_mmu().init_memory(8);
+        cpu.update_pc(DRAM_BASE);
+
+        // Write "lw x1, 0(x0)" instruction, which loads from an unmapped address
+        match cpu.get_mut_mmu().store_word(DRAM_BASE, 0x00002083) {
+            Ok(()) => {}
+            Err(_e) => panic!("Failed to store"),
+        };
+
+        assert!(cpu.take_exception().is_none());
+        cpu.tick();
+
+        match cpu.take_exception() {
+            Some((trap, pc)) => {
+                assert!(matches!(trap.trap_type, TrapType::LoadAccessFault));
+                assert_eq!(0, trap.value);
+                assert_eq!(DRAM_BASE, pc);
+            }
+            None => panic!("Expected an exception"),
+        };
+        assert!(cpu.take_exception().is_none());
+    }
+
     #[test]
     fn tick_operate() {
         let mut cpu = create_cpu();
@@ -46,7 +46,7 @@ pub struct SectionHeader {
     pub sh_addr: u64,
     pub sh_offset: u64,
     pub sh_size: u64,
-    _sh_link: u32,
+    pub sh_link: u32,
     _sh_info: u32,
     _sh_addralign: u64,
     _sh_entsize: u64,
@@ -482,7 +482,7 @@ impl ElfAnalyzer {
                 sh_addr,
                 sh_offset,
                 sh_size,
-                _sh_link: sh_link,
+                sh_link,
                 _sh_info: sh_info,
                 _sh_addralign: sh_addralign,
                 _sh_entsize: sh_entsize,
@@ -21,6 +21,22 @@ impl Memory {
         }
     }
 
+    /// Returns the non-zero bytes of memory as (offset, value) pairs, sorted by offset.
+    pub fn nonzero_bytes(&self) -> Vec<(u64, u8)> {
+        let mut bytes = Vec::new();
+        for (index, word) in self.data.iter().enumerate() {
+            if *word == 0 {
+                continue;
+            }
+            for (i, byte) in word.to_le_bytes().into_iter().enumerate() {
+                if byte != 0 {
+                    bytes.push((index as u64 * 8 + i as u64, byte));
+                }
+            }
+        }
+        bytes
+    }
+
     /// Reads a byte from memory.
     ///
     /// # Arguments
@@ -163,6 +179,6 @@ impl Memory {
     /// # Arguments
     /// * `address`
     pub fn validate_address(&self, address: u64) -> bool {
-        (address as usize) < self.data.len()
+        ((address >> 3) as usize) < self.data.len()
     }
 }
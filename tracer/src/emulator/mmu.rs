@@ -8,8 +8,9 @@ extern crate fnv;
 
 use std::rc::Rc;
 
+use crate::hint::HintProviders;
 use crate::trace::Tracer;
-use common::rv_trace::{JoltDevice, MemoryState};
+use common::rv_trace::{JoltDevice, MemoryLayout, MemoryState};
 
 use self::fnv::FnvHashMap;
 
@@ -40,6 +41,10 @@ pub struct Mmu {
     uart: Uart,
 
     pub jolt_device: JoltDevice,
+    /// Providers for the guest's hint requests. If `None`, hint requests are ignored, as
+    /// when replaying an execution whose hint responses are already in the advice.
+    pub hint_providers: Option<HintProviders>,
+    hint_error: Option<String>,
     tracer: Rc<Tracer>,
 
     /// Address translation can be affected `mstatus` (MPRV, MPP in machine mode)
@@ -112,7 +117,9 @@ impl Mmu {
             plic: Plic::new(),
             clint: Clint::new(),
             uart: Uart::new(terminal),
-            jolt_device: JoltDevice::new(0, 0),
+            jolt_device: JoltDevice::new(MemoryLayout::new(0, 0, 0)),
+            hint_providers: None,
+            hint_error: None,
             tracer,
             mstatus: 0,
             page_cache_enabled: false,
@@ -139,6 +146,25 @@ impl Mmu {
         self.memory.init(capacity);
     }
 
+    /// Sets the address at which main memory starts. Addresses below it are mapped to
+    /// peripheral devices, including the Jolt I/O device.
+    ///
+    /// # Arguments
+    /// * `base`
+    pub fn set_dram_base(&mut self, base: u64) {
+        self.memory.dram_base = base;
+    }
+
+    /// Returns the address at which main memory starts.
+    pub fn dram_base(&self) -> u64 {
+        self.memory.dram_base
+    }
+
+    /// Returns the non-zero bytes of main memory as (address, value) pairs, sorted by address.
+    pub fn dump_memory(&self) -> Vec<(u64, u8)> {
+        self.memory.nonzero_bytes()
+    }
+
     /// Initializes Virtio block disk. This method is expected to be called only once.
     ///
     /// # Arguments
@@ -258,6 +284,12 @@ impl Mmu {
                 // translating an address only once.
                 let effective_address = self.get_effective_address(v_address);
                 match self.translate_address(effective_address, &MemoryAccessType::Execute) {
+                    Ok(p_address) if !self.validate_physical_address(p_address + width - 1) => {
+                        Err(Trap {
+                            trap_type: TrapType::InstructionAccessFault,
+                            value: effective_address,
+                        })
+                    }
                     Ok(p_address) => Ok(self.load_word_raw(p_address)),
                     Err(()) => Err(Trap {
                         trap_type: TrapType::InstructionPageFault,
@@ -285,7 +317,7 @@ impl Mmu {
     /// * `v_address` Virtual address
     pub fn load(&mut self, v_address: u64) -> Result<u8, Trap> {
         let effective_address = self.get_effective_address(v_address);
-        self.trace_load(effective_address, 1);
+        self.trace_load(effective_address, 1)?;
         match self.translate_address(effective_address, &MemoryAccessType::Read) {
             Ok(p_address) => Ok(self.load_raw(p_address)),
             Err(()) => Err(Trap {
@@ -345,7 +377,7 @@ impl Mmu {
     /// * `v_address` Virtual address
     pub fn load_halfword(&mut self, v_address: u64) -> Result<u16, Trap> {
         let effective_address = self.get_effective_address(v_address);
-        self.trace_load(effective_address, 2);
+        self.trace_load(effective_address, 2)?;
         match self.load_bytes(v_address, 2) {
             Ok(data) => Ok(data as u16),
             Err(e) => Err(e),
@@ -359,7 +391,7 @@ impl Mmu {
     /// * `v_address` Virtual address
     pub fn load_word(&mut self, v_address: u64) -> Result<u32, Trap> {
         let effective_address = self.get_effective_address(v_address);
-        self.trace_load(effective_address, 4);
+        self.trace_load(effective_address, 4)?;
         match self.load_bytes(v_address, 4) {
             Ok(data) => Ok(data as u32),
             Err(e) => Err(e),
@@ -373,7 +405,7 @@ impl Mmu {
     /// * `v_address` Virtual address
     pub fn load_doubleword(&mut self, v_address: u64) -> Result<u64, Trap> {
         let effective_address = self.get_effective_address(v_address);
-        self.trace_load(effective_address, 8);
+        self.trace_load(effective_address, 8)?;
         match self.load_bytes(v_address, 8) {
             Ok(data) => Ok(data),
             Err(e) => Err(e),
@@ -388,7 +420,7 @@ impl Mmu {
     /// * `value`
     pub fn store(&mut self, v_address: u64, value: u8) -> Result<(), Trap> {
         let effective_address = self.get_effective_address(v_address);
-        self.trace_store(effective_address, value as u64);
+        self.trace_store(effective_address, value as u64, 1)?;
         match self.translate_address(v_address, &MemoryAccessType::Write) {
             Ok(p_address) => {
                 self.store_raw(p_address, value);
@@ -453,7 +485,7 @@ impl Mmu {
     /// * `value` data written
     pub fn store_halfword(&mut self, v_address: u64, value: u16) -> Result<(), Trap> {
         let effective_address = self.get_effective_address(v_address);
-        self.trace_store(effective_address, value as u64);
+        self.trace_store(effective_address, value as u64, 2)?;
         self.store_bytes(v_address, value as u64, 2)
     }
 
@@ -465,7 +497,7 @@ impl Mmu {
     /// * `value` data written
     pub fn store_word(&mut self, v_address: u64, value: u32) -> Result<(), Trap> {
         let effective_address = self.get_effective_address(v_address);
-        self.trace_store(effective_address, value as u64);
+        self.trace_store(effective_address, value as u64, 4)?;
         self.store_bytes(v_address, value as u64, 4)
     }
 
@@ -477,7 +509,7 @@ impl Mmu {
     /// * `value` data written
     pub fn store_doubleword(&mut self, v_address: u64, value: u64) -> Result<(), Trap> {
         let effective_address = self.get_effective_address(v_address);
-        self.trace_store(effective_address, value);
+        self.trace_store(effective_address, value, 8)?;
         self.store_bytes(v_address, value, 8)
     }
 
@@ -489,7 +521,7 @@ impl Mmu {
     fn load_raw(&mut self, p_address: u64) -> u8 {
         let effective_address = self.get_effective_address(p_address);
         // @TODO: Mapping should be configurable with dtb
-        match effective_address >= DRAM_BASE {
+        match effective_address >= self.memory.dram_base {
             true => self.memory.read_byte(effective_address),
             false => match effective_address {
                 // I don't know why but dtb data seems to be stored from 0x1020 on Linux.
@@ -501,7 +533,10 @@ impl Mmu {
                 0x10000000..=0x100000ff => self.uart.load(effective_address),
                 0x10001000..=0x10001FFF => self.disk.load(effective_address),
                 _ => {
-                    if self.jolt_device.is_input(effective_address) {
+                    if self.jolt_device.is_input(effective_address)
+                        || self.jolt_device.is_advice(effective_address)
+                        || self.jolt_device.is_hint_service(effective_address)
+                    {
                         self.jolt_device.load(effective_address)
                     } else {
                         panic!("Unknown memory mapping {:X}.", effective_address);
@@ -511,9 +546,15 @@ impl Mmu {
         }
     }
 
-    fn trace_load(&mut self, effective_address: u64, bytes: u64) {
-        if effective_address < DRAM_BASE {
-            if self.jolt_device.is_input(effective_address) {
+    fn trace_load(&mut self, effective_address: u64, bytes: u64) -> Result<(), Trap> {
+        if effective_address < self.memory.dram_base {
+            if self.jolt_device.is_hint_service(effective_address) {
+                self.service_hint();
+            }
+            if self.jolt_device.is_input(effective_address)
+                || self.jolt_device.is_advice(effective_address)
+                || self.jolt_device.is_hint_service(effective_address)
+            {
                 let mut value_bytes = [0u8; 8];
                 for i in 0..bytes {
                     value_bytes[i as usize] = self.jolt_device.load(effective_address + i);
@@ -524,9 +565,18 @@ impl Mmu {
                     value,
                 });
             } else {
-                panic!("Unknown memory mapping {:X}.", effective_address);
+                return Err(Trap {
+                    trap_type: TrapType::LoadAccessFault,
+                    value: effective_address,
+                });
             }
         } else {
+            if !self.memory.validate_address(effective_address + bytes - 1) {
+                return Err(Trap {
+                    trap_type: TrapType::LoadAccessFault,
+                    value: effective_address,
+                });
+            }
             let mut value_bytes = [0u8; 8];
             for i in 0..bytes {
                 value_bytes[i as usize] = self.memory.read_byte(effective_address + i);
@@ -537,26 +587,57 @@ impl Mmu {
                 value,
             });
         }
+        Ok(())
     }
 
-    fn trace_store(&mut self, effective_address: u64, value: u64) {
-        if effective_address < DRAM_BASE {
-            if self.jolt_device.is_output(effective_address)
-                || self.jolt_device.is_panic(effective_address)
+    /// Responds to the guest's pending hint request by appending the response to its advice.
+    fn service_hint(&mut self) {
+        let request = std::mem::take(&mut self.jolt_device.hint_request);
+        let providers = match &self.hint_providers {
+            Some(providers) => providers,
+            None => return,
+        };
+        match providers.respond(&request) {
+            Ok(response)
+                if (self.jolt_device.advice.len() + 4 + response.len()) as u64
+                    <= self.jolt_device.memory_layout.max_advice_size =>
             {
-                self.tracer.push_memory(MemoryState::Write {
-                    address: effective_address,
-                    post_value: value,
-                });
-            } else {
-                panic!("Unknown memory mapping {:X}.", effective_address);
+                self.jolt_device.push_hint_response(&response);
+            }
+            Ok(_) => {
+                self.hint_error = Some("hint responses exceed the maximum advice size".to_string())
             }
+            Err(error) => self.hint_error = Some(error),
+        }
+    }
+
+    /// Returns the error from the last hint request, if it failed.
+    pub fn take_hint_error(&mut self) -> Option<String> {
+        self.hint_error.take()
+    }
+
+    fn trace_store(&mut self, effective_address: u64, value: u64, bytes: u64) -> Result<(), Trap> {
+        let is_mapped = if effective_address < self.memory.dram_base {
+            self.jolt_device.is_output(effective_address)
+                || self.jolt_device.is_panic(effective_address)
+                || self.jolt_device.is_termination(effective_address)
+                || self.jolt_device.is_console(effective_address)
+                || self.jolt_device.is_hint(effective_address)
+                || self.jolt_device.is_diagnostics(effective_address)
         } else {
-            self.tracer.push_memory(MemoryState::Write {
-                address: effective_address,
-                post_value: value,
+            self.memory.validate_address(effective_address + bytes - 1)
+        };
+        if !is_mapped {
+            return Err(Trap {
+                trap_type: TrapType::StoreAccessFault,
+                value: effective_address,
             });
         }
+        self.tracer.push_memory(MemoryState::Write {
+            address: effective_address,
+            post_value: value,
+        });
+        Ok(())
     }
 
     /// Loads two bytes from main memory or peripheral devices depending on
@@ -566,7 +647,7 @@ impl Mmu {
     /// * `p_address` Physical address
     fn load_halfword_raw(&mut self, p_address: u64) -> u16 {
         let effective_address = self.get_effective_address(p_address);
-        match effective_address >= DRAM_BASE
+        match effective_address >= self.memory.dram_base
             && effective_address.wrapping_add(1) > effective_address
         {
             // Fast path. Directly load main memory at a time.
@@ -588,7 +669,7 @@ impl Mmu {
     /// * `p_address` Physical address
     pub fn load_word_raw(&mut self, p_address: u64) -> u32 {
         let effective_address = self.get_effective_address(p_address);
-        match effective_address >= DRAM_BASE
+        match effective_address >= self.memory.dram_base
             && effective_address.wrapping_add(3) > effective_address
         {
             // Fast path. Directly load main memory at a time.
@@ -610,7 +691,7 @@ impl Mmu {
     /// * `p_address` Physical address
     fn load_doubleword_raw(&mut self, p_address: u64) -> u64 {
         let effective_address = self.get_effective_address(p_address);
-        match effective_address >= DRAM_BASE
+        match effective_address >= self.memory.dram_base
             && effective_address.wrapping_add(7) > effective_address
         {
             // Fast path. Directly load main memory at a time.
@@ -634,7 +715,7 @@ impl Mmu {
     pub fn store_raw(&mut self, p_address: u64, value: u8) {
         let effective_address = self.get_effective_address(p_address);
         // @TODO: Mapping should be configurable with dtb
-        match effective_address >= DRAM_BASE {
+        match effective_address >= self.memory.dram_base {
             true => self.memory.write_byte(effective_address, value),
             false => match effective_address {
                 0x02000000..=0x0200ffff => self.clint.store(effective_address, value),
@@ -644,6 +725,10 @@ impl Mmu {
                 _ => {
                     if self.jolt_device.is_output(effective_address)
                         || self.jolt_device.is_panic(effective_address)
+                        || self.jolt_device.is_termination(effective_address)
+                        || self.jolt_device.is_console(effective_address)
+                        || self.jolt_device.is_hint(effective_address)
+                        || self.jolt_device.is_diagnostics(effective_address)
                     {
                         self.jolt_device.store(effective_address, value);
                     } else {
@@ -662,7 +747,7 @@ impl Mmu {
     /// * `value` data written
     fn store_halfword_raw(&mut self, p_address: u64, value: u16) {
         let effective_address = self.get_effective_address(p_address);
-        match effective_address >= DRAM_BASE
+        match effective_address >= self.memory.dram_base
             && effective_address.wrapping_add(1) > effective_address
         {
             // Fast path. Directly store to main memory at a time.
@@ -686,7 +771,7 @@ impl Mmu {
     /// * `value` data written
     fn store_word_raw(&mut self, p_address: u64, value: u32) {
         let effective_address = self.get_effective_address(p_address);
-        match effective_address >= DRAM_BASE
+        match effective_address >= self.memory.dram_base
             && effective_address.wrapping_add(3) > effective_address
         {
             // Fast path. Directly store to main memory at a time.
@@ -710,7 +795,7 @@ impl Mmu {
     /// * `value` data written
     fn store_doubleword_raw(&mut self, p_address: u64, value: u64) {
         let effective_address = self.get_effective_address(p_address);
-        match effective_address >= DRAM_BASE
+        match effective_address >= self.memory.dram_base
             && effective_address.wrapping_add(7) > effective_address
         {
             // Fast path. Directly store to main memory at a time.
@@ -737,8 +822,16 @@ impl Mmu {
             Ok(address) => address,
             Err(()) => return Err(()),
         };
+        Ok(self.validate_physical_address(p_address))
+    }
+
+    /// Checks if passed physical address points to main memory or a peripheral device.
+    ///
+    /// # Arguments
+    /// * `p_address` Physical address
+    fn validate_physical_address(&self, p_address: u64) -> bool {
         let effective_address = self.get_effective_address(p_address);
-        let valid = match effective_address >= DRAM_BASE {
+        match effective_address >= self.memory.dram_base {
             true => self.memory.validate_address(effective_address),
             false => matches!(
                 effective_address,
@@ -748,8 +841,7 @@ impl Mmu {
                 0x10000000..=0x100000ff |
                 0x10001000..=0x10001FFF
             ),
-        };
-        Ok(valid)
+        }
     }
 
     fn translate_address(
@@ -1015,9 +1107,11 @@ impl Mmu {
 }
 
 /// [`Memory`](../memory/struct.Memory.html) wrapper. Converts physical address to the one in memory
-/// using [`DRAM_BASE`](constant.DRAM_BASE.html) and accesses [`Memory`](../memory/struct.Memory.html).
+/// using its base address, [`DRAM_BASE`](constant.DRAM_BASE.html) unless the program's RAM starts
+/// elsewhere, and accesses [`Memory`](../memory/struct.Memory.html).
 pub struct MemoryWrapper {
     memory: Memory,
+    dram_base: u64,
     tracer: Rc<Tracer>,
 }
 
@@ -1025,6 +1119,7 @@ impl MemoryWrapper {
     fn new(tracer: Rc<Tracer>) -> Self {
         MemoryWrapper {
             memory: Memory::new(),
+            dram_base: DRAM_BASE,
             tracer,
         }
     }
@@ -1033,87 +1128,97 @@ impl MemoryWrapper {
         self.memory.init(capacity);
     }
 
+    fn nonzero_bytes(&self) -> Vec<(u64, u8)> {
+        self.memory
+            .nonzero_bytes()
+            .into_iter()
+            .map(|(offset, byte)| (offset + self.dram_base, byte))
+            .collect()
+    }
+
     pub fn read_byte(&mut self, p_address: u64) -> u8 {
         debug_assert!(
-            p_address >= DRAM_BASE,
+            p_address >= self.dram_base,
             "Memory address must equals to or bigger than DRAM_BASE. {:X}",
             p_address
         );
 
-        self.memory.read_byte(p_address - DRAM_BASE)
+        self.memory.read_byte(p_address - self.dram_base)
     }
 
     pub fn read_halfword(&mut self, p_address: u64) -> u16 {
         debug_assert!(
-            p_address >= DRAM_BASE && p_address.wrapping_add(1) >= DRAM_BASE,
+            p_address >= self.dram_base && p_address.wrapping_add(1) >= self.dram_base,
             "Memory address must equals to or bigger than DRAM_BASE. {:X}",
             p_address
         );
 
-        self.memory.read_halfword(p_address - DRAM_BASE)
+        self.memory.read_halfword(p_address - self.dram_base)
     }
 
     pub fn read_word(&mut self, p_address: u64) -> u32 {
         debug_assert!(
-            p_address >= DRAM_BASE && p_address.wrapping_add(3) >= DRAM_BASE,
+            p_address >= self.dram_base && p_address.wrapping_add(3) >= self.dram_base,
             "Memory address must equals to or bigger than DRAM_BASE. {:X}",
             p_address
         );
 
-        self.memory.read_word(p_address - DRAM_BASE)
+        self.memory.read_word(p_address - self.dram_base)
     }
 
     pub fn read_doubleword(&mut self, p_address: u64) -> u64 {
         debug_assert!(
-            p_address >= DRAM_BASE && p_address.wrapping_add(7) >= DRAM_BASE,
+            p_address >= self.dram_base && p_address.wrapping_add(7) >= self.dram_base,
             "Memory address must equals to or bigger than DRAM_BASE. {:X}",
             p_address
         );
 
-        self.memory.read_doubleword(p_address - DRAM_BASE)
+        self.memory.read_doubleword(p_address - self.dram_base)
     }
 
     pub fn write_byte(&mut self, p_address: u64, value: u8) {
         debug_assert!(
-            p_address >= DRAM_BASE,
+            p_address >= self.dram_base,
             "Memory address must equals to or bigger than DRAM_BASE. {:X}",
             p_address
         );
 
-        self.memory.write_byte(p_address - DRAM_BASE, value);
+        self.memory.write_byte(p_address - self.dram_base, value);
     }
 
     pub fn write_halfword(&mut self, p_address: u64, value: u16) {
         debug_assert!(
-            p_address >= DRAM_BASE && p_address.wrapping_add(1) >= DRAM_BASE,
+            p_address >= self.dram_base && p_address.wrapping_add(1) >= self.dram_base,
             "Memory address must equals to or bigger than DRAM_BASE. {:X}",
             p_address
         );
 
-        self.memory.write_halfword(p_address - DRAM_BASE, value);
+        self.memory
+            .write_halfword(p_address - self.dram_base, value);
     }
 
     pub fn write_word(&mut self, p_address: u64, value: u32) {
         debug_assert!(
-            p_address >= DRAM_BASE && p_address.wrapping_add(3) >= DRAM_BASE,
+            p_address >= self.dram_base && p_address.wrapping_add(3) >= self.dram_base,
             "Memory address must equals to or bigger than DRAM_BASE. {:X}",
             p_address
         );
 
-        self.memory.write_word(p_address - DRAM_BASE, value);
+        self.memory.write_word(p_address - self.dram_base, value);
     }
 
     pub fn write_doubleword(&mut self, p_address: u64, value: u64) {
         debug_assert!(
-            p_address >= DRAM_BASE && p_address.wrapping_add(7) >= DRAM_BASE,
+            p_address >= self.dram_base && p_address.wrapping_add(7) >= self.dram_base,
             "Memory address must equals to or bigger than DRAM_BASE. {:X}",
             p_address
         );
 
-        self.memory.write_doubleword(p_address - DRAM_BASE, value);
+        self.memory
+            .write_doubleword(p_address - self.dram_base, value);
     }
 
     pub fn validate_address(&self, address: u64) -> bool {
-        self.memory.validate_address(address - DRAM_BASE)
+        self.memory.validate_address(address - self.dram_base)
     }
 }
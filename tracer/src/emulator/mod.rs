@@ -17,6 +17,7 @@ pub mod terminal;
 use self::cpu::{Cpu, Xlen};
 use self::elf_analyzer::ElfAnalyzer;
 use self::terminal::Terminal;
+use crate::TraceError;
 
 /// RISC-V emulator. It emulates RISC-V CPU and peripheral devices.
 ///
@@ -25,7 +26,7 @@ use self::terminal::Terminal;
 /// // Creates an emulator with arbitary terminal
 /// let mut emulator = Emulator::new(Box::new(DefaultTerminal::new()));
 /// // Set up program content binary
-/// emulator.setup_program(program_content);
+/// emulator.setup_program(program_content).unwrap();
 /// // Set up Filesystem content binary
 /// emulator.setup_filesystem(fs_content);
 /// // Go!
@@ -135,17 +136,16 @@ impl Emulator {
 
     /// Sets up program run by the program. This method analyzes the passed content
     /// and configure CPU properly. If the passed contend doesn't seem ELF file,
-    /// it panics. This method is expected to be called only once.
+    /// it returns `TraceError::InvalidElf`. This method is expected to be called only once.
     ///
     /// # Arguments
     /// * `data` Program binary
     // @TODO: Make ElfAnalyzer and move the core logic there.
-    // @TODO: Returns `Err` if the passed contend doesn't seem ELF file
-    pub fn setup_program(&mut self, data: Vec<u8>) {
+    pub fn setup_program(&mut self, data: Vec<u8>) -> Result<(), TraceError> {
         let analyzer = ElfAnalyzer::new(data);
 
         if !analyzer.validate() {
-            panic!("This file does not seem ELF file");
+            return Err(TraceError::InvalidElf);
         }
 
         let header = analyzer.read_header();
@@ -188,7 +188,7 @@ impl Emulator {
         self.cpu.update_xlen(match header.e_width {
             32 => Xlen::Bit32,
             64 => Xlen::Bit64,
-            _ => panic!("No happen"),
+            _ => return Err(TraceError::InvalidElf),
         });
 
         if self.tohost_addr != 0 {
@@ -199,11 +199,12 @@ impl Emulator {
             self.cpu.get_mut_mmu().init_memory(PROGRAM_MEMORY_CAPACITY);
         }
 
+        let dram_base = self.cpu.get_mut_mmu().dram_base();
         for header in &program_data_section_headers {
             let sh_addr = header.sh_addr;
             let sh_offset = header.sh_offset as usize;
             let sh_size = header.sh_size as usize;
-            if sh_addr >= 0x80000000 && sh_offset > 0 && sh_size > 0 {
+            if sh_addr >= dram_base && sh_offset > 0 && sh_size > 0 {
                 for j in 0..sh_size {
                     self.cpu
                         .get_mut_mmu()
@@ -213,6 +214,7 @@ impl Emulator {
         }
 
         self.cpu.update_pc(header.e_entry);
+        Ok(())
     }
 
     /// Loads symbols of program and adds them to `symbol_map`.
@@ -0,0 +1,80 @@
+use std::fmt::Write;
+
+use thiserror::Error;
+
+use crate::check::UnsupportedInstruction;
+use crate::emulator::cpu::{get_trap_cause, Trap, TrapType, Xlen};
+
+/// Errors that can occur while executing a guest program in the tracer.
+#[derive(Error, Debug)]
+pub enum TraceError {
+    #[error("Failed to read ELF file: {0}")]
+    ElfRead(#[from] std::io::Error),
+    #[error("Invalid ELF file")]
+    InvalidElf,
+    #[error("Cycle limit of {0} exceeded")]
+    CycleLimitExceeded(u64),
+    #[error("Illegal instruction {instruction:#010x} at pc {pc:#x}")]
+    IllegalInstruction { pc: u64, instruction: u64 },
+    #[error("Misaligned memory access to {address:#x} at pc {pc:#x}")]
+    MisalignedAccess { pc: u64, address: u64 },
+    #[error("Out-of-range memory access to {address:#x} at pc {pc:#x}")]
+    OutOfRangeAccess { pc: u64, address: u64 },
+    #[error("Unhandled trap (cause {cause}) at pc {pc:#x}")]
+    UnhandledTrap { pc: u64, cause: u64 },
+    #[error("Guest {}", .0.as_deref().unwrap_or("panicked"))]
+    GuestPanic(Option<String>),
+    #[error("Hint request failed: {0}")]
+    HintFailed(String),
+    #[error("Section {section} ({start:#x}..{end:#x}) lies outside of guest RAM")]
+    SectionOutOfRange {
+        section: String,
+        start: u64,
+        end: u64,
+    },
+    #[error("Entry point {0:#x} lies outside of guest RAM")]
+    EntryOutOfRange(u64),
+    #[error("{} unsupported instruction(s):{}", .0.len(), list_instructions(.0))]
+    UnsupportedInstructions(Vec<UnsupportedInstruction>),
+}
+
+fn list_instructions(instructions: &[UnsupportedInstruction]) -> String {
+    instructions
+        .iter()
+        .fold(String::new(), |mut list, instruction| {
+            let _ = write!(list, "\n  {}", instruction);
+            list
+        })
+}
+
+impl TraceError {
+    /// Converts an exception raised by the emulated CPU while executing the
+    /// instruction at `pc` into a `TraceError`.
+    pub(crate) fn from_trap(trap: &Trap, pc: u64) -> Self {
+        match trap.trap_type {
+            TrapType::IllegalInstruction => TraceError::IllegalInstruction {
+                pc,
+                instruction: trap.value,
+            },
+            TrapType::InstructionAddressMisaligned
+            | TrapType::LoadAddressMisaligned
+            | TrapType::StoreAddressMisaligned => TraceError::MisalignedAccess {
+                pc,
+                address: trap.value,
+            },
+            TrapType::InstructionAccessFault
+            | TrapType::LoadAccessFault
+            | TrapType::StoreAccessFault
+            | TrapType::InstructionPageFault
+            | TrapType::LoadPageFault
+            | TrapType::StorePageFault => TraceError::OutOfRangeAccess {
+                pc,
+                address: trap.value,
+            },
+            _ => TraceError::UnhandledTrap {
+                pc,
+                cause: get_trap_cause(trap, &Xlen::Bit32),
+            },
+        }
+    }
+}
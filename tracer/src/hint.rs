@@ -0,0 +1,49 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// Computes the response to a hint request from the request payload.
+pub type HintProvider = Arc<dyn Fn(&[u8]) -> Vec<u8> + Send + Sync>;
+
+/// The hints a guest can request while it is traced, by name.
+///
+/// A guest requests a hint by writing the hint's name, a zero byte, the request payload and
+/// a terminating zero byte to the hint address, and then loading from the hint service
+/// address. The response is appended to the guest's advice (see
+/// `JoltDevice::push_hint_response`), so it is committed to like any other advice.
+#[derive(Clone, Default)]
+pub struct HintProviders {
+    providers: HashMap<String, HintProvider>,
+}
+
+impl HintProviders {
+    pub fn insert(&mut self, name: &str, provider: HintProvider) {
+        self.providers.insert(name.to_string(), provider);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Computes the response to `request`, as written to the hint address by the guest.
+    pub(crate) fn respond(&self, request: &[u8]) -> Result<Vec<u8>, String> {
+        let request = match request.split_last() {
+            Some((0, request)) => request,
+            _ => return Err("hint request is not terminated".to_string()),
+        };
+        let separator = request
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or_else(|| "hint request has no name".to_string())?;
+        let name = String::from_utf8_lossy(&request[..separator]);
+        let provider = self
+            .providers
+            .get(name.as_ref())
+            .ok_or_else(|| format!("no provider for hint \"{}\"", name))?;
+        Ok(provider(&request[separator + 1..]))
+    }
+}
+
+impl fmt::Debug for HintProviders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.providers.keys()).finish()
+    }
+}
@@ -3,7 +3,6 @@
 
 use std::{fs::File, io::Read, path::PathBuf};
 
-use common::{self, constants::RAM_START_ADDRESS};
 use emulator::{
     cpu::{self, Xlen},
     default_terminal::DefaultTerminal,
@@ -12,81 +11,316 @@ use emulator::{
 
 use object::{Object, ObjectSection, SectionKind};
 
+mod check;
 mod decode;
 mod emulator;
+mod error;
+mod hint;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 mod trace;
 
 pub use common::rv_trace::{
-    ELFInstruction, JoltDevice, MemoryState, RVTraceRow, RegisterState, RV32IM,
+    ELFInstruction, JoltDevice, MachineSnapshot, MemoryConfig, MemoryLayout, MemoryState,
+    RVTraceRow, RegisterState, RV32IM,
 };
 
+pub use crate::check::{check, check_layout, UnsupportedInstruction, UnsupportedKind};
+pub use crate::error::TraceError;
+pub use crate::hint::{HintProvider, HintProviders};
+
 use crate::decode::decode_raw;
 
+/// Executes the guest program in `elf` on `inputs` and private `advice`, and returns its
+/// execution trace along with the resulting I/O device. The guest's hint requests are
+/// serviced by `hints`; if `None`, their responses must already be in `advice`. Execution
+/// is aborted with a `TraceError` if the guest traps or runs for more than `max_cycles`
+/// cycles. A guest panic is not an error: it ends the execution like a normal return, with
+/// `panic` set in the returned device, and is proven as part of the program I/O.
 #[tracing::instrument(skip_all)]
 pub fn trace(
     elf: &PathBuf,
     inputs: &[u8],
-    input_size: u64,
-    output_size: u64,
-) -> (Vec<RVTraceRow>, JoltDevice) {
+    advice: &[u8],
+    memory_layout: &MemoryLayout,
+    hints: Option<&HintProviders>,
+    max_cycles: u64,
+) -> Result<(Vec<RVTraceRow>, JoltDevice), TraceError> {
+    let mut emulator = setup_emulator(elf, inputs, advice, memory_layout, hints)?;
+    execute(&mut emulator, max_cycles, true)?;
+
+    let rows = take_rows(&mut emulator);
+    let device = emulator.get_mut_cpu().get_mut_mmu().jolt_device.clone();
+
+    Ok((rows, device))
+}
+
+/// Executes the guest program like [`trace`], but discards its execution trace as it goes,
+/// so that memory use doesn't grow with the length of the execution. Returns only the
+/// resulting I/O device, e.g. to collect the responses to the guest's hint requests.
+#[tracing::instrument(skip_all)]
+pub fn run(
+    elf: &PathBuf,
+    inputs: &[u8],
+    advice: &[u8],
+    memory_layout: &MemoryLayout,
+    hints: Option<&HintProviders>,
+    max_cycles: u64,
+) -> Result<JoltDevice, TraceError> {
+    let mut emulator = setup_emulator(elf, inputs, advice, memory_layout, hints)?;
+    execute(&mut emulator, max_cycles, false)?;
+
+    Ok(emulator.get_mut_cpu().get_mut_mmu().jolt_device.clone())
+}
+
+/// Runs the emulator until the guest halts, which it signals by writing to the termination
+/// address (see `MemoryLayout::termination`), part of the proven program I/O. Unless
+/// `keep_rows` is set, the execution trace is discarded after every instruction.
+fn execute(emulator: &mut Emulator, max_cycles: u64, keep_rows: bool) -> Result<(), TraceError> {
+    let mut cycles = 0;
+    while !emulator.get_mut_cpu().get_mut_mmu().jolt_device.termination {
+        if cycles == max_cycles {
+            return Err(TraceError::CycleLimitExceeded(max_cycles));
+        }
+        emulator.tick();
+        cycles += 1;
+        if !keep_rows {
+            emulator.get_mut_cpu().tracer.rows.borrow_mut().clear();
+        }
+
+        if let Some(error) = take_error(emulator) {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+/// A contiguous chunk of a guest execution, as produced by [`trace_segments`].
+#[derive(Debug, Clone)]
+pub struct TraceSegment {
+    /// The execution trace of this segment.
+    pub rows: Vec<RVTraceRow>,
+    /// The machine state before the first instruction of this segment.
+    pub initial_state: MachineSnapshot,
+    /// Program I/O at the end of this segment.
+    pub device: JoltDevice,
+}
+
+/// Executes the guest program like [`trace`], but splits the execution into segments of
+/// `segment_length` instructions (see [`SegmentedTrace::exclude_from_boundaries`] for
+/// exceptions). Each segment records the machine state it starts from, so that segments can
+/// be proven independently and chained together.
+///
+/// Segments are produced lazily, so only one segment's trace is held in memory at a time.
+pub fn trace_segments(
+    elf: &PathBuf,
+    inputs: &[u8],
+    advice: &[u8],
+    memory_layout: &MemoryLayout,
+    hints: Option<&HintProviders>,
+    max_cycles: u64,
+    segment_length: u64,
+) -> Result<SegmentedTrace, TraceError> {
+    assert!(segment_length > 0, "segment length must be positive");
+    let emulator = setup_emulator(elf, inputs, advice, memory_layout, hints)?;
+    Ok(SegmentedTrace {
+        emulator,
+        max_cycles,
+        segment_length,
+        excluded_from_boundaries: |_| false,
+        cycles: 0,
+        finished: false,
+    })
+}
+
+/// Iterator over the segments of a guest execution. See [`trace_segments`].
+pub struct SegmentedTrace {
+    emulator: Emulator,
+    max_cycles: u64,
+    segment_length: u64,
+    excluded_from_boundaries: fn(RV32IM) -> bool,
+    cycles: u64,
+    finished: bool,
+}
+
+impl SegmentedTrace {
+    /// Keeps instructions for which `excluded` holds away from segment boundaries: a segment
+    /// neither starts nor ends with one, and instead runs past `segment_length` until the
+    /// next boundary between two other instructions.
+    pub fn exclude_from_boundaries(mut self, excluded: fn(RV32IM) -> bool) -> Self {
+        self.excluded_from_boundaries = excluded;
+        self
+    }
+
+    /// Whether the segment may end before the next instruction.
+    fn at_boundary(&mut self) -> bool {
+        let excluded = self.excluded_from_boundaries;
+        let cpu = self.emulator.get_mut_cpu();
+        let last_excluded = cpu
+            .tracer
+            .rows
+            .borrow()
+            .last()
+            .is_some_and(|row| excluded(row.instruction.opcode));
+        if last_excluded {
+            return false;
+        }
+
+        let pc = cpu.read_pc();
+        let next_opcode = cpu.get_mut_mmu().fetch_word(pc).ok().and_then(|word| {
+            let inst = decode_raw(word).ok()?;
+            inst.trace
+                .map(|trace| trace(&inst, &get_xlen(), word, pc).opcode)
+        });
+        // An undecodable instruction traps, ending the execution anyway.
+        !next_opcode.is_some_and(excluded)
+    }
+
+    fn snapshot(&mut self) -> MachineSnapshot {
+        let xlen = get_xlen();
+        let cpu = self.emulator.get_mut_cpu();
+        let pc = cpu.read_pc();
+        let registers = cpu
+            .x
+            .iter()
+            .map(|value| trace::normalize_register_value(*value, &xlen))
+            .collect();
+        let mmu = cpu.get_mut_mmu();
+        MachineSnapshot {
+            pc,
+            registers,
+            ram: mmu.dump_memory(),
+            device: mmu.jolt_device.clone(),
+        }
+    }
+}
+
+impl Iterator for SegmentedTrace {
+    type Item = Result<TraceSegment, TraceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let initial_state = self.snapshot();
+        let mut segment_cycles = 0;
+        while !self
+            .emulator
+            .get_mut_cpu()
+            .get_mut_mmu()
+            .jolt_device
+            .termination
+            && (segment_cycles < self.segment_length || !self.at_boundary())
+        {
+            if self.cycles == self.max_cycles {
+                self.finished = true;
+                return Some(Err(TraceError::CycleLimitExceeded(self.max_cycles)));
+            }
+            self.emulator.tick();
+            self.cycles += 1;
+            segment_cycles += 1;
+
+            if let Some(error) = take_error(&mut self.emulator) {
+                self.finished = true;
+                return Some(Err(error));
+            }
+        }
+
+        let device = self
+            .emulator
+            .get_mut_cpu()
+            .get_mut_mmu()
+            .jolt_device
+            .clone();
+        if device.termination {
+            self.finished = true;
+        }
+
+        Some(Ok(TraceSegment {
+            rows: take_rows(&mut self.emulator),
+            initial_state,
+            device,
+        }))
+    }
+}
+
+fn setup_emulator(
+    elf: &PathBuf,
+    inputs: &[u8],
+    advice: &[u8],
+    memory_layout: &MemoryLayout,
+    hints: Option<&HintProviders>,
+) -> Result<Emulator, TraceError> {
+    assert!(
+        advice.len() as u64 <= memory_layout.max_advice_size,
+        "advice exceeds the maximum advice size"
+    );
     let term = DefaultTerminal::new();
     let mut emulator = Emulator::new(Box::new(term));
     emulator.update_xlen(get_xlen());
 
-    let mut jolt_device = JoltDevice::new(input_size, output_size);
+    let mut jolt_device = JoltDevice::new(memory_layout.clone());
     jolt_device.inputs = inputs.to_vec();
-    emulator.get_mut_cpu().get_mut_mmu().jolt_device = jolt_device;
+    jolt_device.advice = advice.to_vec();
+    let mmu = emulator.get_mut_cpu().get_mut_mmu();
+    mmu.set_dram_base(memory_layout.ram_start);
+    mmu.jolt_device = jolt_device;
+    mmu.hint_providers = hints.cloned();
 
-    let mut elf_file = File::open(elf).unwrap();
+    let mut elf_file = File::open(elf)?;
 
     let mut elf_contents = Vec::new();
-    elf_file.read_to_end(&mut elf_contents).unwrap();
+    elf_file.read_to_end(&mut elf_contents)?;
 
-    emulator.setup_program(elf_contents);
-
-    let mut prev_pc = 0;
-    loop {
-        let pc = emulator.get_cpu().read_pc();
-        emulator.tick();
-
-        // This is a trick to see if the program has terminated by throwing itself
-        // into an infinite loop. It seems to be a good heuristic for now but we
-        // should eventually migrate to an explicit shutdown signal.
-        if prev_pc == pc {
-            break;
-        }
+    emulator.setup_program(elf_contents)?;
+    Ok(emulator)
+}
 
-        prev_pc = pc;
+/// Returns the error raised by the last executed instruction, if any.
+fn take_error(emulator: &mut Emulator) -> Option<TraceError> {
+    if let Some((trap, pc)) = emulator.get_mut_cpu().take_exception() {
+        return Some(TraceError::from_trap(&trap, pc));
     }
+    emulator
+        .get_mut_cpu()
+        .get_mut_mmu()
+        .take_hint_error()
+        .map(TraceError::HintFailed)
+}
 
+fn take_rows(emulator: &mut Emulator) -> Vec<RVTraceRow> {
     let mut rows = emulator.get_mut_cpu().tracer.rows.try_borrow_mut().unwrap();
     let mut output = Vec::new();
     output.append(&mut rows);
-    drop(rows);
-
-    let device = emulator.get_mut_cpu().get_mut_mmu().jolt_device.clone();
-
-    (output, device)
+    output
 }
 
+/// Decodes the instructions and initial memory of the guest program in `elf`, i.e. of its
+/// sections at or above `ram_start`. Fails with `TraceError::InvalidElf` if `elf` is
+/// malformed.
 #[tracing::instrument(skip_all)]
-pub fn decode(elf: &[u8]) -> (Vec<ELFInstruction>, Vec<(u64, u8)>) {
-    let obj = object::File::parse(elf).unwrap();
+pub fn decode(
+    elf: &[u8],
+    ram_start: u64,
+) -> Result<(Vec<ELFInstruction>, Vec<(u64, u8)>), TraceError> {
+    let obj = object::File::parse(elf).map_err(|_| TraceError::InvalidElf)?;
 
     let sections = obj
         .sections()
-        .filter(|s| s.address() >= RAM_START_ADDRESS)
+        .filter(|s| s.address() >= ram_start)
         .collect::<Vec<_>>();
 
     let mut instructions = Vec::new();
     let mut data = Vec::new();
 
     for section in sections {
-        let raw_data = section.data().unwrap();
+        let raw_data = section.data().map_err(|_| TraceError::InvalidElf)?;
 
         if let SectionKind::Text = section.kind() {
             for (chunk, word) in raw_data.chunks(4).enumerate() {
-                let word = u32::from_le_bytes(word.try_into().unwrap());
+                let word = word.try_into().map_err(|_| TraceError::InvalidElf)?;
+                let word = u32::from_le_bytes(word);
                 let address = chunk as u64 * 4 + section.address();
 
                 if let Ok(inst) = decode_raw(word) {
@@ -104,7 +338,7 @@ pub fn decode(elf: &[u8]) -> (Vec<ELFInstruction>, Vec<(u64, u8)>) {
                     rs2: None,
                     rd: None,
                     imm: None,
-                    virtual_sequence_index: None,
+                    virtual_sequence_remaining: None,
                 });
             }
         }
@@ -114,7 +348,7 @@ pub fn decode(elf: &[u8]) -> (Vec<ELFInstruction>, Vec<(u64, u8)>) {
         }
     }
 
-    (instructions, data)
+    Ok((instructions, data))
 }
 
 fn get_xlen() -> Xlen {
@@ -124,3 +358,224 @@ fn get_xlen() -> Xlen {
         _ => panic!("Emulator only supports 32 / 64 bit registers."),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{elf_with_text, load_byte, store_byte, words, write_elf, SELF_LOOP};
+    use common::constants::MAX_DIAGNOSTICS_SIZE;
+
+    /// Traces `instructions`, placed at the start of RAM, with the default memory layout.
+    fn trace_program(instructions: &[u32]) -> Result<(Vec<RVTraceRow>, JoltDevice), TraceError> {
+        trace_with(
+            instructions,
+            &MemoryConfig::default().memory_layout(),
+            None,
+            1000,
+        )
+    }
+
+    fn trace_with(
+        instructions: &[u32],
+        memory_layout: &MemoryLayout,
+        hints: Option<&HintProviders>,
+        max_cycles: u64,
+    ) -> Result<(Vec<RVTraceRow>, JoltDevice), TraceError> {
+        let ram_start = memory_layout.ram_start as u32;
+        let elf = write_elf(&elf_with_text(ram_start, &words(instructions), ram_start));
+        let result = trace(&elf, &[], &[], memory_layout, hints, max_cycles);
+        std::fs::remove_file(elf).unwrap();
+        result
+    }
+
+    /// A program requesting the hint `name` with `payload`, then terminating.
+    fn hint_program(memory_layout: &MemoryLayout, name: &[u8], payload: &[u8]) -> Vec<u32> {
+        let request = [name, &[0], payload, &[0]].concat();
+        let mut program: Vec<u32> = request
+            .iter()
+            .flat_map(|byte| store_byte(memory_layout.hint, *byte))
+            .collect();
+        program.extend(load_byte(memory_layout.hint_service));
+        program.extend(store_byte(memory_layout.termination, 1));
+        program
+    }
+
+    /// Hint providers answering `repeat` with its payload repeated `count` times.
+    fn repeat_provider(count: usize) -> HintProviders {
+        let mut providers = HintProviders::default();
+        providers.insert(
+            "repeat",
+            std::sync::Arc::new(move |payload: &[u8]| payload.repeat(count)),
+        );
+        providers
+    }
+
+    #[test]
+    fn self_loop_is_not_termination() {
+        // A self-loop used to be taken as the end of the program, truncating the trace
+        assert!(matches!(
+            trace_program(&[SELF_LOOP]),
+            Err(TraceError::CycleLimitExceeded(1000))
+        ));
+    }
+
+    #[test]
+    fn trace_ends_at_termination() {
+        let termination = MemoryConfig::default().memory_layout().termination;
+        let mut program = store_byte(termination, 1);
+        program.push(SELF_LOOP);
+
+        let (rows, device) = trace_program(&program).unwrap();
+        assert!(device.termination);
+        assert!(!device.panic);
+        assert_eq!(rows.len(), 4);
+    }
+
+    #[test]
+    fn console_output_is_captured() {
+        let memory_layout = MemoryConfig::default().memory_layout();
+        let mut program: Vec<u32> = b"hi"
+            .iter()
+            .flat_map(|byte| store_byte(memory_layout.console, *byte))
+            .collect();
+        program.extend(store_byte(memory_layout.termination, 1));
+
+        let (_, device) = trace_program(&program).unwrap();
+        assert_eq!(device.console, b"hi");
+        assert!(device.outputs.is_empty());
+    }
+
+    #[test]
+    fn hint_request_framing() {
+        let providers = repeat_provider(2);
+        assert_eq!(providers.respond(b"repeat\0ab\0").unwrap(), b"abab");
+        assert_eq!(providers.respond(b"repeat\0\0").unwrap(), b"");
+        // The payload may itself contain zero bytes
+        assert_eq!(providers.respond(b"repeat\0a\0\0").unwrap(), b"a\0a\0");
+    }
+
+    #[test]
+    fn malformed_hint_requests_are_rejected() {
+        let providers = repeat_provider(2);
+        assert_eq!(
+            providers.respond(b"repeat\0ab").unwrap_err(),
+            "hint request is not terminated"
+        );
+        assert_eq!(
+            providers.respond(b"").unwrap_err(),
+            "hint request is not terminated"
+        );
+        assert_eq!(
+            providers.respond(b"repeat\0").unwrap_err(),
+            "hint request has no name"
+        );
+        assert_eq!(
+            providers.respond(b"missing\0ab\0").unwrap_err(),
+            "no provider for hint \"missing\""
+        );
+    }
+
+    #[test]
+    fn hint_response_is_appended_to_advice() {
+        let memory_layout = MemoryConfig {
+            max_advice_size: 16,
+            ..Default::default()
+        }
+        .memory_layout();
+        let program = hint_program(&memory_layout, b"repeat", b"ab");
+
+        let (_, device) =
+            trace_with(&program, &memory_layout, Some(&repeat_provider(3)), 1000).unwrap();
+        assert!(device.termination);
+        assert_eq!(device.advice, [&6u32.to_le_bytes()[..], b"ababab"].concat());
+    }
+
+    #[test]
+    fn hint_response_exceeding_advice_size_fails() {
+        let memory_layout = MemoryConfig {
+            max_advice_size: 16,
+            ..Default::default()
+        }
+        .memory_layout();
+        // The length prefix and 13 bytes of response don't fit in 16 bytes of advice
+        let program = hint_program(&memory_layout, b"repeat", b"a");
+        let result = trace_with(&program, &memory_layout, Some(&repeat_provider(13)), 1000);
+        assert!(matches!(
+            result,
+            Err(TraceError::HintFailed(error)) if error == "hint responses exceed the maximum advice size"
+        ));
+
+        // Just enough room
+        let (_, device) =
+            trace_with(&program, &memory_layout, Some(&repeat_provider(12)), 1000).unwrap();
+        assert_eq!(device.advice.len(), 16);
+    }
+
+    #[test]
+    fn failed_hint_request_aborts_trace() {
+        let memory_layout = MemoryConfig {
+            max_advice_size: 16,
+            ..Default::default()
+        }
+        .memory_layout();
+        let program = hint_program(&memory_layout, b"missing", b"a");
+        let result = trace_with(&program, &memory_layout, Some(&repeat_provider(1)), 1000);
+        assert!(matches!(
+            result,
+            Err(TraceError::HintFailed(error)) if error == "no provider for hint \"missing\""
+        ));
+    }
+
+    #[test]
+    fn diagnostics_are_truncated() {
+        let memory_layout = MemoryConfig::default().memory_layout();
+        let count = MAX_DIAGNOSTICS_SIZE as u32 + 16;
+        let (t0, t1, t2) = (5, 6, 7);
+        // Stores `count` bytes to the diagnostics address: the first store sets up t0 and t1
+        let mut program = store_byte(memory_layout.diagnostics, b'x');
+        program.extend([
+            ((count - 1) << 20) | (t2 << 7) | 0x13, // addi t2, zero, count - 1
+            (t1 << 20) | (t0 << 15) | 0x23,         // loop: sb t1, 0(t0)
+            (0xfff << 20) | (t2 << 15) | (t2 << 7) | 0x13, // addi t2, t2, -1
+            0xfe03_9ce3,                            // bne t2, zero, loop
+        ]);
+        program.extend(store_byte(memory_layout.termination, 1));
+
+        let (_, device) = trace_with(&program, &memory_layout, None, 10_000).unwrap();
+        assert!(device.termination);
+        assert_eq!(
+            device.diagnostics,
+            vec![b'x'; MAX_DIAGNOSTICS_SIZE as usize]
+        );
+    }
+
+    #[test]
+    fn decode_rejects_malformed_elf() {
+        let ram_start = MemoryConfig::default().ram_start;
+        assert!(matches!(
+            decode(b"not an elf", ram_start),
+            Err(TraceError::InvalidElf)
+        ));
+
+        // A text section ending in a partial instruction
+        let elf = elf_with_text(
+            ram_start as u32,
+            &[0x13, 0, 0, 0, 0x13, 0],
+            ram_start as u32,
+        );
+        assert!(matches!(
+            decode(&elf, ram_start),
+            Err(TraceError::InvalidElf)
+        ));
+
+        // A section whose data lies beyond the end of the file
+        let mut elf = elf_with_text(ram_start as u32, &words(&[0x13]), ram_start as u32);
+        let shoff = u32::from_le_bytes(elf[32..36].try_into().unwrap()) as usize;
+        let text_sh_offset = shoff + 40 + 16;
+        elf[text_sh_offset..text_sh_offset + 4].copy_from_slice(&0x10000u32.to_le_bytes());
+        assert!(matches!(
+            decode(&elf, ram_start),
+            Err(TraceError::InvalidElf)
+        ));
+    }
+}
@@ -0,0 +1,107 @@
+//! Helpers for building guest programs in tests. Other crates enable them with the
+//! `test-utils` feature.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A minimal 32-bit RISC-V ELF with a single `.text` section holding `text` at `address`.
+pub fn elf_with_text(address: u32, text: &[u8], entry: u32) -> Vec<u8> {
+    let shstrtab = b"\0.text\0.shstrtab\0";
+    let text_offset = 52;
+    let shstrtab_offset = text_offset + text.len();
+    let shoff = (shstrtab_offset + shstrtab.len()).next_multiple_of(4);
+
+    let mut elf = vec![0x7f, b'E', b'L', b'F', 1, 1, 1];
+    elf.resize(16, 0);
+    elf.extend(2u16.to_le_bytes()); // ET_EXEC
+    elf.extend(243u16.to_le_bytes()); // EM_RISCV
+    elf.extend(1u32.to_le_bytes());
+    elf.extend(entry.to_le_bytes());
+    elf.extend(0u32.to_le_bytes()); // No program headers
+    elf.extend((shoff as u32).to_le_bytes());
+    elf.extend(0u32.to_le_bytes());
+    for half in [52u16, 32, 0, 40, 3, 2] {
+        elf.extend(half.to_le_bytes());
+    }
+    elf.extend(text);
+    elf.extend(shstrtab);
+    elf.resize(shoff, 0);
+
+    let section_headers: [[u32; 10]; 3] = [
+        [0; 10],
+        // .text: SHT_PROGBITS, SHF_ALLOC | SHF_EXECINSTR
+        [
+            1,
+            1,
+            6,
+            address,
+            text_offset as u32,
+            text.len() as u32,
+            0,
+            0,
+            2,
+            0,
+        ],
+        // .shstrtab: SHT_STRTAB
+        [
+            7,
+            3,
+            0,
+            0,
+            shstrtab_offset as u32,
+            shstrtab.len() as u32,
+            0,
+            0,
+            1,
+            0,
+        ],
+    ];
+    for field in section_headers.iter().flatten() {
+        elf.extend(field.to_le_bytes());
+    }
+    elf
+}
+
+pub fn words(instructions: &[u32]) -> Vec<u8> {
+    instructions.iter().flat_map(|i| i.to_le_bytes()).collect()
+}
+
+/// Writes `elf` to a fresh file in the temporary directory, returning its path.
+pub fn write_elf(elf: &[u8]) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "tracer-test-{}-{}.elf",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&path, elf).unwrap();
+    path
+}
+
+/// `jal zero, 0`: jumps to itself forever.
+pub const SELF_LOOP: u32 = 0x0000_006f;
+
+/// Instructions storing the byte `value` at `address`, using `t0` and `t1` as scratch.
+pub fn store_byte(address: u64, value: u8) -> Vec<u32> {
+    let (t0, t1) = (5, 6);
+    let upper = ((address as u32).wrapping_add(0x800)) & 0xffff_f000;
+    let lower = (address as u32).wrapping_sub(upper) & 0xfff;
+    vec![
+        upper | (t0 << 7) | 0x37,                      // lui t0, upper
+        (lower << 20) | (t0 << 15) | (t0 << 7) | 0x13, // addi t0, t0, lower
+        ((value as u32) << 20) | (t1 << 7) | 0x13,     // addi t1, zero, value
+        (t1 << 20) | (t0 << 15) | 0x23,                // sb t1, 0(t0)
+    ]
+}
+
+/// Instructions loading the byte at `address`, using `t0` and `t1` as scratch.
+pub fn load_byte(address: u64) -> Vec<u32> {
+    let (t0, t1) = (5, 6);
+    let upper = ((address as u32).wrapping_add(0x800)) & 0xffff_f000;
+    let lower = (address as u32).wrapping_sub(upper) & 0xfff;
+    vec![
+        upper | (t0 << 7) | 0x37,                      // lui t0, upper
+        (lower << 20) | (t0 << 15) | (t0 << 7) | 0x13, // addi t0, t0, lower
+        (t0 << 15) | (t1 << 7) | 0x03,                 // lb t1, 0(t0)
+    ]
+}
@@ -74,7 +74,7 @@ impl Tracer {
     }
 }
 
-fn normalize_register_value(value: i64, xlen: &Xlen) -> u64 {
+pub(crate) fn normalize_register_value(value: i64, xlen: &Xlen) -> u64 {
     match xlen {
         Xlen::Bit32 => value as u32 as u64,
         Xlen::Bit64 => value as u64,